@@ -0,0 +1,76 @@
+//! CSV出力の生成・書き出しパイプラインのベンチマーク
+//!
+//! 全ての乱数列を生成してから並列に書き出す従来の方法（before）と，
+//! 生成と書き出しをチャネルで重ねる[`rand_scenario::gen_norm_rand_csv`]（after）の実行時間を比較する．
+//!
+//! ```zsh
+//! cargo bench --bench pipeline
+//! BENCH_NUM=10000 cargo bench --bench pipeline
+//! ```
+//!
+//! 出力するファイル数は環境変数`BENCH_NUM`（既定は2000）で指定し，
+//! 各方法を`REPEAT`回ずつ交互に実行して実行時間の中央値を表示する．
+
+extern crate process_param;
+extern crate rayon;
+
+use rand_scenario::gen_norm_rand_csv;
+use rand_scenario::norm::RandomScenario;
+use rayon::prelude::*;
+use std::fs::{create_dir_all, remove_dir_all};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// 各方法の実行回数
+const REPEAT: usize = 5;
+
+
+// 従来の方法：全ての乱数列を生成してから，ファイルへ並列に書き出す
+fn generate_then_write(path_scenario: &Path, dir_out: &Path, num: usize) {
+    let scenario = process_param::norm::Scenario::from_toml(&path_scenario).unwrap();
+    create_dir_all(dir_out).unwrap();
+    let randoms = RandomScenario::from_scenario_multiple(&scenario, num).unwrap();
+    randoms.par_iter()
+           .enumerate()
+           .for_each(|(i, r)| r.to_csv(&dir_out.join(format!("test_scenario_{}.csv", i + 1))).unwrap());
+}
+
+// パイプライン：生成と書き出しを重ねる
+fn pipeline(path_scenario: &Path, dir_out: &Path, num: usize) {
+    gen_norm_rand_csv(&path_scenario, &dir_out, num).unwrap();
+}
+
+// 出力先を空にしてから1回実行し，実行時間を返す
+fn time_once<F: Fn(&Path, &Path, usize)>(run: F, path_scenario: &Path, dir_out: &Path, num: usize) -> Duration {
+    remove_dir_all(dir_out).ok();
+    let start = Instant::now();
+    run(path_scenario, dir_out, num);
+    start.elapsed()
+}
+
+fn median(mut times: Vec<Duration>) -> Duration {
+    times.sort();
+    times[times.len() / 2]
+}
+
+fn main() {
+    let num = std::env::var("BENCH_NUM").ok()
+                                        .and_then(|s| s.parse().ok())
+                                        .unwrap_or(2000);
+    let path_scenario = Path::new("test/test_scenario.toml");
+    let dir_before = Path::new("target/bench_pipeline/before");
+    let dir_after = Path::new("target/bench_pipeline/after");
+
+    let mut before = Vec::with_capacity(REPEAT);
+    let mut after = Vec::with_capacity(REPEAT);
+    for _ in 0..REPEAT {
+        before.push(time_once(generate_then_write, path_scenario, dir_before, num));
+        after.push(time_once(pipeline, path_scenario, dir_after, num));
+    }
+    let (before, after) = (median(before), median(after));
+    println!("files: {}, threads: {}", num, rayon::current_num_threads());
+    println!("before (generate all, then write): {:>10.3} ms", before.as_secs_f64() * 1e3);
+    println!("after  (pipeline):                 {:>10.3} ms", after.as_secs_f64() * 1e3);
+    println!("speedup: {:.2}x", before.as_secs_f64() / after.as_secs_f64());
+    remove_dir_all("target/bench_pipeline").ok();
+}
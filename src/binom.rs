@@ -0,0 +1,568 @@
+//! 二項分布に従う乱数生成プログラム
+//!
+//! 合否判定のような計数値のデータを想定し，各時点でn個の製品を検査したときの不適合品数を生成する．
+//! 不適合品数は，Mersenne-Twister法による一様乱数$ U \in [0, 1) $が不適合品率$ p $を下回った個数として生成する．
+//! そのため，seed値が同じであれば同じ乱数列が得られる．
+//!
+//! # シナリオの書式
+//! 正規分布の場合と同様に，区間の終端となる変化点`tau`とその区間での不適合品率`p`を並べる．
+//! 最初の区間の不適合品率を管理状態の不適合品率$ p_0 $とする．
+//!
+//! ```toml
+//! n = 50
+//! parameter = [
+//!     {tau = 15, p = 0.05},
+//!     {tau = 30, p = 0.1},
+//! ]
+//! ```
+
+use super::{GenOptions, ScenarioError, ScenarioErrorKind};
+use super::piecewise;
+
+extern crate serde;
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+use std::fs;
+extern crate toml;
+
+pub use super::norm::Seed;
+
+
+// 最初の変化点以前の乱数列を再生成する回数の上限
+// 管理状態で管理外れ状態を検出する確率が高い場合に，再生成が終わらないことを防ぐ
+const MAX_INCONTROL_RETRIES: usize = 10_000;
+
+
+/// 二項分布に従う乱数生成のシナリオ
+///
+/// # 引数
+/// * `n` - 各時点で検査する個数（サブグループの大きさ）
+/// * `parameter` - 各区間のパラメータ．変化点の昇順に並べる．
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    n: usize,
+    parameter: Vec<ChangePoint>,
+}
+
+/// 二項分布のシナリオにおける1区間
+///
+/// # 引数
+/// * `tau` - 区間の終端となる時点
+/// * `p` - 区間内の不適合品率$ p $
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChangePoint {
+    tau: usize,
+    p: f64,
+}
+
+impl Scenario {
+    /// サンプル・サイズを取得
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// 管理状態の不適合品率$ p_0 $を取得
+    pub fn p_in_control(&self) -> Result<f64, ScenarioError> {
+        match self.parameter.first() {
+            Some(cp) => Ok(cp.p),
+            None => Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Scenario has no parameter.")),
+        }
+    }
+
+    /// TOMLファイルからシナリオを読み込み
+    ///
+    /// # 引数
+    /// * `path` - シナリオが記述されたTOMLファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::binom::Scenario;
+    /// let path = std::path::Path::new("test/test_scenario_binom.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// assert_eq!(scenario.n(), 50);
+    /// assert_eq!(scenario.p_in_control().unwrap(), 0.05);
+    /// ```
+    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+    /// TOML形式の文字列からシナリオを読み込み
+    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(toml::from_str(toml_str)?)
+    }
+
+    /// TOML形式の文字列に変換
+    pub fn to_toml_string(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    /// 各時点の不適合品率を展開
+    ///
+    /// 不適合品率が$ [0, 1] $の範囲外の場合や，変化点が狭義単調増加でない場合はエラーを返す．
+    pub fn decompression(&self) -> Result<Vec<f64>, ScenarioError> {
+        piecewise::expand(&self.parameter, |cp| cp.tau, |cp| {
+            if !(0.0..=1.0).contains(&cp.p) {
+                return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Probability p must be in [0, 1], but p = {}.", cp.p)));
+            }
+            Ok(cp.p)
+        })
+    }
+}
+
+
+/// p管理図の管理限界
+///
+/// # 引数
+/// * `cl` - 中心線（管理状態の不適合品率$ p_0 $）
+/// * `lcl`, `ucl` - 下方・上方管理限界．$ [0, 1] $の範囲に切り詰める．
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControlLimits {
+    pub cl: f64,
+    pub lcl: f64,
+    pub ucl: f64,
+}
+
+impl ControlLimits {
+    /// 不適合品数が管理限界の外にあるか判定
+    ///
+    /// # 引数
+    /// * `count` - 不適合品数
+    /// * `n` - 検査した個数
+    pub fn out_of_control(&self, count: u64, n: usize) -> bool {
+        let p_hat = count as f64 / n as f64;
+        p_hat < self.lcl || p_hat > self.ucl
+    }
+}
+
+
+// 不適合品率`p`の不適合品数が管理限界を外れ得るか
+// 0 < p < 1ならば0からnまでの全ての不適合品数が正の確率で生じるため，両端のみ調べればよい
+fn can_alart(limits: &ControlLimits, n: usize, p: f64) -> bool {
+    let (zero, all) = (limits.out_of_control(0, n), limits.out_of_control(n as u64, n));
+    if p <= 0.0 {
+        zero
+    } else if p >= 1.0 {
+        all
+    } else {
+        zero || all
+    }
+}
+
+
+/// p管理図の管理限界を計算
+///
+/// 管理限界は$ p_0 \pm k \sqrt{p_0 (1 - p_0) / n} $とする．
+///
+/// # 引数
+/// * `scenario` - 乱数生成のシナリオ
+/// * `sigma_mult` - 管理限界の幅$ k $（シグマの倍数）．通常の管理図では3.0．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::binom::{Scenario, control_limits};
+/// let path = std::path::Path::new("test/test_scenario_binom.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let limits = control_limits(&scenario, 3.0).unwrap();
+/// assert_eq!(limits.cl, 0.05);
+/// assert_eq!(limits.lcl, 0.0);
+/// assert!((limits.ucl - (0.05 + 3.0 * (0.05 * 0.95 / 50.0_f64).sqrt())).abs() < 1e-12);
+/// ```
+pub fn control_limits(scenario: &Scenario, sigma_mult: f64) -> Result<ControlLimits, ScenarioError> {
+    let p_0 = scenario.p_in_control()?;
+    if scenario.n() == 0 {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Sample size n must be positive, but n = 0."));
+    }
+    let width = sigma_mult * (p_0 * (1.0 - p_0) / scenario.n() as f64).sqrt();
+    Ok(ControlLimits{ cl: p_0, lcl: (p_0 - width).max(0.0), ucl: (p_0 + width).min(1.0) })
+}
+
+
+/// シナリオを検証
+///
+/// 以下の場合はエラーを返す．
+/// * サンプル・サイズ`n`が正でない
+/// * パラメータ区間が無い
+/// * 変化点が正でない，または狭義単調増加でない
+/// * 不適合品率`p`が$ [0, 1] $の範囲外
+///
+/// # 引数
+/// * `scenario` - 検証するシナリオ
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::binom::{Scenario, validate};
+/// let path = std::path::Path::new("test/test_scenario_binom.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// assert!(validate(&scenario).is_ok());
+///
+/// // 不適合品率が1を超える
+/// let large_p = Scenario::parse_toml_str("n = 50\nparameter = [{tau = 10, p = 1.5}]").unwrap();
+/// assert!(validate(&large_p).is_err());
+///
+/// // パラメータ区間が無い
+/// let empty = Scenario::parse_toml_str("n = 5\nparameter = []").unwrap();
+/// assert!(validate(&empty).is_err());
+/// ```
+pub fn validate(scenario: &Scenario) -> Result<(), ScenarioError> {
+    piecewise::validate_size(scenario.n, scenario.parameter.len())?;
+    scenario.decompression()?;
+    Ok(())
+}
+
+
+/// シナリオから生成した二項乱数を格納
+///
+/// # 引数
+/// * `scenario` - 乱数生成に利用したシナリオ
+/// * `seed` - 乱数生成に利用したシード値
+/// * `random_variables` - 生成された各時点の不適合品数
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RandomScenario {
+    scenario: Scenario,
+    seed: Seed,
+    random_variables: Vec<u64>
+}
+
+extern crate rand;
+use rand::RngCore;
+extern crate rand_mt;
+use rand_mt::Mt64;
+impl RandomScenario {
+    /// 乱数列（各時点の不適合品数）を取得
+    pub fn rand_vars(&self) -> &Vec<u64> {
+        &self.random_variables
+    }
+
+    /// 各時点の不適合品率を取得
+    pub fn proportions(&self) -> Vec<f64> {
+        let n = self.scenario.n() as f64;
+        self.random_variables.iter().map(|c| *c as f64 / n).collect()
+    }
+
+    /// seedを取得
+    pub fn get_seed(&self) -> Seed {
+        self.seed
+    }
+
+
+    /// Scenarioから乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::binom::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_binom.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario);
+    /// println!("{:?}", randoms);
+    /// ```
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, ScenarioError> {
+        Self::from_scenario_with(scenario, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario`]と同様だが，`options.deterministic`が`true`の場合は
+    /// 暗黙のエントロピーでseed値を生成せずにエラーを返す．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::GenOptions;
+    /// # use rand_scenario::binom::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_binom.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_multiple_with(&scenario, 4, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &GenOptions::default()).is_ok());
+    /// ```
+    pub fn from_scenario_with(scenario: &Scenario, options: &GenOptions) -> Result<Self, ScenarioError> {
+        Self::from_scenario_seed(scenario, piecewise::entropy_seed(options.deterministic)?)
+    }
+
+    /// Seedを指定してScenarioから乱数列を生成
+    ///
+    /// 生成の前に[`validate`]でシナリオを検証する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::binom::{Scenario, RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario_binom.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.rand_vars().len(), 40);
+    /// assert!(randoms.rand_vars().iter().all(|c| *c <= 50));
+    /// assert_eq!(randoms, RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap());
+    /// ```
+    pub fn from_scenario_seed(scenario: &Scenario, seed: Seed) -> Result<Self, ScenarioError> {
+        validate(scenario)?;
+        let random_variables = Self::gen_random(&scenario, seed)?;
+        Ok(RandomScenario{ scenario: scenario.clone(), seed, random_variables })
+    }
+
+    // 乱数生成コア
+    fn gen_random(scenario: &Scenario, seed: Seed) -> Result<Vec<u64>, ScenarioError> {
+        let mut rng = Mt64::new(seed.0);
+        let ps = scenario.decompression()?;
+        let n = scenario.n();
+        Ok(ps.iter()
+             .map(|p| rand_binom(&mut rng, n, *p))
+             .collect())
+    }
+
+
+    /// Seedを指定してScenarioからp管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// 管理限界は[`control_limits`]で計算した3シグマの管理限界とする．
+    /// 最初の変化点以前で管理外れ状態を検出した場合には乱数列を再生成する．
+    /// 最後の変化点以降は，最後の区間の不適合品率で乱数生成を続ける．
+    /// 生成の前に[`validate`]でシナリオを検証する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 注意
+    /// 最後の区間の不適合品率では管理外れ状態を検出し得ない場合はエラーを返します．
+    /// 例えば管理限界が$ [0, 1] $全体を覆う場合や，最後の区間の不適合品率が0で下方管理限界も0の場合です．
+    /// 最初の変化点以前の乱数列を上限回数再生成しても管理状態とならない場合は，
+    /// 種類が[`ScenarioErrorKind::RetryExhausted`](crate::ScenarioErrorKind::RetryExhausted)のエラーを返します．
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::binom::{Scenario, RandomScenario, Seed, control_limits};
+    /// let path = std::path::Path::new("test/test_scenario_binom.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap();
+    /// let limits = control_limits(&scenario, 3.0).unwrap();
+    /// // 最後の時点でのみ管理外れ状態を検出する
+    /// let (last, before) = randoms.rand_vars().split_last().unwrap();
+    /// assert!(limits.out_of_control(*last, scenario.n()));
+    /// assert!(before.iter().all(|c| !limits.out_of_control(*c, scenario.n())));
+    ///
+    /// // 最後の区間の不適合品率が0の場合，不適合品数は常に0で下方管理限界（0）を外れない
+    /// let scenario = Scenario::parse_toml_str(r#"
+    /// n = 50
+    /// parameter = [
+    ///     {tau = 15, p = 0.05},
+    ///     {tau = 30, p = 0.0},
+    /// ]
+    /// "#).unwrap();
+    /// assert!(RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).is_err());
+    ///
+    /// // 管理状態でも各時点で約1%の確率で管理外れ状態を検出するため，最初の変化点以前を管理状態のまま生成できない
+    /// let scenario = Scenario::parse_toml_str(r#"
+    /// n = 1
+    /// parameter = [
+    ///     {tau = 3000, p = 0.99},
+    ///     {tau = 3010, p = 0.5},
+    /// ]
+    /// "#).unwrap();
+    /// let err = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap_err();
+    /// assert_eq!(err.kind, rand_scenario::ScenarioErrorKind::RetryExhausted);
+    /// ```
+    pub fn from_scenario_seed_controlchart(scenario: &Scenario, seed: Seed) -> Result<Self, ScenarioError> {
+        validate(scenario)?;
+        let random_variables = Self::gen_random_controlchart(&scenario, seed)?;
+        Ok(RandomScenario{ scenario: scenario.clone(), seed, random_variables })
+    }
+
+    // p管理図が管理外れ状態を検出するまで乱数を生成
+    // 手順は正規分布の場合（norm::RandomScenario::from_scenario_seed_controlchart）と同様
+    fn gen_random_controlchart(scenario: &Scenario, seed: Seed) -> Result<Vec<u64>, ScenarioError> {
+        let mut rng = Mt64::new(seed.0);
+        let ps = scenario.decompression()?;
+        let n = scenario.n();
+        let limits = control_limits(scenario, 3.0)?;
+        // 最後の区間で管理外れ状態を検出し得ない場合，乱数生成が終わらないため予めエラーとする
+        let p_last = ps[ps.len() - 1];
+        if !can_alart(&limits, n, p_last) {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("p chart cannot alart after the last change point (p = {p_last}, n = {n}, limits = [{}, {}]).", limits.lcl, limits.ucl)
+            ));
+        }
+        let inctrl_len = scenario.parameter[0].tau;
+
+        // 管理状態の乱数列
+        let mut randoms: Vec<u64>;
+        let mut retries = 0;
+        loop {
+            randoms = ps[..inctrl_len].iter()
+                                      .map(|p| rand_binom(&mut rng, n, *p))
+                                      .collect();
+            if !randoms.iter().any(|c| limits.out_of_control(*c, n)) {
+                // 管理状態ならば現在のrandomsを利用
+                break;
+            }
+            retries = retries + 1;
+            if retries >= MAX_INCONTROL_RETRIES {
+                return Err(ScenarioError::new(
+                    ScenarioErrorKind::RetryExhausted,
+                    format!("p chart alarts before the first change point in all {retries} attempts (p = {}, n = {n}).", ps[0])
+                ));
+            }
+        }
+
+        // 最後の変化点までの乱数生成
+        for p in ps[inctrl_len..].iter() {
+            let count = rand_binom(&mut rng, n, *p);
+            randoms.push(count);
+            if limits.out_of_control(count, n) {
+                // 管理外れ状態を検出した時点までの乱数を返す
+                return Ok(randoms)
+            }
+        }
+
+        // 最後の区間の不適合品率で，管理外れ状態を検出するまで乱数を生成
+        loop {
+            let count = rand_binom(&mut rng, n, p_last);
+            randoms.push(count);
+            if limits.out_of_control(count, n) {
+                return Ok(randoms)
+            }
+        }
+    }
+
+    /// Scenarioから複数の乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::binom::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_binom.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_multiple(&scenario, 4).unwrap();
+    /// assert_eq!(randoms.len(), 4);
+    /// ```
+    pub fn from_scenario_multiple(scenario: &Scenario, num: usize) -> Result<Vec<Self>, ScenarioError> {
+        Self::from_scenario_multiple_with(scenario, num, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_multiple`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    /// 使用例は[`RandomScenario::from_scenario_with`]を参照．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    pub fn from_scenario_multiple_with(scenario: &Scenario, num: usize, options: &GenOptions) -> Result<Vec<Self>, ScenarioError> {
+        piecewise::gen_multiple(num, options.deterministic, |seed| Self::from_scenario_seed(scenario, seed))
+    }
+
+
+    /// TOMLファイルからRandomScenarioを作成
+    ///
+    /// RandomScenario::to_tomlにより生成されたTOMLファイルを読み込む．
+    ///
+    /// # 引数
+    /// * `path` - 読み込むTOMLファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::binom::{Scenario, RandomScenario};
+    /// let path_scenario = std::path::Path::new("test/test_scenario_binom.toml");
+    /// let path_toml = std::path::Path::new("test/randoms_from_test_scenario_binom.toml");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// // TOMLファイルに保存
+    /// randoms.to_toml(&path_toml).unwrap();
+    /// // TOMLファイルから読み出し
+    /// let rs_read = RandomScenario::from_toml(&path_toml).unwrap();
+    /// assert_eq!(rs_read, randoms);
+    /// ```
+    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+
+    /// TOML形式の文字列からRandScenario読み取り
+    ///
+    /// 未知のフィールドやセクション（`[meta]`等）は無視する．
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::binom::{RandomScenario, Seed};
+    /// let toml_str = r#"
+    /// seed = "7"
+    /// random_variables = [1, 0, 2]
+    ///
+    /// [meta]
+    /// version = "9.9.9"
+    ///
+    /// [scenario]
+    /// n = 50
+    /// parameter = [{tau = 3, p = 0.05}]
+    /// "#;
+    /// let randoms = RandomScenario::parse_toml_str(toml_str).unwrap();
+    /// assert_eq!(randoms.get_seed(), Seed(7));
+    /// assert_eq!(randoms.rand_vars(), &vec![1, 0, 2]);
+    /// ```
+    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (scenario, seed, random_variables) = piecewise::parse_random_toml(toml_str)?;
+        Ok(RandomScenario {scenario, seed, random_variables})
+    }
+
+
+    /// 乱数列をCSVとして出力
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::binom::{Scenario, RandomScenario};
+    /// let path_scenario = std::path::Path::new("test/test_scenario_binom.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_binom.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv(&path_csv).unwrap();
+    /// ```
+    ///
+    /// # 注意: 出力されるCSVファイルの見方
+    ///
+    /// 各行に1時点の不適合品数が並ぶ．
+    /// 列方向（縦）は，時系列の昇順に並んでいる．
+    pub fn to_csv<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        piecewise::write_csv_rows(path, self.rand_vars().iter().map(|count| [count]))
+    }
+
+
+    /// TOML形式の文字列に変換
+    pub fn to_toml_string(&self) -> String {
+        piecewise::random_toml_string(&self.scenario, self.get_seed(), self.rand_vars())
+    }
+
+
+    /// 乱数列をtomlとして出力
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    pub fn to_toml<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        piecewise::write_string(path, &self.to_toml_string())
+    }
+}
+
+
+// 不適合品率pでn個を検査したときの不適合品数を生成
+// 一様乱数がpを下回った個数を数える
+fn rand_binom<R: RngCore>(rng: &mut R, n: usize, p: f64) -> u64 {
+    (0..n).filter(|_| super::rand_uniform(rng) < p).count() as u64
+}
@@ -0,0 +1,242 @@
+//! シナリオを持たない乱数列
+//!
+//! [`norm::RandomScenario`](crate::norm::RandomScenario)は生成に用いたシナリオとseed値（来歴）を常に保持するが，
+//! CSVファイルから読み込んだ乱数列のようにそれらを持たない場合もある．
+//! [`RandomData`]は数値のみを保持し，分析用のメソッドを提供する．
+
+extern crate serde;
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+
+#[cfg(feature = "ndarray")]
+use ndarray::Array2;
+use super::{ScenarioError, ScenarioErrorKind};
+
+use super::norm::RandomScenario;
+
+
+/// シナリオを持たない乱数列
+///
+/// # 引数
+/// * `n` - サンプル・サイズ（最初の時点のもの）
+/// * `random_variables` - 乱数列．`random_variables[t][i]`は時点tにおけるi番目のサンプル．
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RandomData {
+    n: usize,
+    random_variables: Vec<Vec<f64>>,
+}
+
+impl RandomData {
+    /// 乱数列から作成
+    ///
+    /// # 引数
+    /// * `random_variables` - 乱数列．`random_variables[t][i]`は時点tにおけるi番目のサンプル．
+    pub fn new(random_variables: Vec<Vec<f64>>) -> Self {
+        let n = random_variables.first().map_or(0, |rnds| rnds.len());
+        RandomData{ n, random_variables }
+    }
+
+    /// サンプル・サイズを取得
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// 乱数列を取得
+    pub fn rand_vars(&self) -> &Vec<Vec<f64>> {
+        &self.random_variables
+    }
+
+    /// 時点数を取得
+    pub fn num_timepoints(&self) -> usize {
+        self.random_variables.len()
+    }
+
+    /// CSVファイルから乱数列を読み込み
+    ///
+    /// [`RandomScenario::to_csv`]等が出力した，ヘッダを持たないCSVファイルを読み込む．
+    /// 1行が1時点のn個のサンプルに対応する．空欄は欠測値（NaN）として読み込む．
+    ///
+    /// # 引数
+    /// * `path` - 読み込むCSVファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// # use rand_scenario::data::RandomData;
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_data.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// randoms.to_csv(&path_csv).unwrap();
+    /// let data = RandomData::from_csv(&path_csv).unwrap();
+    /// assert_eq!(data, randoms.data());
+    /// ```
+    pub fn from_csv<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut rdr = csv::ReaderBuilder::new()
+                          .has_headers(false)
+                          .flexible(true)
+                          .from_path(path)?;
+        let mut random_variables = Vec::new();
+        for record in rdr.records() {
+            let row = record?.iter()
+                             .map(|field| if field.is_empty() { Ok(f64::NAN) } else { field.parse::<f64>() })
+                             .collect::<Result<Vec<f64>, _>>()?;
+            random_variables.push(row);
+        }
+        Ok(Self::new(random_variables))
+    }
+
+    /// バイナリ形式（`.bin`）のファイルから乱数列を読み込み
+    ///
+    /// [`RandomScenario::to_bin`]が出力したファイル（リトルエンディアンの時点数，サンプル・サイズ，行優先のf64の値）を読み込む．
+    ///
+    /// # 引数
+    /// * `path` - 読み込むファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// # use rand_scenario::data::RandomData;
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_bin = std::path::Path::new("test/randoms_from_test_scenario_data.bin");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// randoms.to_bin(&path_bin).unwrap();
+    /// let data = RandomData::from_bin(&path_bin).unwrap();
+    /// assert_eq!(data, randoms.data());
+    ///
+    /// // 途中で切れたファイルはエラー
+    /// let bytes = std::fs::read(&path_bin).unwrap();
+    /// std::fs::write(&path_bin, &bytes[..bytes.len() - 1]).unwrap();
+    /// assert!(RandomData::from_bin(&path_bin).is_err());
+    ///
+    /// // サンプル・サイズ0で時点数が正のヘッダはエラー
+    /// let header: Vec<u8> = u64::MAX.to_le_bytes().into_iter().chain(0u64.to_le_bytes()).collect();
+    /// std::fs::write(&path_bin, &header).unwrap();
+    /// assert!(RandomData::from_bin(&path_bin).is_err());
+    /// ```
+    pub fn from_bin<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let bytes = std::fs::read(path)?;
+        let invalid = |message: String| ScenarioError::new(ScenarioErrorKind::Parse, message);
+        if bytes.len() < 16 {
+            return Err(Box::new(invalid(format!("Binary file is too short for the header: {} bytes.", bytes.len()))));
+        }
+        let num_timepoints = usize::try_from(u64::from_le_bytes(bytes[..8].try_into()?))?;
+        let n = usize::try_from(u64::from_le_bytes(bytes[8..16].try_into()?))?;
+        // サンプル・サイズ0ではファイルの大きさから時点数を検証できず，巨大な領域の確保を招くため拒否する
+        if n == 0 && num_timepoints > 0 {
+            return Err(Box::new(invalid(format!("Binary file has {} timepoints with n = 0.", num_timepoints))));
+        }
+        let expected = num_timepoints.checked_mul(n)
+                                     .and_then(|len| len.checked_mul(8))
+                                     .and_then(|len| len.checked_add(16));
+        if expected != Some(bytes.len()) {
+            return Err(Box::new(invalid(format!("Binary file size {} doesn't match the header ({} timepoints, n = {}).", bytes.len(), num_timepoints, n))));
+        }
+        let values: Vec<f64> = bytes[16..].chunks_exact(8)
+                                          .map(|b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+                                          .collect();
+        let random_variables = if n == 0 {
+            Vec::new()
+        } else {
+            values.chunks(n).map(|row| row.to_vec()).collect()
+        };
+        Ok(RandomData{ n, random_variables })
+    }
+
+    /// 乱数列を1次元のベクトルに平坦化して取得
+    ///
+    /// 時点の昇順に，各時点のサンプルを連結する（行優先）．
+    pub fn flatten(&self) -> Vec<f64> {
+        self.random_variables.iter().flatten().cloned().collect()
+    }
+
+    /// 各時点の標本平均を計算
+    ///
+    /// 欠測値（NaN）は除いて計算する．全てのサンプルが欠測の時点はNaNとなる．
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::data::RandomData;
+    /// let data = RandomData::new(vec![vec![1.0, 2.0, 3.0], vec![4.0, f64::NAN, 6.0]]);
+    /// assert_eq!(data.sample_means(), vec![2.0, 5.0]);
+    /// ```
+    pub fn sample_means(&self) -> Vec<f64> {
+        self.random_variables.iter()
+            .map(|rnds| sample_mean(rnds))
+            .collect()
+    }
+
+    /// 各時点の標本標準偏差（不偏分散の平方根）を計算
+    ///
+    /// 欠測値（NaN）は除いて計算する．欠測値を除いたサンプルが2個未満の時点はNaNとなる．
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::data::RandomData;
+    /// let data = RandomData::new(vec![vec![1.0, 2.0, 3.0], vec![4.0, 4.0, 4.0]]);
+    /// assert_eq!(data.sample_stddevs(), vec![1.0, 0.0]);
+    /// ```
+    pub fn sample_stddevs(&self) -> Vec<f64> {
+        self.random_variables.iter()
+            .map(|rnds| sample_stddev(rnds))
+            .collect()
+    }
+
+    /// 乱数列を(時点数, サンプル・サイズ)の2次元配列に変換
+    ///
+    /// # 注意
+    /// `ndarray` featureを有効にする必要があります．
+    /// 時点ごとにサンプル・サイズが異なる場合はエラーを返します．
+    ///
+    /// # 使用例
+    /// ```
+    /// # #[cfg(feature = "ndarray")] {
+    /// # use rand_scenario::data::RandomData;
+    /// let data = RandomData::new(vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]]);
+    /// let array = data.to_ndarray().unwrap();
+    /// assert_eq!(array.shape(), &[3, 2]);
+    /// assert_eq!(array[[1, 0]], 3.0);
+    /// assert!(RandomData::new(vec![vec![1.0, 2.0], vec![3.0]]).to_ndarray().is_err());
+    /// # }
+    /// ```
+    #[cfg(feature = "ndarray")]
+    pub fn to_ndarray(&self) -> Result<Array2<f64>, ScenarioError> {
+        Array2::from_shape_vec((self.num_timepoints(), self.n), self.flatten()).map_err(|e| ScenarioError::new(
+            ScenarioErrorKind::Other,
+            format!("Random variables cannot be converted to a 2D array: {e}")
+        ).with_source(e))
+    }
+}
+
+impl From<&RandomScenario> for RandomData {
+    fn from(randoms: &RandomScenario) -> Self {
+        Self::new(randoms.rand_vars().clone())
+    }
+}
+
+impl From<RandomScenario> for RandomData {
+    fn from(randoms: RandomScenario) -> Self {
+        Self::from(&randoms)
+    }
+}
+
+
+// 1時点のサンプル平均．欠測値（NaN）は除き，全てのサンプルが欠測の場合はNaN
+// 管理図の監視統計量（norm.rs）でも同じ計算を用いる
+pub(crate) fn sample_mean(rands: &[f64]) -> f64 {
+    let values: Vec<f64> = rands.iter().cloned().filter(|x| !x.is_nan()).collect();
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// 1時点の不偏標準偏差．欠測値（NaN）は除き，欠測値を除いたサンプルが2個未満の場合はNaN
+pub(crate) fn sample_stddev(rands: &[f64]) -> f64 {
+    let values: Vec<f64> = rands.iter().cloned().filter(|x| !x.is_nan()).collect();
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1.0)).sqrt()
+}
@@ -0,0 +1,346 @@
+//! 指数分布に従う乱数生成プログラム
+//!
+//! 指数分布の乱数は，Mersenne-Twister法による一様乱数$ U \in [0, 1) $を逆関数法
+//! $ X = -\log(1 - U) / \lambda $で変換して生成する．
+//! そのため，seed値が同じであれば同じ乱数列が得られる．
+//!
+//! # シナリオの書式
+//! 正規分布の場合と同様に，区間の終端となる変化点`tau`とその区間での発生率`lambda`を並べる．
+//!
+//! ```toml
+//! n = 10
+//! parameter = [
+//!     {tau = 15, lambda = 1.0},
+//!     {tau = 30, lambda = 2.0},
+//! ]
+//! ```
+
+use super::{GenOptions, ScenarioError, ScenarioErrorKind};
+use super::piecewise;
+
+extern crate serde;
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+use std::fs;
+extern crate toml;
+
+pub use super::norm::Seed;
+
+
+/// 指数分布に従う乱数生成のシナリオ
+///
+/// # 引数
+/// * `n` - サンプル・サイズ
+/// * `parameter` - 各区間のパラメータ．変化点の昇順に並べる．
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    n: usize,
+    parameter: Vec<ChangePoint>,
+}
+
+/// 指数分布のシナリオにおける1区間
+///
+/// # 引数
+/// * `tau` - 区間の終端となる時点
+/// * `lambda` - 区間内の発生率$ \lambda $
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChangePoint {
+    tau: usize,
+    lambda: f64,
+}
+
+impl Scenario {
+    /// サンプル・サイズを取得
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// TOMLファイルからシナリオを読み込み
+    ///
+    /// # 引数
+    /// * `path` - シナリオが記述されたTOMLファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::exp::Scenario;
+    /// let path = std::path::Path::new("test/test_scenario_exp.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// assert_eq!(scenario.n(), 10);
+    /// ```
+    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+    /// TOML形式の文字列からシナリオを読み込み
+    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(toml::from_str(toml_str)?)
+    }
+
+    /// TOML形式の文字列に変換
+    pub fn to_toml_string(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    /// 各時点の発生率を展開
+    ///
+    /// 発生率が正でない場合や，変化点が狭義単調増加でない場合はエラーを返す．
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::exp::Scenario;
+    /// let scenario = Scenario::parse_toml_str("n = 5\nparameter = [{tau = 2, lambda = 1.0}, {tau = 3, lambda = 2.0}]").unwrap();
+    /// assert_eq!(scenario.decompression().unwrap(), vec![1.0, 1.0, 2.0]);
+    ///
+    /// let unordered = Scenario::parse_toml_str("n = 5\nparameter = [{tau = 3, lambda = 1.0}, {tau = 3, lambda = 2.0}]").unwrap();
+    /// assert!(unordered.decompression().is_err());
+    /// ```
+    pub fn decompression(&self) -> Result<Vec<f64>, ScenarioError> {
+        piecewise::expand(&self.parameter, |cp| cp.tau, |cp| {
+            if cp.lambda.is_nan() || cp.lambda <= 0.0 {
+                return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Rate lambda must be positive, but lambda = {}.", cp.lambda)));
+            }
+            Ok(cp.lambda)
+        })
+    }
+}
+
+
+/// シナリオを検証
+///
+/// 以下の場合はエラーを返す．
+/// * サンプル・サイズ`n`が正でない
+/// * パラメータ区間が無い
+/// * 変化点が正でない，または狭義単調増加でない
+/// * 発生率`lambda`が正でない
+///
+/// # 引数
+/// * `scenario` - 検証するシナリオ
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::exp::{Scenario, validate};
+/// let path = std::path::Path::new("test/test_scenario_exp.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// assert!(validate(&scenario).is_ok());
+///
+/// // 発生率が負
+/// let negative_lambda = Scenario::parse_toml_str("n = 5\nparameter = [{tau = 10, lambda = -1.0}]").unwrap();
+/// assert!(validate(&negative_lambda).is_err());
+///
+/// // パラメータ区間が無い
+/// let empty = Scenario::parse_toml_str("n = 5\nparameter = []").unwrap();
+/// assert!(validate(&empty).is_err());
+/// ```
+pub fn validate(scenario: &Scenario) -> Result<(), ScenarioError> {
+    piecewise::validate_size(scenario.n, scenario.parameter.len())?;
+    scenario.decompression()?;
+    Ok(())
+}
+
+
+/// シナリオから生成した指数乱数を格納
+///
+/// # 引数
+/// * `scenario` - 乱数生成に利用したシナリオ
+/// * `seed` - 乱数生成に利用したシード値
+/// * `random_variables` - 生成された乱数列
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RandomScenario {
+    scenario: Scenario,
+    seed: Seed,
+    random_variables: Vec<Vec<f64>>
+}
+
+extern crate rand_mt;
+use rand_mt::Mt64;
+impl RandomScenario {
+    /// 乱数列を取得
+    pub fn rand_vars(&self) -> &Vec<Vec<f64>> {
+        &self.random_variables
+    }
+
+    /// seedを取得
+    pub fn get_seed(&self) -> Seed {
+        self.seed
+    }
+
+
+    /// Scenarioから乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::exp::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_exp.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario);
+    /// println!("{:?}", randoms);
+    /// ```
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, ScenarioError> {
+        Self::from_scenario_with(scenario, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario`]と同様だが，`options.deterministic`が`true`の場合は
+    /// 暗黙のエントロピーでseed値を生成せずにエラーを返す．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::GenOptions;
+    /// # use rand_scenario::exp::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_exp.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_multiple_with(&scenario, 4, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &GenOptions::default()).is_ok());
+    /// ```
+    pub fn from_scenario_with(scenario: &Scenario, options: &GenOptions) -> Result<Self, ScenarioError> {
+        Self::from_scenario_seed(scenario, piecewise::entropy_seed(options.deterministic)?)
+    }
+
+    /// Seedを指定してScenarioから乱数列を生成
+    ///
+    /// 生成の前に[`validate`]でシナリオを検証する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::exp::{Scenario, RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario_exp.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.rand_vars().len(), 40);
+    /// assert!(randoms.rand_vars().iter().flatten().all(|x| *x >= 0.0));
+    /// ```
+    pub fn from_scenario_seed(scenario: &Scenario, seed: Seed) -> Result<Self, ScenarioError> {
+        validate(scenario)?;
+        let random_variables = Self::gen_random(&scenario, seed)?;
+        Ok(RandomScenario{ scenario: scenario.clone(), seed, random_variables })
+    }
+
+    // 乱数生成コア
+    fn gen_random(scenario: &Scenario, seed: Seed) -> Result<Vec<Vec<f64>>, ScenarioError> {
+        let mut rng = Mt64::new(seed.0);
+        let lambdas = scenario.decompression()?;
+        let n = scenario.n();
+        Ok(lambdas.iter()
+                  .map(|lambda| (0..n).map(|_| -(1.0 - super::rand_uniform(&mut rng)).ln() / lambda)
+                                      .collect())
+                  .collect())
+    }
+
+    /// Scenarioから複数の乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::exp::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_exp.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_multiple(&scenario, 4).unwrap();
+    /// assert_eq!(randoms.len(), 4);
+    /// ```
+    pub fn from_scenario_multiple(scenario: &Scenario, num: usize) -> Result<Vec<Self>, ScenarioError> {
+        Self::from_scenario_multiple_with(scenario, num, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_multiple`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    /// 使用例は[`RandomScenario::from_scenario_with`]を参照．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    pub fn from_scenario_multiple_with(scenario: &Scenario, num: usize, options: &GenOptions) -> Result<Vec<Self>, ScenarioError> {
+        piecewise::gen_multiple(num, options.deterministic, |seed| Self::from_scenario_seed(scenario, seed))
+    }
+
+
+    /// TOMLファイルからRandomScenarioを作成
+    ///
+    /// RandomScenario::to_tomlにより生成されたTOMLファイルを読み込む．
+    ///
+    /// # 引数
+    /// * `path` - 読み込むTOMLファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::exp::{Scenario, RandomScenario};
+    /// let path_scenario = std::path::Path::new("test/test_scenario_exp.toml");
+    /// let path_toml = std::path::Path::new("test/randoms_from_test_scenario_exp.toml");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// // TOMLファイルに保存
+    /// randoms.to_toml(&path_toml).unwrap();
+    /// // TOMLファイルから読み出し
+    /// let rs_read = RandomScenario::from_toml(&path_toml).unwrap();
+    /// assert_eq!(rs_read, randoms);
+    /// ```
+    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+
+    /// TOML形式の文字列からRandScenario読み取り
+    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (scenario, seed, random_variables) = piecewise::parse_random_toml(toml_str)?;
+        Ok(RandomScenario {scenario, seed, random_variables})
+    }
+
+
+    /// 乱数列をCSVとして出力
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::exp::{Scenario, RandomScenario};
+    /// let path_scenario = std::path::Path::new("test/test_scenario_exp.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_exp.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv(&path_csv).unwrap();
+    /// ```
+    ///
+    /// # 注意: 出力されるCSVファイルの見方
+    ///
+    /// 行方向（横）に同一時点でのn個のサンプルが並ぶ．
+    /// 列方向（縦）は，時系列の昇順に並んでいる．
+    pub fn to_csv<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        piecewise::write_csv_rows(path, self.rand_vars())
+    }
+
+
+    /// TOML形式の文字列に変換
+    pub fn to_toml_string(&self) -> String {
+        piecewise::random_toml_string(&self.scenario, self.get_seed(), self.rand_vars())
+    }
+
+
+    /// 乱数列をtomlとして出力
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    pub fn to_toml<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        piecewise::write_string(path, &self.to_toml_string())
+    }
+}
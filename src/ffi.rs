@@ -0,0 +1,119 @@
+//! C言語から利用するためのインターフェース（C ABI）
+//!
+//! `ffi` featureを有効にして共有ライブラリ（cdylib）としてビルドすると，次の関数を呼び出せます．
+//! Cargo.tomlでは`crate-type`を指定していないため，ビルド時に指定してください．
+//!
+//! > cargo rustc --release --lib --features ffi --crate-type cdylib
+//!
+//! ```c
+//! double *rs_generate(const char *scenario_path, uint64_t seed, size_t *out_len, size_t *out_cols);
+//! void rs_free(double *ptr, size_t len);
+//! ```
+//!
+//! # メモリの所有権
+//! [`rs_generate`]が返すバッファはRust側で確保したメモリです．
+//! 利用後は必ず[`rs_free`]に同じポインタと`out_len`に書き込まれた長さを渡して解放してください．
+//! C言語の`free`で解放したり，2回以上解放したりしてはいけません．
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::catch_unwind;
+use std::path::Path;
+
+use super::norm::{self, RandomScenario, Seed};
+
+
+/// シナリオのファイルから乱数列を生成し，平坦化したバッファを返す
+///
+/// [`RandomScenario::from_scenario_seed`]で生成した乱数列を[`RandomScenario::flatten`]により
+/// 行優先（時点の昇順に各時点のn個のサンプルを連結）で平坦化して返す．
+/// 時点tのi番目のサンプルはバッファの`t * n + i`番目の要素となる．
+///
+/// # 引数
+/// * `scenario_path` - シナリオのファイルのパス（NUL終端のUTF-8文字列）．拡張子の扱いは[`norm::load_scenario`]と同じ．
+/// * `seed` - 乱数生成に用いるseed値
+/// * `out_len` - バッファの要素数の書き込み先
+/// * `out_cols` - 1時点あたりのサンプル・サイズnの書き込み先．不要であればNULLを指定できる．時点数は`out_len / out_cols`となる．
+///
+/// # 注意
+/// 返り値のバッファは[`rs_free`]で解放すること．
+/// シナリオの読み込みや乱数生成に失敗した場合，およびRust側でパニックが発生した場合はNULLを返し，
+/// `out_len`（と`out_cols`）には0を書き込む．
+/// `scenario_path`または`out_len`がNULLの場合もNULLを返す．
+///
+/// # Safety
+/// `scenario_path`は有効なNUL終端文字列，`out_len`と`out_cols`は書き込み可能な`usize`へのポインタ（`out_cols`はNULLも可）であること．
+///
+/// # 使用例
+/// ```
+/// # #[cfg(feature = "ffi")] {
+/// # use rand_scenario::ffi::{rs_generate, rs_free};
+/// # use rand_scenario::norm::{RandomScenario, Seed};
+/// # use process_param::norm::Scenario;
+/// let path = std::ffi::CString::new("test/test_scenario.toml").unwrap();
+/// let mut len = 0;
+/// let mut cols = 0;
+/// let ptr = unsafe { rs_generate(path.as_ptr(), 42, &mut len, &mut cols) };
+/// assert!(!ptr.is_null());
+/// assert_eq!((len / cols, cols), (40, 10));
+///
+/// // 同じseed値で生成した乱数列を平坦化したものと一致する
+/// let scenario = Scenario::from_toml(&std::path::Path::new("test/test_scenario.toml")).unwrap();
+/// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+/// assert_eq!(unsafe { std::slice::from_raw_parts(ptr, len) }, &randoms.flatten()[..]);
+/// unsafe { rs_free(ptr, len) };
+///
+/// // 存在しないファイルはNULL
+/// let missing = std::ffi::CString::new("test/not_exist.toml").unwrap();
+/// assert!(unsafe { rs_generate(missing.as_ptr(), 42, &mut len, std::ptr::null_mut()) }.is_null());
+/// assert_eq!(len, 0);
+/// # }
+/// ```
+#[no_mangle]
+pub unsafe extern "C" fn rs_generate(scenario_path: *const c_char, seed: u64, out_len: *mut usize, out_cols: *mut usize) -> *mut f64 {
+    if scenario_path.is_null() || out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    *out_len = 0;
+    if !out_cols.is_null() {
+        *out_cols = 0;
+    }
+    let path = match CStr::from_ptr(scenario_path).to_str() {
+        Ok(path) => path.to_string(),
+        Err(_) => return std::ptr::null_mut(),
+    };
+    // パニックがFFIの境界を越えないようにする
+    let result = catch_unwind(|| -> Result<(Vec<f64>, usize), Box<dyn std::error::Error>> {
+        let scenario = norm::load_scenario(&Path::new(&path))?;
+        let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(seed))?;
+        let (flat, _num_timepoints, n) = randoms.flatten_with_shape();
+        Ok((flat, n))
+    });
+    match result {
+        Ok(Ok((flat, n))) => {
+            *out_len = flat.len();
+            if !out_cols.is_null() {
+                *out_cols = n;
+            }
+            Box::into_raw(flat.into_boxed_slice()) as *mut f64
+        },
+        _ => std::ptr::null_mut(),
+    }
+}
+
+
+/// [`rs_generate`]が返したバッファを解放
+///
+/// # 引数
+/// * `ptr` - [`rs_generate`]が返したポインタ．NULLの場合は何もしない．
+/// * `len` - [`rs_generate`]が`out_len`に書き込んだ要素数
+///
+/// # Safety
+/// `ptr`と`len`は[`rs_generate`]から得たものをそのまま渡し，1度だけ解放すること．
+#[no_mangle]
+pub unsafe extern "C" fn rs_free(ptr: *mut f64, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
+}
@@ -0,0 +1,380 @@
+//! ガンマ分布に従う乱数生成プログラム
+//!
+//! 作業時間のような正の値をとり右に裾の長いデータを想定する．
+//! ガンマ乱数はMarsaglia-Tsang法で生成する．
+//! 同法が用いる標準正規乱数（Box-Muller法，[`crate::uniform_stream`]を参照）と一様乱数には，
+//! 同じMersenne-Twister法の乱数生成器を用いるため，seed値が同じであれば同じ乱数列が得られる．
+//!
+//! # シナリオの書式
+//! 正規分布の場合と同様に，区間の終端となる変化点`tau`とその区間での形状母数`shape`，尺度母数`scale`を並べる．
+//! 平均は`shape * scale`，分散は`shape * scale^2`となる．
+//!
+//! ```toml
+//! n = 10
+//! parameter = [
+//!     {tau = 15, shape = 2.0, scale = 1.0},
+//!     {tau = 30, shape = 2.0, scale = 1.5},
+//! ]
+//! ```
+
+use super::{GenOptions, ScenarioError, ScenarioErrorKind};
+use super::piecewise;
+
+extern crate serde;
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+use std::fs;
+extern crate toml;
+
+extern crate process_param;
+
+pub use super::norm::Seed;
+
+
+/// ガンマ分布に従う乱数生成のシナリオ
+///
+/// # 引数
+/// * `n` - サンプル・サイズ
+/// * `parameter` - 各区間のパラメータ．変化点の昇順に並べる．
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    n: usize,
+    parameter: Vec<ChangePoint>,
+}
+
+/// ガンマ分布のシナリオにおける1区間
+///
+/// # 引数
+/// * `tau` - 区間の終端となる時点
+/// * `shape` - 区間内の形状母数$ k $
+/// * `scale` - 区間内の尺度母数$ \theta $
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChangePoint {
+    tau: usize,
+    shape: f64,
+    scale: f64,
+}
+
+impl Scenario {
+    /// サンプル・サイズを取得
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// TOMLファイルからシナリオを読み込み
+    ///
+    /// # 引数
+    /// * `path` - シナリオが記述されたTOMLファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::gamma::Scenario;
+    /// let path = std::path::Path::new("test/test_scenario_gamma.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// assert_eq!(scenario.n(), 10);
+    /// ```
+    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+    /// TOML形式の文字列からシナリオを読み込み
+    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(toml::from_str(toml_str)?)
+    }
+
+    /// TOML形式の文字列に変換
+    pub fn to_toml_string(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    /// 各時点の(形状母数, 尺度母数)を展開
+    ///
+    /// 形状母数または尺度母数が正でない場合や，変化点が狭義単調増加でない場合はエラーを返す．
+    pub fn decompression(&self) -> Result<Vec<(f64, f64)>, ScenarioError> {
+        piecewise::expand(&self.parameter, |cp| cp.tau, |cp| {
+            if cp.shape.is_nan() || cp.shape <= 0.0 {
+                return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Shape must be positive, but shape = {}.", cp.shape)));
+            }
+            if cp.scale.is_nan() || cp.scale <= 0.0 {
+                return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Scale must be positive, but scale = {}.", cp.scale)));
+            }
+            Ok((cp.shape, cp.scale))
+        })
+    }
+}
+
+
+/// シナリオを検証
+///
+/// 以下の場合はエラーを返す．
+/// * サンプル・サイズ`n`が正でない
+/// * パラメータ区間が無い
+/// * 変化点が正でない，または狭義単調増加でない
+/// * 形状母数`shape`または尺度母数`scale`が正でない
+///
+/// # 引数
+/// * `scenario` - 検証するシナリオ
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::gamma::{Scenario, validate};
+/// let path = std::path::Path::new("test/test_scenario_gamma.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// assert!(validate(&scenario).is_ok());
+///
+/// // 形状母数が0
+/// let zero_shape = Scenario::parse_toml_str("n = 5\nparameter = [{tau = 10, shape = 0.0, scale = 1.0}]").unwrap();
+/// assert!(validate(&zero_shape).is_err());
+///
+/// // パラメータ区間が無い
+/// let empty = Scenario::parse_toml_str("n = 5\nparameter = []").unwrap();
+/// assert!(validate(&empty).is_err());
+/// ```
+pub fn validate(scenario: &Scenario) -> Result<(), ScenarioError> {
+    piecewise::validate_size(scenario.n, scenario.parameter.len())?;
+    scenario.decompression()?;
+    Ok(())
+}
+
+
+/// シナリオから生成したガンマ乱数を格納
+///
+/// # 引数
+/// * `scenario` - 乱数生成に利用したシナリオ
+/// * `seed` - 乱数生成に利用したシード値
+/// * `random_variables` - 生成された乱数列
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RandomScenario {
+    scenario: Scenario,
+    seed: Seed,
+    random_variables: Vec<Vec<f64>>
+}
+
+extern crate rand;
+use rand::RngCore;
+extern crate rand_mt;
+use rand_mt::Mt64;
+impl RandomScenario {
+    /// 乱数列を取得
+    pub fn rand_vars(&self) -> &Vec<Vec<f64>> {
+        &self.random_variables
+    }
+
+    /// seedを取得
+    pub fn get_seed(&self) -> Seed {
+        self.seed
+    }
+
+
+    /// Scenarioから乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::gamma::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_gamma.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario);
+    /// println!("{:?}", randoms);
+    /// ```
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, ScenarioError> {
+        Self::from_scenario_with(scenario, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario`]と同様だが，`options.deterministic`が`true`の場合は
+    /// 暗黙のエントロピーでseed値を生成せずにエラーを返す．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::GenOptions;
+    /// # use rand_scenario::gamma::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_gamma.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_multiple_with(&scenario, 4, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &GenOptions::default()).is_ok());
+    /// ```
+    pub fn from_scenario_with(scenario: &Scenario, options: &GenOptions) -> Result<Self, ScenarioError> {
+        Self::from_scenario_seed(scenario, piecewise::entropy_seed(options.deterministic)?)
+    }
+
+    /// Seedを指定してScenarioから乱数列を生成
+    ///
+    /// 生成の前に[`validate`]でシナリオを検証する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::gamma::{Scenario, RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario_gamma.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.rand_vars().len(), 40);
+    /// assert!(randoms.rand_vars().iter().flatten().all(|x| *x > 0.0));
+    ///
+    /// // 形状母数が正でないシナリオは生成時にエラーとなる
+    /// let invalid = Scenario::parse_toml_str("n = 5\nparameter = [{tau = 10, shape = 0.0, scale = 1.0}]").unwrap();
+    /// assert!(RandomScenario::from_scenario_seed(&invalid, Seed(42)).is_err());
+    ///
+    /// // 標本平均は平均shape * scaleに近い
+    /// let many = Scenario::parse_toml_str("n = 10000\nparameter = [{tau = 1, shape = 2.0, scale = 1.5}]").unwrap();
+    /// let xs = &RandomScenario::from_scenario_seed(&many, Seed(1)).unwrap().rand_vars()[0];
+    /// let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+    /// assert!((mean - 3.0).abs() < 0.1);
+    /// ```
+    pub fn from_scenario_seed(scenario: &Scenario, seed: Seed) -> Result<Self, ScenarioError> {
+        validate(scenario)?;
+        let random_variables = Self::gen_random(&scenario, seed)?;
+        Ok(RandomScenario{ scenario: scenario.clone(), seed, random_variables })
+    }
+
+    // 乱数生成コア
+    fn gen_random(scenario: &Scenario, seed: Seed) -> Result<Vec<Vec<f64>>, ScenarioError> {
+        let mut rng = Mt64::new(seed.0);
+        let params = scenario.decompression()?;
+        let n = scenario.n();
+        Ok(params.iter()
+                 .map(|(shape, scale)| (0..n).map(|_| scale * rand_gamma(&mut rng, *shape))
+                                             .collect())
+                 .collect())
+    }
+
+    /// Scenarioから複数の乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::gamma::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_gamma.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_multiple(&scenario, 4).unwrap();
+    /// assert_eq!(randoms.len(), 4);
+    /// ```
+    pub fn from_scenario_multiple(scenario: &Scenario, num: usize) -> Result<Vec<Self>, ScenarioError> {
+        Self::from_scenario_multiple_with(scenario, num, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_multiple`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    /// 使用例は[`RandomScenario::from_scenario_with`]を参照．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    pub fn from_scenario_multiple_with(scenario: &Scenario, num: usize, options: &GenOptions) -> Result<Vec<Self>, ScenarioError> {
+        piecewise::gen_multiple(num, options.deterministic, |seed| Self::from_scenario_seed(scenario, seed))
+    }
+
+
+    /// TOMLファイルからRandomScenarioを作成
+    ///
+    /// RandomScenario::to_tomlにより生成されたTOMLファイルを読み込む．
+    ///
+    /// # 引数
+    /// * `path` - 読み込むTOMLファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::gamma::{Scenario, RandomScenario};
+    /// let path_scenario = std::path::Path::new("test/test_scenario_gamma.toml");
+    /// let path_toml = std::path::Path::new("test/randoms_from_test_scenario_gamma.toml");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// // TOMLファイルに保存
+    /// randoms.to_toml(&path_toml).unwrap();
+    /// // TOMLファイルから読み出し
+    /// let rs_read = RandomScenario::from_toml(&path_toml).unwrap();
+    /// assert_eq!(rs_read, randoms);
+    /// ```
+    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+
+    /// TOML形式の文字列からRandScenario読み取り
+    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (scenario, seed, random_variables) = piecewise::parse_random_toml(toml_str)?;
+        Ok(RandomScenario {scenario, seed, random_variables})
+    }
+
+
+    /// 乱数列をCSVとして出力
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::gamma::{Scenario, RandomScenario};
+    /// let path_scenario = std::path::Path::new("test/test_scenario_gamma.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_gamma.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv(&path_csv).unwrap();
+    /// ```
+    ///
+    /// # 注意: 出力されるCSVファイルの見方
+    ///
+    /// 行方向（横）に同一時点でのn個のサンプルが並ぶ．
+    /// 列方向（縦）は，時系列の昇順に並んでいる．
+    pub fn to_csv<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        piecewise::write_csv_rows(path, self.rand_vars())
+    }
+
+
+    /// TOML形式の文字列に変換
+    pub fn to_toml_string(&self) -> String {
+        piecewise::random_toml_string(&self.scenario, self.get_seed(), self.rand_vars())
+    }
+
+
+    /// 乱数列をtomlとして出力
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    pub fn to_toml<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        piecewise::write_string(path, &self.to_toml_string())
+    }
+}
+
+
+// 形状母数shape，尺度母数1のガンマ乱数をMarsaglia-Tsang法で生成
+// shape < 1の場合は，shape + 1のガンマ乱数にU^(1 / shape)を掛けて生成する
+fn rand_gamma<R: RngCore>(rng: &mut R, shape: f64) -> f64 {
+    if shape < 1.0 {
+        let u = super::rand_uniform(rng);
+        return rand_gamma(rng, shape + 1.0) * u.powf(1.0 / shape);
+    }
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let x = super::rand_std_norm_n(rng, 1)[0];
+        let v = (1.0 + c * x).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u = super::rand_uniform(rng);
+        if u < 1.0 - 0.0331 * x.powi(4) || u.ln() < 0.5 * x * x + d * (1.0 - v + v.ln()) {
+            return d * v;
+        }
+    }
+}
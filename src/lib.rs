@@ -37,18 +37,149 @@
 //! 引数等は変更しなくても動くはずです．
 
 pub mod norm;
+pub mod pois;
+pub mod exp;
+pub mod mvnorm;
+pub mod binom;
+pub mod gamma;
+pub mod data;
+mod piecewise;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+// 並列処理の切り替え
+// `parallel` featureが有効な場合はrayonの並列イテレータを利用する．
+#[cfg(feature = "parallel")]
+mod par {
+    extern crate rayon;
+    pub(crate) use rayon::prelude::*;
+}
+
+// `parallel` featureが無効な場合は，`par_iter`を逐次処理のイテレータとして提供する．
+// 呼び出し側のコードは並列・逐次のどちらでも同一．
+#[cfg(not(feature = "parallel"))]
+mod par {
+    pub(crate) trait IntoParallelRefIterator<'a> {
+        type Iter: Iterator;
+        fn par_iter(&'a self) -> Self::Iter;
+    }
+
+    impl<'a, T: 'a> IntoParallelRefIterator<'a> for [T] {
+        type Iter = std::slice::Iter<'a, T>;
+        fn par_iter(&'a self) -> Self::Iter {
+            self.iter()
+        }
+    }
+}
 
 use std;
 use std::fmt;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufReader, BufWriter, Write};
 
 /// シナリオに関するエラー
+///
+/// # 引数
+/// * `kind` - エラーの種類
+/// * `source` - このエラーの原因となったエラー（TOMLの構文エラー等）．[`std::error::Error::source`]で取得できる．
+///   エラーを複製できるよう`Arc`で保持する．
+/// * `message` - エラーの内容．表示（`Display`）にはこの内容のみを用いる．
+///
+/// # 使用例
+/// ```
+/// # use std::error::Error;
+/// # use rand_scenario::norm::parse_json_str;
+/// let err = parse_json_str("{").unwrap_err();
+/// assert!(err.to_string().starts_with("Invalid JSON scenario"));
+/// // 原因となったJSONの構文エラーを辿れる
+/// assert!(err.source().unwrap().downcast_ref::<serde_json::Error>().is_some());
+///
+/// // Send + Syncであるため，ワーカースレッドで発生したエラーをパニックさせずに呼び出し元へ返せる
+/// let worker = std::thread::spawn(|| parse_json_str("{"));
+/// let err = worker.join().expect("worker thread panicked").unwrap_err();
+/// assert!(err.to_string().starts_with("Invalid JSON scenario"));
+/// ```
 #[derive(Debug, Clone)]
 pub struct ScenarioError {
+    pub kind: ScenarioErrorKind,
+    pub source: Option<Arc<dyn std::error::Error + Send + Sync>>,
     pub message: String,
 }
 
+/// [`ScenarioError`]の種類
+///
+/// * `Parse` - シナリオやseed値等の読み込みに失敗した
+/// * `OutOfRange` - パラメータや引数が取り得る範囲外である
+/// * `RngFailure` - 乱数生成（生成した乱数からの推定を含む）に失敗した
+/// * `RetryExhausted` - 管理図が上限の時点数までに管理外れ状態を検出しない等，試行の上限に達した
+/// * `Timeout` - 管理図が制限時間内に管理外れ状態を検出しなかった
+/// * `Other` - その他（[`process_param`]クレートのエラーを含む）
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::ScenarioErrorKind;
+/// # use rand_scenario::norm::{parse_json_str, RandomScenario, Seed};
+/// assert_eq!(parse_json_str("{").unwrap_err().kind, ScenarioErrorKind::Parse);
+///
+/// let scenario = Scenario::from_toml(&std::path::Path::new("test/test_scenario.toml")).unwrap();
+/// let err = RandomScenario::from_scenario_seed_controlchart_capped(&scenario, Seed(42), 1).unwrap_err();
+/// assert_eq!(err.kind, ScenarioErrorKind::RetryExhausted);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScenarioErrorKind {
+    Parse,
+    OutOfRange,
+    RngFailure,
+    RetryExhausted,
+    Timeout,
+    Other,
+}
+
+impl ScenarioError {
+    /// 原因となるエラーを持たない[`ScenarioError`]を作成
+    ///
+    /// # 引数
+    /// * `kind` - エラーの種類
+    /// * `message` - エラーメッセージ
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::{ScenarioError, ScenarioErrorKind};
+    /// use std::error::Error;
+    /// let err = ScenarioError::new(ScenarioErrorKind::OutOfRange, "n must be positive.");
+    /// assert_eq!(err.kind, ScenarioErrorKind::OutOfRange);
+    /// assert_eq!(err.to_string(), "n must be positive.");
+    /// assert!(err.source().is_none());
+    /// ```
+    pub fn new(kind: ScenarioErrorKind, message: impl Into<String>) -> Self {
+        ScenarioError { kind, source: None, message: message.into() }
+    }
+
+    /// 原因となったエラーを付与
+    ///
+    /// 付与したエラーは[`std::error::Error::source`]で辿れる．
+    ///
+    /// # 引数
+    /// * `source` - 原因となったエラー
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::{ScenarioError, ScenarioErrorKind};
+    /// use std::error::Error;
+    /// let e = "x".parse::<u64>().unwrap_err();
+    /// let err = ScenarioError::new(ScenarioErrorKind::Parse, format!("Invalid seed: {e}")).with_source(e);
+    /// assert!(err.source().unwrap().downcast_ref::<std::num::ParseIntError>().is_some());
+    /// ```
+    pub fn with_source<E: std::error::Error + Send + Sync + 'static>(mut self, source: E) -> Self {
+        self.source = Some(Arc::new(source));
+        self
+    }
+}
+
 impl fmt::Display for ScenarioError {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         write!(f, "{}", self.message)
@@ -59,221 +190,2312 @@ impl std::error::Error for ScenarioError {
     fn description(&self) -> &str {
         &self.message
     }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
+
+// エラーは並列処理のワーカースレッドから返されるため，Send + Syncであることをコンパイル時に保証する
+const _: () = {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    #[allow(dead_code)]
+    fn assert_all() {
+        assert_send_sync::<ScenarioError>();
+        assert_send_sync::<ScenarioErrorKind>();
+        assert_send_sync::<process_param::ScenarioError>();
+        assert_send_sync::<DirGenResult>();
+    }
+};
+
+impl From<process_param::ScenarioError> for ScenarioError {
+    fn from(e: process_param::ScenarioError) -> Self {
+        ScenarioError::new(ScenarioErrorKind::Other, e.message.clone()).with_source(e)
+    }
+}
+
+impl From<ScenarioError> for process_param::ScenarioError {
+    fn from(e: ScenarioError) -> Self {
+        process_param::ScenarioError { message: e.message }
+    }
+}
+
+
+// 出力先のディレクトリを作成できなかった場合のエラー
+// 既存のディレクトリを指定した場合等
+fn dir_out_error<P: AsRef<Path>>(dir_out: &P, e: std::io::Error) -> ScenarioError {
+    ScenarioError::new(ScenarioErrorKind::Other, format!("{:?}: {}", dir_out.as_ref(), e)).with_source(e)
+}
+
+
+// seed値の生成に用いるエントロピー由来の乱数生成器
+// `deterministic`が`true`の場合（[`GenOptions::deterministic`]）は暗黙のエントロピーを利用せずにエラーを返す
+pub(crate) fn entropy_rng(deterministic: bool) -> Result<rand::rngs::ThreadRng, ScenarioError> {
+    if deterministic {
+        Err(ScenarioError::new(ScenarioErrorKind::Other, "Deterministic mode is enabled: an explicit seed is required."))
+    } else {
+        Ok(rand::thread_rng())
+    }
+}
+
+// エントロピー由来の乱数生成器から`num`個のseed値を生成
+// `deterministic`の扱いは`entropy_rng`と同じ
+pub(crate) fn entropy_seeds(num: usize, deterministic: bool) -> Result<Vec<norm::Seed>, ScenarioError> {
+    let mut rng_for_seed = entropy_rng(deterministic)?;
+    Ok((0..num).map(|_| norm::Seed(rng_for_seed.next_u64())).collect())
 }
 
 use std::path::{Path,PathBuf};
-use std::fs::create_dir;
-extern crate rayon;
-use rayon::prelude::*;
+use std::fs::{create_dir, create_dir_all};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicBool, Ordering};
+use par::*;
 extern crate serde;
-use serde::Serialize;
+use serde::{Serialize, Deserialize};
 extern crate process_param;
+extern crate rand;
+use rand::RngCore;
+/// シナリオから複数の乱数列をファイルに書き出さずに生成
+///
+/// [`norm::RandomScenario::from_scenario_multiple`]をクレート直下から利用するための関数．
+/// 生成前にシナリオを[`norm::validate`]で検証する．
+///
+/// # 引数
+/// * `scenario` - 乱数生成に用いるシナリオ
+/// * `num` - 生成する乱数列の個数
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::generate_batch;
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let randoms = generate_batch(&scenario, 4).unwrap();
+/// assert_eq!(randoms.len(), 4);
+/// ```
+pub fn generate_batch(scenario: &process_param::norm::Scenario, num: usize) -> Result<Vec<norm::RandomScenario>, ScenarioError> {
+    norm::validate(scenario)?;
+    Ok(norm::RandomScenario::from_scenario_multiple(scenario, num)?)
+}
+
+
+/// 管理図を併用した複数の乱数列をファイルに書き出さずに生成
+///
+/// [`norm::RandomScenario::from_scenario_controlchart_multiple`]をクレート直下から利用するための関数．
+/// 生成前にシナリオを[`norm::validate`]で検証する．
+///
+/// # 引数
+/// * `scenario` - 乱数生成に用いるシナリオ
+/// * `num` - 生成する乱数列の個数
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::generate_batch_controlchart;
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let randoms = generate_batch_controlchart(&scenario, 4).unwrap();
+/// assert_eq!(randoms.len(), 4);
+/// ```
+pub fn generate_batch_controlchart(scenario: &process_param::norm::Scenario, num: usize) -> Result<Vec<norm::RandomScenario>, ScenarioError> {
+    norm::validate(scenario)?;
+    Ok(norm::RandomScenario::from_scenario_controlchart_multiple(scenario, num)?)
+}
+
+
 /// 生成した乱数列を指定した個数分csvファイルで出力
 ///
 /// # 引数
-/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLまたはJSONファイルのパス
 /// * `dir_out`- 出力するディレクトリ名
 /// * `num` - 出力するファイルの個数
 /// 
 /// # 注意
 /// 出力ファイルは「シナリオ名_番号.csv」となります．  
 /// また，各乱数生成に用いたseed値は「seed.txt」に記録します．
+/// シナリオは拡張子が`.toml`ならTOML形式，`.json`ならJSON形式として読み込みます（[`norm::load_scenario`]）．
+/// 返り値は，出力した各ファイルのパスとseed値の組をファイル番号順に並べたものです．
+/// 他の`gen_*`関数も同様の値を返します．
 /// 
 /// # 使用例
 /// ```
 /// # use rand_scenario::gen_norm_rand_csv;
+/// # use rand_scenario::norm::RandomScenario;
 /// # use std::path::Path;
-/// # use std::fs::remove_dir_all;
+/// # use std::fs::{remove_dir_all, read_to_string};
 /// let path_scenario = Path::new("test/test_scenario.toml");
 /// let dir_out = Path::new("test/gen_norm_rand_csv");
 /// # remove_dir_all(dir_out.clone()).ok();
-/// gen_norm_rand_csv(&path_scenario, &dir_out, 10).unwrap();
+/// let seeds = gen_norm_rand_csv(&path_scenario, &dir_out, 10).unwrap();
+/// assert_eq!(seeds.len(), 10);
+/// assert_eq!(seeds[0].0, dir_out.join("test_scenario_1.csv"));
+/// // seed値から出力ファイルを再現
+/// # extern crate process_param;
+/// let scenario = process_param::norm::Scenario::from_toml(&path_scenario).unwrap();
+/// let path_again = Path::new("test/randoms_from_test_scenario_seed.csv");
+/// RandomScenario::from_scenario_seed(&scenario, seeds[0].1).unwrap().to_csv(&path_again).unwrap();
+/// assert_eq!(read_to_string(&seeds[0].0).unwrap(), read_to_string(&path_again).unwrap());
+///
+/// // JSON形式のシナリオ
+/// let dir_out_json = Path::new("test/gen_norm_rand_csv_json");
+/// # remove_dir_all(dir_out_json.clone()).ok();
+/// let seeds = gen_norm_rand_csv(&Path::new("test/test_scenario.json"), &dir_out_json, 3).unwrap();
+/// assert_eq!(seeds[0].0, dir_out_json.join("test_scenario_1.csv"));
 /// ```
-pub fn gen_norm_rand_csv<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let scenario = process_param::norm::Scenario::from_toml(path_scenario)?;
-    // ファイルパスの準備
-    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
-    if let Err(e) = create_dir(dir_out) {
-        panic!("{:?}: {}", dir_out.as_ref(), e)
-    }
-    let dir_out_ref = dir_out.as_ref();
-    let csvs: Vec<PathBuf> = (1..num+1).collect::<Vec<usize>>()
-                                       .par_iter()
-                                       .map(|i| dir_out_ref.join(Path::new(&format!("{}_{}.csv",filename, i))))
-                                       .collect();
-
-    // seed値の記録用
-    let mut wtr = csv::Writer::from_path(
-                      dir_out.as_ref().join(Path::new("seed.txt"))
-                  )?;
-    #[derive(Serialize)]
-    struct SeedRecord {
-        file: String,
-        seed: norm::Seed,
+pub fn gen_norm_rand_csv<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_norm_rand_csv_with(path_scenario, dir_out, num, &GenOptions::default())
+}
+
+
+/// CSVファイルの出力方法の設定
+///
+/// # 引数
+/// * `gzip` - CSVファイルをgzipで圧縮して「シナリオ名_番号.csv.gz」として出力する（`gzip` featureが必要）
+/// * `with_manifest` - 出力した全ファイルの一覧を「manifest.json」に記録する
+/// * `zero_pad` - ファイル番号を出力するファイルの個数の桁数まで0で埋める（例：「シナリオ名_0001.csv」）
+/// * `filename_template` - 出力ファイル名の書式．`{stem}`（シナリオ名），`{index}`（ファイル番号），`{ext}`（拡張子）を置き換える．
+///   `None`の場合は`{stem}_{index}.{ext}`となる．`{index}`を含み，パスの区切り文字（`/`，`\`）を含まない必要がある．
+/// * `seed_file` - seed値を記録するファイル名．`None`の場合は`seed_format`に応じて「seed.txt」（CSV形式）または「seed.json」（JSON形式）となる．
+/// * `seed_format` - seed値を記録するファイルの形式
+/// * `control_limit_file` - 管理限界を記録するファイル名．`None`の場合は「controlLimit.txt」となる．
+/// * `resume` - 中断した出力を再開する．出力先のディレクトリが既に存在してもよく，
+///   末尾まで書き出されたファイルがありseed値が記録済みの番号は生成を省略する．
+///   再開する場合に限り，seed値は書き出しを終えたファイルから順に「seed値のファイル名.partial」へ追記するため，
+///   プロセスが異常終了してseed値のファイルが出力されなかった場合も再開できる．
+/// * `with_checksums` - 出力した各ファイルのSHA-256を「checksums.txt」に記録する（`checksum` featureが必要）
+/// * `scenario_parallelism` - [`gen_norm_rand_csv_dir_with`]で複数のシナリオを同時に生成する際のスレッド数．
+///   各シナリオ内の並列処理も同じスレッドで行うため，全体のスレッド数の上限となる（ただし同時に生成するシナリオごとに書き出し用のスレッドを1本使う）．
+///   `None`の場合はrayonの既定（CPUのコア数）となる．`parallel` featureが無効な場合は無視する．
+/// * `deterministic` - 決定的モード．`true`の場合，seed値を暗黙のエントロピー（`rand::thread_rng`）で生成せずにエラーを返す．
+///   テストやCIで再現できない乱数生成が紛れ込むことを防ぐために利用する．
+///   設定はこの`GenOptions`を渡した呼び出しにのみ適用され，他のスレッドの呼び出しには影響しない．
+///
+/// # 使用例
+/// 決定的モードでは，seed値を暗黙のエントロピーで生成する出力関数はいずれもエラーを返す．
+/// ```
+/// # use rand_scenario::*;
+/// # use std::path::{Path, PathBuf};
+/// # use std::fs::remove_dir_all;
+/// type Gen<'a> = Box<dyn Fn(&Path) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> + 'a>;
+/// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+/// let norm = Path::new("test/test_scenario.toml");
+/// let pois = Path::new("test/test_scenario_pois.toml");
+/// let exp = Path::new("test/test_scenario_exp.toml");
+/// let binom = Path::new("test/test_scenario_binom.toml");
+/// let gamma = Path::new("test/test_scenario_gamma.toml");
+/// let generators: Vec<Gen> = vec![
+///     Box::new(|dir| gen_norm_rand_csv_with(&norm, &dir, 3, &options)),
+///     Box::new(|dir| gen_norm_rand_toml_with(&norm, &dir, 3, &options)),
+///     Box::new(|dir| gen_norm_rand_controlchart_csv_with(&norm, &dir, 3, &options)),
+///     Box::new(|dir| gen_norm_rand_controlchart_csv_sigma_with(&norm, &dir, 3, 2.5, &options)),
+///     Box::new(|dir| gen_norm_rand_controlchart_toml_with(&norm, &dir, 3, &options)),
+///     Box::new(|dir| gen_pois_rand_csv_with(&pois, &dir, 3, &options)),
+///     Box::new(|dir| gen_exp_rand_csv_with(&exp, &dir, 3, &options)),
+///     Box::new(|dir| gen_binom_rand_csv_with(&binom, &dir, 3, &options)),
+///     Box::new(|dir| gen_gamma_rand_csv_with(&gamma, &dir, 3, &options)),
+///     Box::new(|dir| gen_gamma_rand_toml_with(&gamma, &dir, 3, &options)),
+/// ];
+/// for (i, generate) in generators.iter().enumerate() {
+///     let dir = PathBuf::from(format!("test/gen_options_deterministic_{i}"));
+/// #   remove_dir_all(&dir).ok();
+///     assert!(generate(&dir).is_err());
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GenOptions {
+    pub gzip: bool,
+    pub with_manifest: bool,
+    pub zero_pad: bool,
+    pub filename_template: Option<String>,
+    pub seed_file: Option<String>,
+    pub seed_format: SeedFormat,
+    pub control_limit_file: Option<String>,
+    pub resume: bool,
+    pub with_checksums: bool,
+    pub scenario_parallelism: Option<usize>,
+    pub deterministic: bool,
+}
+
+
+/// seed値を記録するファイルの形式
+///
+/// * `Csv` - `file,seed`の見出しを持つCSV形式
+/// * `Json` - `file`と`seed`を持つオブジェクトの配列からなるJSON形式
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SeedFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl GenOptions {
+    // 出力ファイルの拡張子
+    fn extension(&self) -> &'static str {
+        if self.gzip { "csv.gz" } else { "csv" }
     }
 
-    let randoms = norm::RandomScenario::from_scenario_multiple(&scenario, num)?;
-    for (r, fb) in randoms.iter().zip(csvs.iter()) {
-        r.to_csv(fb)?;
-        wtr.serialize( SeedRecord {file: fb.to_str().unwrap().to_string(), seed: r.get_seed()})?;
+    // 出力ファイル名
+    fn file_name(&self, stem: &str, index: usize, num: usize, ext: &str) -> String {
+        let index = if self.zero_pad {
+            format!("{:0width$}", index, width = num.to_string().len())
+        } else {
+            index.to_string()
+        };
+        self.filename_template.as_deref()
+                              .unwrap_or("{stem}_{index}.{ext}")
+                              .replace("{stem}", stem)
+                              .replace("{index}", &index)
+                              .replace("{ext}", ext)
+    }
+
+    // 出力するcsvファイルのパス（ファイル番号順）
+    fn output_paths<P: AsRef<Path>>(&self, dir_out: &P, filename: &str, num: usize) -> Vec<PathBuf> {
+        self.output_paths_ext(dir_out, filename, num, self.extension())
+    }
+
+    // 拡張子を指定した出力ファイルのパス（ファイル番号順）
+    fn output_paths_ext<P: AsRef<Path>>(&self, dir_out: &P, filename: &str, num: usize, ext: &str) -> Vec<PathBuf> {
+        let dir_out_ref = dir_out.as_ref();
+        (1..num+1).collect::<Vec<usize>>()
+                  .par_iter()
+                  .map(|i| dir_out_ref.join(Path::new(&self.file_name(filename, *i, num, ext))))
+                  .collect()
+    }
+
+    // seed値を記録するファイル名．指定しない場合は形式に応じた既定の名前とする
+    fn seed_file_name(&self) -> &str {
+        match (&self.seed_file, self.seed_format) {
+            (Some(name), _) => name,
+            (None, SeedFormat::Csv) => "seed.txt",
+            (None, SeedFormat::Json) => "seed.json",
+        }
+    }
+
+    // seed値を記録するファイルのパス
+    fn seed_path<P: AsRef<Path>>(&self, dir_out: &P) -> PathBuf {
+        dir_out.as_ref().join(Path::new(self.seed_file_name()))
+    }
+
+    // 出力先のディレクトリを作成．再開する場合は既存のディレクトリを許す
+    fn create_dir_out<P: AsRef<Path>>(&self, dir_out: &P) -> Result<(), ScenarioError> {
+        let result = if self.resume { create_dir_all(dir_out) } else { create_dir(dir_out) };
+        result.map_err(|e| dir_out_error(dir_out, e))
+    }
+
+    // 記録済みのseed値を読み込み．ファイルが無い場合は空とする
+    fn read_seed<P: AsRef<Path>>(&self, dir_out: &P) -> Result<Vec<SeedRecord>, Box<dyn std::error::Error>> {
+        let path = self.seed_path(dir_out);
+        if !path.exists() {
+            return Ok(Vec::new())
+        }
+        match self.seed_format {
+            SeedFormat::Csv => {
+                let mut rdr = csv::Reader::from_path(path)?;
+                Ok(rdr.deserialize().collect::<Result<Vec<SeedRecord>, csv::Error>>()?)
+            },
+            SeedFormat::Json => Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?),
+        }
+    }
+
+    // 書き出しを終えたファイルのseed値を逐次記録するファイルのパス
+    fn journal_path<P: AsRef<Path>>(&self, dir_out: &P) -> PathBuf {
+        let mut path = self.seed_path(dir_out).into_os_string();
+        path.push(".partial");
+        PathBuf::from(path)
+    }
+
+    // 出力済みのファイルのseed値の記録と，これから出力するファイルのパスに分ける
+    // 末尾まで書き出されたファイルが存在し，かつseed値が記録済みの場合のみ出力済みとみなす
+    // seed値のファイルに加え，異常終了時に残った逐次記録も読み込む．逐次記録の方が新しいため優先する
+    // 記録済みでもファイルを出力し直す場合は，そのseed値を重複して数えないよう記録から除く
+    fn split_done<P: AsRef<Path>>(&self, dir_out: &P, paths: &[PathBuf]) -> Result<(Vec<SeedRecord>, Vec<PathBuf>), Box<dyn std::error::Error>> {
+        if !self.resume {
+            return Ok((Vec::new(), paths.to_vec()))
+        }
+        let mut existing: HashMap<String, (norm::Seed, Option<usize>)> = self.read_seed(dir_out)?
+                                                                            .into_iter()
+                                                                            .map(|r| (r.file, (r.seed, None)))
+                                                                            .collect();
+        existing.extend(SeedJournal::read(&self.journal_path(dir_out))?
+                            .into_iter()
+                            .map(|r| (r.file, (r.seed, Some(r.rows)))));
+        let mut done = Vec::new();
+        let mut pending = Vec::new();
+        for path in paths.iter() {
+            let file = path.to_str().unwrap().to_string();
+            match existing.get(&file) {
+                Some((seed, rows)) if is_complete_csv(path, *rows) => done.push(SeedRecord { file, seed: *seed }),
+                _ => pending.push(path.clone()),
+            }
+        }
+        Ok((done, pending))
+    }
+
+    // 逐次記録を開く．既存の記録に追記する
+    // 再開しない場合は逐次記録を作成せず，ファイルごとのロックとフラッシュも行わない
+    fn open_journal<P: AsRef<Path>>(&self, dir_out: &P) -> Result<SeedJournal, Box<dyn std::error::Error>> {
+        if self.resume {
+            SeedJournal::open(self.journal_path(dir_out))
+        } else {
+            Ok(SeedJournal::disabled())
+        }
+    }
+
+    // seed値の記録を出力
+    fn wtr_seed<P: AsRef<Path>>(&self, dir_out: &P, records: &[SeedRecord]) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.seed_path(dir_out);
+        self.write_seed_records(BufWriter::new(File::create(path)?), records)
+    }
+
+    // seed値の記録を任意の出力先に書き込む
+    fn write_seed_records<W: Write>(&self, writer: W, records: &[SeedRecord]) -> Result<(), Box<dyn std::error::Error>> {
+        match self.seed_format {
+            SeedFormat::Csv => {
+                let mut wtr = csv::Writer::from_writer(writer);
+                for record in records.iter() {
+                    wtr.serialize(record)?;
+                }
+                wtr.flush()?;
+            },
+            SeedFormat::Json => {
+                let mut wtr = writer;
+                serde_json::to_writer_pretty(&mut wtr, records)?;
+                wtr.flush()?;
+            },
+        }
+        Ok(())
+    }
+
+    // 設定の検証．ファイル名が重複しないよう，書式は`{index}`を含む必要がある
+    // 出力先のディレクトリの外に書き出さないよう，書式はパスの区切り文字を含んではならない
+    fn validate(&self) -> Result<(), ScenarioError> {
+        if self.scenario_parallelism == Some(0) {
+            return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Scenario parallelism must be at least 1."));
+        }
+        match &self.filename_template {
+            Some(template) if !template.contains("{index}") => Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Filename template must contain {{index}}, but template = {:?}.", template)
+            )),
+            Some(template) if template.contains('/') || template.contains('\\') => Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Filename template must not contain path separators, but template = {:?}.", template)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // 設定に従って乱数列を1つのファイルに書き出す
+    fn write_csv(&self, randoms: &norm::RandomScenario, path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+        if self.gzip {
+            write_csv_gz(randoms, path)
+        } else {
+            randoms.to_csv(path)
+        }
     }
-    wtr.flush()?;
-    Ok(())
 }
 
 
-/// 生成した乱数列を指定した個数分tomlファイルで出力
+#[cfg(feature = "gzip")]
+fn write_csv_gz(randoms: &norm::RandomScenario, path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    randoms.to_csv_gz(path)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn write_csv_gz(_randoms: &norm::RandomScenario, _path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    Err(Box::new(ScenarioError::new(ScenarioErrorKind::Other, "gzip output requires the `gzip` feature.")))
+}
+
+
+/// 出力方法を指定して，生成した乱数列を指定した個数分csvファイルで出力
 ///
 /// # 引数
 /// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
 /// * `dir_out`- 出力するディレクトリ名
 /// * `num` - 出力するファイルの個数
-/// 
+/// * `options` - 出力方法の設定
+///
 /// # 注意
-/// 出力ファイルは「シナリオ名_番号.toml」となります．  
-/// 
+/// 出力ファイルは[`gen_norm_rand_csv`]と同じです．
+/// ただし，`options.gzip`が`true`の場合は「シナリオ名_番号.csv.gz」となります．
+/// `options.zero_pad`が`true`の場合は，番号を`num`の桁数まで0で埋めます（例：`num = 1000`のとき「シナリオ名_0001.csv」）．
+/// `options.filename_template`を指定した場合は，その書式に従ったファイル名となります．
+/// seed値の記録は`options.seed_file`のファイル名で，`options.seed_format`の形式で出力します．
+/// `options.with_manifest`が`true`の場合は，全ファイルの一覧を「manifest.json」に記録します．
+/// `options.resume`が`true`の場合は，出力済みのファイルを残して残りのファイルのみ出力し，seed値の記録に追記します．
+/// 途中までしか書き出されていないファイル（改行で終わらない，または行数が足りない）は出力し直します．
+/// このとき「manifest.json」には今回出力したファイルのみを記録します．
+/// `options.with_checksums`が`true`の場合は，各ファイルのSHA-256を「checksums.txt」に記録します．
+/// 記録したチェックサムは[`verify_checksums`]で検証できます．
+///
 /// # 使用例
 /// ```
-/// # use rand_scenario::gen_norm_rand_toml;
+/// # use rand_scenario::{gen_norm_rand_csv_with, GenOptions, SeedFormat};
 /// # use std::path::Path;
 /// # use std::fs::remove_dir_all;
 /// let path_scenario = Path::new("test/test_scenario.toml");
-/// let dir_out = Path::new("test/gen_norm_rand_toml");
+/// let dir_out = Path::new("test/gen_norm_rand_csv_with");
 /// # remove_dir_all(dir_out.clone()).ok();
-/// gen_norm_rand_toml(&path_scenario, &dir_out, 10).unwrap();
+/// let options = GenOptions { with_manifest: true, ..GenOptions::default() };
+/// gen_norm_rand_csv_with(&path_scenario, &dir_out, 10, &options).unwrap();
+/// assert!(dir_out.join("manifest.json").exists());
+///
+/// // ファイル番号を0で埋める
+/// let dir_out_pad = Path::new("test/gen_norm_rand_csv_with_zero_pad");
+/// # remove_dir_all(dir_out_pad.clone()).ok();
+/// let options = GenOptions { zero_pad: true, ..GenOptions::default() };
+/// gen_norm_rand_csv_with(&path_scenario, &dir_out_pad, 12, &options).unwrap();
+/// assert!(dir_out_pad.join("test_scenario_01.csv").exists());
+/// assert!(dir_out_pad.join("test_scenario_12.csv").exists());
+///
+/// // seed値をJSON形式で記録
+/// let dir_out_json = Path::new("test/gen_norm_rand_csv_with_seed_json");
+/// # remove_dir_all(dir_out_json.clone()).ok();
+/// let options = GenOptions { seed_file: Some("mysc_seeds.json".to_string()), seed_format: SeedFormat::Json, ..GenOptions::default() };
+/// gen_norm_rand_csv_with(&path_scenario, &dir_out_json, 3, &options).unwrap();
+/// let seeds: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir_out_json.join("mysc_seeds.json")).unwrap()).unwrap();
+/// assert_eq!(seeds.as_array().unwrap().len(), 3);
+/// // ファイル名を指定しない場合は「seed.json」
+/// let dir_out_json_default = Path::new("test/gen_norm_rand_csv_with_seed_json_default");
+/// # remove_dir_all(dir_out_json_default.clone()).ok();
+/// let options = GenOptions { seed_format: SeedFormat::Json, ..GenOptions::default() };
+/// gen_norm_rand_csv_with(&path_scenario, &dir_out_json_default, 3, &options).unwrap();
+/// assert!(dir_out_json_default.join("seed.json").exists());
+/// assert!(!dir_out_json_default.join("seed.txt").exists());
+///
+/// // 中断した出力を再開
+/// let dir_out_resume = Path::new("test/gen_norm_rand_csv_with_resume");
+/// # remove_dir_all(dir_out_resume.clone()).ok();
+/// let options = GenOptions { resume: true, ..GenOptions::default() };
+/// let first = gen_norm_rand_csv_with(&path_scenario, &dir_out_resume, 5, &options).unwrap();
+/// std::fs::remove_file(dir_out_resume.join("test_scenario_2.csv")).unwrap();
+/// let second = gen_norm_rand_csv_with(&path_scenario, &dir_out_resume, 5, &options).unwrap();
+/// assert_eq!(second.len(), 5);
+/// assert_eq!(first[0], second[0]);
+/// assert!(dir_out_resume.join("test_scenario_2.csv").exists());
+/// let rdr = csv::Reader::from_path(dir_out_resume.join("seed.txt")).unwrap();
+/// assert_eq!(rdr.into_records().count(), 5);
+///
+/// // 末尾まで書き出されていないファイルは出力し直す
+/// let path_3 = dir_out_resume.join("test_scenario_3.csv");
+/// let content = std::fs::read_to_string(&path_3).unwrap();
+/// let lines: Vec<&str> = content.lines().collect();
+/// std::fs::write(&path_3, lines[..lines.len() - 1].join("\n")).unwrap();
+/// let third = gen_norm_rand_csv_with(&path_scenario, &dir_out_resume, 5, &options).unwrap();
+/// assert_eq!(second[0], third[0]);
+/// assert_ne!(second[2], third[2]);
+///
+/// // seed値のファイルを出力する前に異常終了した場合も，逐次記録から再開できる
+/// std::fs::remove_file(dir_out_resume.join("seed.txt")).unwrap();
+/// let rows = std::fs::read_to_string(dir_out_resume.join("test_scenario_1.csv")).unwrap().lines().count();
+/// let journal = format!("{},{},{}\n", third[0].0.to_str().unwrap(), third[0].1.0, rows);
+/// std::fs::write(dir_out_resume.join("seed.txt.partial"), journal).unwrap();
+/// let fourth = gen_norm_rand_csv_with(&path_scenario, &dir_out_resume, 5, &options).unwrap();
+/// assert_eq!(third[0], fourth[0]);
+/// assert_ne!(third[1], fourth[1]);
+/// assert!(!dir_out_resume.join("seed.txt.partial").exists());
 /// ```
-pub fn gen_norm_rand_toml<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let scenario = process_param::norm::Scenario::from_toml(path_scenario)?;
-    // ファイルパスの準備
+pub fn gen_norm_rand_csv_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = norm::load_scenario(path_scenario)?;
     let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
-    if let Err(e) = create_dir(dir_out) {
-        panic!("{:?}: {}", dir_out.as_ref(), e)
-    }
-    let dir_out_ref = dir_out.as_ref();
-    let csvs: Vec<PathBuf> = (1..num+1).collect::<Vec<usize>>()
-                                       .par_iter()
-                                       .map(|i| dir_out_ref.join(Path::new(&format!("{}_{}.toml",filename, i))))
-                                       .collect();
-
-    let randoms = norm::RandomScenario::from_scenario_multiple(&scenario, num)?;
-    for (r, fb) in randoms.iter().zip(csvs.iter()) {
-        r.to_toml(fb)?;
-    }
-    Ok(())
+    gen_norm_rand_csv_scenario(&scenario, filename, dir_out, num, options)
 }
 
 
-/// 管理図を併用して生成した乱数列を指定した個数分csvファイルで出力
+/// TOML形式の文字列で与えたシナリオから，生成した乱数列を指定した個数分csvファイルで出力
+///
+/// 標準入力等，ファイル以外から読み込んだシナリオを利用する場合に用いる．
 ///
 /// # 引数
-/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `scenario_toml` - 乱数生成のシナリオを記述したTOML形式の文字列
+/// * `filename` - 出力ファイル名の接頭辞（シナリオ名の代わりに用いる）
 /// * `dir_out`- 出力するディレクトリ名
 /// * `num` - 出力するファイルの個数
-/// 
+/// * `options` - 出力方法の設定
+///
 /// # 注意
-/// 出力ファイルは「シナリオ名_番号.csv」となります．  
-/// また，各乱数生成に用いたseed値は「seed.txt」，管理図の管理限界は「controlLimit.txt」に記録します．
-/// 
+/// 出力ファイルは「`filename`_番号.csv」となります．
+///
 /// # 使用例
 /// ```
-/// # use rand_scenario::gen_norm_rand_controlchart_csv;
+/// # use rand_scenario::{gen_norm_rand_csv_from_str, GenOptions};
 /// # use std::path::Path;
-/// # use std::fs::remove_dir_all;
-/// let path_scenario = Path::new("test/test_scenario.toml");
-/// let dir_out = Path::new("test/gen_norm_rand_controlchart_csv");
+/// # use std::fs::{remove_dir_all, read_to_string};
+/// let scenario_toml = read_to_string("test/test_scenario.toml").unwrap();
+/// let dir_out = Path::new("test/gen_norm_rand_csv_from_str");
 /// # remove_dir_all(dir_out.clone()).ok();
-/// gen_norm_rand_controlchart_csv(&path_scenario, &dir_out, 10).unwrap();
+/// gen_norm_rand_csv_from_str(&scenario_toml, "stdin", &dir_out, 3, &GenOptions::default()).unwrap();
+/// assert!(dir_out.join("stdin_3.csv").exists());
 /// ```
-pub fn gen_norm_rand_controlchart_csv<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let scenario = process_param::norm::Scenario::from_toml(path_scenario)?;
+pub fn gen_norm_rand_csv_from_str<P: AsRef<Path>>(scenario_toml: &str, filename: &str, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = process_param::norm::Scenario::parse_toml_str(scenario_toml)?;
+    gen_norm_rand_csv_scenario(&scenario, filename, dir_out, num, options)
+}
+
+
+// 読み込み済みのシナリオから乱数列を生成してcsvファイルで出力
+fn gen_norm_rand_csv_scenario<P: AsRef<Path>>(scenario: &process_param::norm::Scenario, filename: &str, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    norm::validate(scenario)?;
+    options.validate()?;
     // ファイルパスの準備
-    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
-    if let Err(e) = create_dir(dir_out) {
-        panic!("{:?}: {}", dir_out.as_ref(), e)
-    }
-    let dir_out_ref = dir_out.as_ref();
-    let csvs: Vec<PathBuf> = (1..num+1).collect::<Vec<usize>>()
-                                       .par_iter()
-                                       .map(|i| dir_out_ref.join(Path::new(&format!("{}_{}.csv",filename, i))))
-                                       .collect();
-
-    // seed値の記録用
-    let mut wtr_seed = csv::Writer::from_path(
-                      dir_out.as_ref().join(Path::new("seed.txt"))
-                  )?;
-    #[derive(Serialize)]
-    struct SeedRecord {
-        file: String,
-        seed: norm::Seed,
-    }
+    options.create_dir_out(dir_out)?;
+    let csvs = options.output_paths(dir_out, filename, num);
 
-    let randoms = norm::RandomScenario::from_scenario_controlchart_multiple(&scenario, num)?;
-    for (r, fb) in randoms.iter().zip(csvs.iter()) {
-        r.to_csv(fb)?;
-        wtr_seed.serialize( SeedRecord {file: fb.to_str().unwrap().to_string(), seed: r.get_seed()})?;
+    let (done, pending) = options.split_done(dir_out, &csvs)?;
+    let journal = options.open_journal(dir_out)?;
+    let mut rng_for_seed = entropy_rng(options.deterministic)?;
+    let seeds: Vec<norm::Seed> = pending.iter().map(|_| norm::Seed(rng_for_seed.next_u64())).collect();
+    let written = gen_write_pipeline(scenario, &seeds, &pending, |r, fb| {
+        options.write_csv(r, fb)?;
+        journal.append(fb, r.get_seed(), r.num_timepoints())
+    })?;
+    let (new_records, entries): (Vec<SeedRecord>, Vec<ManifestEntry>) = written.into_iter().unzip();
+    let records = merge_seed_records(&csvs, done, new_records);
+    options.wtr_seed(dir_out, &records)?;
+    journal.finish()?;
+    if options.with_manifest {
+        wtr_manifest_entries(dir_out, &entries)?;
+    }
+    if options.with_checksums {
+        wtr_checksums(dir_out, &csvs)?;
     }
-    wtr_seed.flush()?;
+    Ok(seed_list(records))
+}
 
-    wtr_norm_control_limit(dir_out, &scenario)?;
 
-    Ok(())
+/// 乱数列のcsvファイル出力の計画
+///
+/// # 引数
+/// * `files` - 出力するファイルのパス（ファイル番号順）
+/// * `change_points` - シナリオの変化点
+/// * `estimated_bytes` - 出力するファイルの合計サイズの見積もり（バイト）
+/// * `estimated_memory` - 乱数列の生成に用いるメモリの見積もり（バイト）．[`norm::estimate_memory`]を参照．
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenPlan {
+    pub files: Vec<PathBuf>,
+    pub change_points: Vec<usize>,
+    pub estimated_bytes: u64,
+    pub estimated_memory: usize,
 }
 
 
-/// 管理図を併用して生成した乱数列を指定した個数分tomlファイルで出力
+// csvファイルの1つの値あたりのバイト数の見積もり（符号，17桁の数字，小数点，区切り文字）
+const CSV_BYTES_PER_VALUE: u64 = 20;
+
+
+/// 乱数列を生成せずに，csvファイル出力の計画を作成
 ///
 /// # 引数
-/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `scenario` - 乱数生成のシナリオ
+/// * `filename` - 出力ファイル名の接頭辞（通常はシナリオ名）
 /// * `dir_out`- 出力するディレクトリ名
 /// * `num` - 出力するファイルの個数
-/// 
+/// * `options` - 出力方法の設定
+///
 /// # 注意
-/// 出力ファイルは「シナリオ名_番号.toml」となります．
-/// また管理図の管理限界は「controlLimit.txt」に保存されます．
-/// 
+/// ファイル名は[`gen_norm_rand_csv_with`]と同じ規則で決まります．
+/// ディレクトリやファイルは作成しません．
+/// 合計サイズは1つの値あたり20バイトとして見積もった非圧縮時の値で，`options.gzip`が`true`の場合は上限の目安です．
+///
 /// # 使用例
 /// ```
-/// # use rand_scenario::gen_norm_rand_controlchart_toml;
+/// # use rand_scenario::{plan_norm_rand_csv, GenOptions};
+/// # use rand_scenario::norm::load_scenario;
 /// # use std::path::Path;
-/// # use std::fs::remove_dir_all;
-/// let path_scenario = Path::new("test/test_scenario.toml");
-/// let dir_out = Path::new("test/gen_norm_rand_controlchart_toml");
-/// # remove_dir_all(dir_out.clone()).ok();
-/// gen_norm_rand_controlchart_toml(&path_scenario, &dir_out, 10).unwrap();
+/// let scenario = load_scenario(&Path::new("test/test_scenario.toml")).unwrap();
+/// let dir_out = Path::new("test/plan_norm_rand_csv");
+/// let options = GenOptions { zero_pad: true, ..GenOptions::default() };
+/// let plan = plan_norm_rand_csv(&scenario, "test_scenario", &dir_out, 12, &options).unwrap();
+/// assert_eq!(plan.files.len(), 12);
+/// assert_eq!(plan.files[0], dir_out.join("test_scenario_01.csv"));
+/// assert_eq!(plan.change_points, vec![15, 30]);
+/// assert_eq!(plan.estimated_bytes, 12 * 40 * 10 * 20);
+/// assert_eq!(plan.estimated_memory, rand_scenario::norm::estimate_memory(&scenario, 12).unwrap());
+/// assert!(!dir_out.exists());
 /// ```
-pub fn gen_norm_rand_controlchart_toml<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let scenario = process_param::norm::Scenario::from_toml(path_scenario)?;
-    // ファイルパスの準備
-    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
-    if let Err(e) = create_dir(dir_out) {
-        panic!("{:?}: {}", dir_out.as_ref(), e)
+pub fn plan_norm_rand_csv<P: AsRef<Path>>(scenario: &process_param::norm::Scenario, filename: &str, dir_out: &P, num: usize, options: &GenOptions) -> Result<GenPlan, Box<dyn std::error::Error>> {
+    norm::validate(scenario)?;
+    options.validate()?;
+    let n = match u64::try_from(scenario.n()) {
+        Ok(val) => val,
+        Err(_) => return Err(Box::new(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Sample size n doesn't convert to u64."))),
+    };
+    let horizon = scenario_horizon(scenario)? as u64;
+    Ok(GenPlan {
+        files: options.output_paths(dir_out, filename, num),
+        change_points: norm::change_points(scenario)?,
+        estimated_bytes: num as u64 * horizon * n * CSV_BYTES_PER_VALUE,
+        estimated_memory: norm::estimate_memory(scenario, num)?,
+    })
+}
+
+
+/// ディレクトリ内の各シナリオに対する乱数生成の結果
+///
+/// # 引数
+/// * `scenario` - シナリオのファイルのパス
+/// * `dir_out` - 乱数列を出力したディレクトリ
+/// * `result` - 出力したファイルのパスとseed値の組，または失敗した理由
+#[derive(Debug)]
+pub struct DirGenResult {
+    pub scenario: PathBuf,
+    pub dir_out: PathBuf,
+    pub result: Result<Vec<(PathBuf, norm::Seed)>, ScenarioError>,
+}
+
+
+/// ディレクトリ内の全てのシナリオから，生成した乱数列を指定した個数分csvファイルで出力
+///
+/// `scenario_dir`内の拡張子が`.toml`の全てのファイルをシナリオとして，ファイル名の順に
+/// [`gen_norm_rand_csv`]と同様に乱数列を出力する．
+/// 出力先は`out_root`の下のシナリオ名のサブディレクトリ（`out_root/シナリオ名`）となる．
+///
+/// # 引数
+/// * `scenario_dir` - シナリオのTOMLファイルを置いたディレクトリ
+/// * `out_root` - 出力先のディレクトリ（存在しない場合は作成する）
+/// * `num` - シナリオごとに出力するファイルの個数
+///
+/// # 注意
+/// 不正なシナリオや出力先のサブディレクトリが既に存在するシナリオがあっても全体の処理は中断せず，
+/// シナリオごとの成否を[`DirGenResult`]として返します．
+/// `scenario_dir`を読み込めない場合のみエラーを返します．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::{gen_norm_rand_csv_dir, ScenarioErrorKind};
+/// # use std::path::Path;
+/// # use std::fs::{create_dir_all, copy, write, remove_dir_all};
+/// let scenario_dir = Path::new("test/gen_norm_rand_csv_dir_scenarios");
+/// let out_root = Path::new("test/gen_norm_rand_csv_dir");
+/// # remove_dir_all(scenario_dir.clone()).ok();
+/// # remove_dir_all(out_root.clone()).ok();
+/// create_dir_all(&scenario_dir).unwrap();
+/// copy("test/test_scenario.toml", scenario_dir.join("a.toml")).unwrap();
+/// copy("test/test_scenario_small_shift.toml", scenario_dir.join("b.toml")).unwrap();
+/// write(scenario_dir.join("broken.toml"), "n = ").unwrap();
+/// write(scenario_dir.join("notes.txt"), "not a scenario").unwrap();
+/// write(scenario_dir.join("zero_n.toml"), r#"
+/// n = 0
+/// parameter = [{tau = 10, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}}]
+/// "#).unwrap();
+///
+/// let results = gen_norm_rand_csv_dir(&scenario_dir, &out_root, 3).unwrap();
+/// assert_eq!(results.len(), 4);
+/// assert_eq!(results[0].dir_out, out_root.join("a"));
+/// assert_eq!(results[0].result.as_ref().unwrap().len(), 3);
+/// assert!(out_root.join("b").join("b_3.csv").exists());
+/// // 不正なシナリオは失敗として報告される
+/// assert_eq!(results[2].scenario, scenario_dir.join("broken.toml"));
+/// assert!(results[2].result.is_err());
+/// // 失敗の種類はそのまま報告される
+/// assert_eq!(results[3].result.as_ref().unwrap_err().kind, ScenarioErrorKind::OutOfRange);
+/// ```
+pub fn gen_norm_rand_csv_dir<P: AsRef<Path> + Sync>(scenario_dir: &P, out_root: &P, num: usize) -> Result<Vec<DirGenResult>, Box<dyn std::error::Error>> {
+    gen_norm_rand_csv_dir_with(scenario_dir, out_root, num, &GenOptions::default())
+}
+
+
+/// 出力方法を指定して，ディレクトリ内の全てのシナリオから乱数列をcsvファイルで出力
+///
+/// 詳しくは[`gen_norm_rand_csv_dir`]を参照．
+/// 複数のシナリオは並列に生成する．同時に利用するスレッド数は`options.scenario_parallelism`で指定できる．
+/// 返り値はシナリオのファイル名の順に並ぶ．
+///
+/// # 引数
+/// * `scenario_dir` - シナリオのTOMLファイルを置いたディレクトリ
+/// * `out_root` - 出力先のディレクトリ（存在しない場合は作成する）
+/// * `num` - シナリオごとに出力するファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::{gen_norm_rand_csv_dir_with, GenOptions};
+/// # use std::path::Path;
+/// # use std::fs::{create_dir_all, copy, remove_dir_all};
+/// let scenario_dir = Path::new("test/gen_norm_rand_csv_dir_with_scenarios");
+/// let out_root = Path::new("test/gen_norm_rand_csv_dir_with");
+/// # remove_dir_all(scenario_dir.clone()).ok();
+/// # remove_dir_all(out_root.clone()).ok();
+/// create_dir_all(&scenario_dir).unwrap();
+/// for i in 0..6 {
+///     copy("test/test_scenario.toml", scenario_dir.join(format!("s{}.toml", i))).unwrap();
+/// }
+/// // 2スレッドで生成
+/// let options = GenOptions { scenario_parallelism: Some(2), ..GenOptions::default() };
+/// let results = gen_norm_rand_csv_dir_with(&scenario_dir, &out_root, 4, &options).unwrap();
+/// assert_eq!(results.len(), 6);
+/// assert!(results.iter().all(|r| r.result.is_ok()));
+/// assert_eq!(results[5].dir_out, out_root.join("s5"));
+///
+/// // スレッド数0はエラー
+/// let options = GenOptions { scenario_parallelism: Some(0), ..GenOptions::default() };
+/// assert!(gen_norm_rand_csv_dir_with(&scenario_dir, &out_root, 4, &options).is_err());
+/// ```
+pub fn gen_norm_rand_csv_dir_with<P: AsRef<Path> + Sync>(scenario_dir: &P, out_root: &P, num: usize, options: &GenOptions) -> Result<Vec<DirGenResult>, Box<dyn std::error::Error>> {
+    options.validate()?;
+    let scenarios = scenario_files(scenario_dir)?;
+    create_dir_all(out_root)?;
+    let gen = || scenarios.par_iter()
+                          .map(|path| gen_norm_rand_csv_in_root(path.clone(), out_root, num, options))
+                          .collect();
+    match options.scenario_parallelism {
+        Some(threads) => with_thread_pool(threads, gen),
+        None => Ok(gen()),
     }
-    let dir_out_ref = dir_out.as_ref();
-    let csvs: Vec<PathBuf> = (1..num+1).collect::<Vec<usize>>()
-                                       .par_iter()
-                                       .map(|i| dir_out_ref.join(Path::new(&format!("{}_{}.toml",filename, i))))
-                                       .collect();
+}
+
+// 指定したスレッド数のスレッドプールで処理を実行する
+// 内部の並列処理も同じスレッドプールで行うため，スレッド数の合計は`threads`を超えない
+#[cfg(feature = "parallel")]
+fn with_thread_pool<T: Send, F: FnOnce() -> T + Send>(threads: usize, f: F) -> Result<T, Box<dyn std::error::Error>> {
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()?;
+    Ok(pool.install(f))
+}
 
-    let randoms = norm::RandomScenario::from_scenario_controlchart_multiple(&scenario, num)?;
-    for (r, fb) in randoms.iter().zip(csvs.iter()) {
-        r.to_toml(fb)?;
+// `parallel` featureが無効な場合は逐次処理のためスレッド数は無視する
+#[cfg(not(feature = "parallel"))]
+fn with_thread_pool<T, F: FnOnce() -> T>(_threads: usize, f: F) -> Result<T, Box<dyn std::error::Error>> {
+    Ok(f())
+}
+
+// ディレクトリ内のシナリオのTOMLファイルをファイル名の順に列挙
+fn scenario_files<P: AsRef<Path>>(scenario_dir: &P) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut scenarios = Vec::new();
+    for entry in std::fs::read_dir(scenario_dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().and_then(|e| e.to_str()) == Some("toml") {
+            scenarios.push(path);
+        }
     }
+    scenarios.sort();
+    Ok(scenarios)
+}
 
-    wtr_norm_control_limit(dir_out, &scenario)?;
+// 1つのシナリオから`out_root/シナリオ名`に乱数列を出力し，その成否を記録
+fn gen_norm_rand_csv_in_root<P: AsRef<Path> + Sync>(path_scenario: PathBuf, out_root: &P, num: usize, options: &GenOptions) -> DirGenResult {
+    let dir_out = out_root.as_ref().join(path_scenario.file_stem().unwrap_or_default());
+    // 出力ファイル名の接頭辞に用いるため，シナリオ名がUTF-8でない場合は失敗として記録する
+    let stem = match path_scenario.file_stem().and_then(|stem| stem.to_str()) {
+        Some(stem) => stem.to_string(),
+        None => {
+            let result = Err(ScenarioError::new(ScenarioErrorKind::Other, format!("Scenario file name {:?} is not valid UTF-8.", path_scenario)));
+            return DirGenResult { scenario: path_scenario, dir_out, result }
+        },
+    };
+    // 出力先が既に存在する場合は，ディレクトリ作成時のパニックを避けて失敗として記録する
+    let result = if dir_out.exists() && !options.resume {
+        Err(ScenarioError::new(ScenarioErrorKind::Other, format!("Output directory {:?} already exists.", dir_out)))
+    } else {
+        norm::load_scenario(&path_scenario)
+            .and_then(|scenario| gen_norm_rand_csv_scenario(&scenario, &stem, &dir_out, num, options))
+            .map_err(into_scenario_error)
+    };
+    DirGenResult { scenario: path_scenario, dir_out, result }
+}
 
-    Ok(())
+// 生成時のエラーを[`ScenarioError`]に変換
+// `ScenarioError`はそのまま返し，種類と原因を保つ．それ以外のエラーは`Other`とし，型が分かる場合は原因として付与する
+fn into_scenario_error(e: Box<dyn std::error::Error>) -> ScenarioError {
+    let e = match e.downcast::<ScenarioError>() {
+        Ok(e) => return *e,
+        Err(e) => e,
+    };
+    let e = match e.downcast::<process_param::ScenarioError>() {
+        Ok(e) => return ScenarioError::from(*e),
+        Err(e) => e,
+    };
+    let err = ScenarioError::new(ScenarioErrorKind::Other, e.to_string());
+    let e = match e.downcast::<std::io::Error>() {
+        Ok(e) => return err.with_source(*e),
+        Err(e) => e,
+    };
+    let e = match e.downcast::<csv::Error>() {
+        Ok(e) => return err.with_source(*e),
+        Err(e) => e,
+    };
+    match e.downcast::<serde_json::Error>() {
+        Ok(e) => err.with_source(*e),
+        Err(_) => err,
+    }
 }
 
+// ファイルの読み書きで発生したエラーを，パスを付けた種類`Other`の[`ScenarioError`]に変換
+// 元のエラーは`into_scenario_error`で変換して原因として付与する
+fn path_error<P: AsRef<Path>>(path: &P, e: Box<dyn std::error::Error>) -> ScenarioError {
+    let message = format!("{:?}: {}", path.as_ref(), e);
+    ScenarioError::new(ScenarioErrorKind::Other, message).with_source(into_scenario_error(e))
+}
 
-// 正規分布に従うプロセスについて，管理限界の情報を書き出し
-fn wtr_norm_control_limit<P: AsRef<Path>>(path_dir: &P, scenario: &process_param::norm::Scenario) -> Result<(), Box<dyn std::error::Error>> {
-    let (mu_0, sigma_0_2) = scenario.param_in_control();
-    let (lcl_xbar, ucl_xbar) = scenario.control_limit_xbar();
-    let (lcl_s, ucl_s) = scenario.control_limit_s();
-    let cl_info = format!("μ_0, {mu_0}\nσ_0^2, {sigma_0_2}\n\nbarX control chart\nLCL, {lcl_xbar}\nUCL, {ucl_xbar}\n\ns control chart\nLCL, {lcl_s}\nUCL, {ucl_s}");
-    let mut wtr_cl = File::create(
-        path_dir.as_ref().join(Path::new("controlLimit.txt"))
-        )?;
+
+/// 生成した乱数列を指定した個数分csvファイルとして1つのzipファイルに出力
+///
+/// 多数のファイルを個別に作成せず，全てのcsvファイル，seed値の記録，管理限界の情報を1つのzipファイルにまとめる．
+/// 乱数列は一定個数ずつ生成して書き込むため，全ての乱数列を同時にメモリに保持しない．
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたファイルのパス
+/// * `path_zip` - 出力するzipファイルのパス
+/// * `num` - 出力するcsvファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// `zip` featureを有効にする必要があります．
+/// zipファイル内のファイル名は`options`の書式（`filename_template`，`zero_pad`）に従い，
+/// seed値の記録は`options.seed_file`（既定はCSV形式で「seed.txt」，JSON形式で「seed.json」），
+/// 3シグマの管理限界の情報は`options.control_limit_file`（既定は「controlLimit.txt」）となります．
+/// 管理限界の情報は，同じシナリオを[`gen_norm_rand_csv`]でディレクトリに出力した場合には出力されない（[`gen_norm_rand_controlchart_csv`]等でのみ出力される）ため，
+/// zipファイルの内容はディレクトリへの出力と一致しません．
+/// zipファイル自体が圧縮されるため`options.gzip`は無視し，`with_manifest`，`resume`，`with_checksums`にも対応しません．
+/// 返り値のパスはzipファイル内のファイル名です．
+///
+/// # 使用例
+/// ```
+/// # #[cfg(feature = "zip")] {
+/// # use rand_scenario::{gen_norm_rand_zip, GenOptions};
+/// # use std::path::Path;
+/// # use std::fs::{File, remove_file};
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let path_zip = Path::new("test/gen_norm_rand_zip.zip");
+/// # remove_file(path_zip.clone()).ok();
+/// let seeds = gen_norm_rand_zip(&path_scenario, &path_zip, 5, &GenOptions::default()).unwrap();
+/// assert_eq!(seeds.len(), 5);
+/// let mut archive = zip::ZipArchive::new(File::open(&path_zip).unwrap()).unwrap();
+/// assert_eq!(archive.len(), 7);
+/// assert!(archive.by_name("test_scenario_1.csv").is_ok());
+/// assert!(archive.by_name("seed.txt").is_ok());
+/// assert!(archive.by_name("controlLimit.txt").is_ok());
+/// # }
+/// ```
+pub fn gen_norm_rand_zip<P: AsRef<Path>>(path_scenario: &P, path_zip: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = norm::load_scenario(path_scenario)?;
+    norm::validate(&scenario)?;
+    options.validate()?;
+    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
+    let records = archive_records(filename, num, options)?;
+    wtr_archive(create_zip(path_zip)?, &scenario, &records, options)?;
+    Ok(seed_list(records))
+}
+
+
+/// 生成した乱数列を指定した個数分csvファイルとして1つのtar.gzファイルに出力
+///
+/// [`gen_norm_rand_zip`]のtar.gz版．アーカイブには全てのcsvファイルに加えて，
+/// seed値の記録（既定は「seed.txt」）と3シグマの管理限界の情報（既定は「controlLimit.txt」）も含まれる．
+/// 各ファイルは生成した順にgzipストリームへ書き出すため，全ての乱数列を同時にメモリに保持しない．
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたファイルのパス
+/// * `path_tar_gz` - 出力するtar.gzファイルのパス
+/// * `num` - 出力するcsvファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// `tar-gz` featureを有効にする必要があります．
+/// アーカイブ内のファイル名と`options`の扱い，管理限界の情報を含む点でディレクトリへの出力と異なることは[`gen_norm_rand_zip`]と同じです．
+///
+/// # 使用例
+/// ```
+/// # #[cfg(feature = "tar-gz")] {
+/// # use rand_scenario::{gen_norm_rand_tar_gz, GenOptions};
+/// # use std::path::Path;
+/// # use std::fs::{File, remove_file};
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let path_tar_gz = Path::new("test/gen_norm_rand_tar_gz.tar.gz");
+/// # remove_file(path_tar_gz.clone()).ok();
+/// let seeds = gen_norm_rand_tar_gz(&path_scenario, &path_tar_gz, 5, &GenOptions::default()).unwrap();
+/// assert_eq!(seeds.len(), 5);
+/// let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(File::open(&path_tar_gz).unwrap()));
+/// let names: Vec<String> = archive.entries().unwrap()
+///                                 .map(|e| e.unwrap().path().unwrap().to_str().unwrap().to_string())
+///                                 .collect();
+/// assert_eq!(names.len(), 7);
+/// assert_eq!(names[0], "test_scenario_1.csv");
+/// assert!(names.contains(&"seed.txt".to_string()));
+/// assert!(names.contains(&"controlLimit.txt".to_string()));
+/// # }
+/// ```
+pub fn gen_norm_rand_tar_gz<P: AsRef<Path>>(path_scenario: &P, path_tar_gz: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = norm::load_scenario(path_scenario)?;
+    norm::validate(&scenario)?;
+    options.validate()?;
+    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
+    let records = archive_records(filename, num, options)?;
+    wtr_archive(create_tar_gz(path_tar_gz)?, &scenario, &records, options)?;
+    Ok(seed_list(records))
+}
+
+
+// アーカイブ内の各csvファイルの名前とseed値
+// seed値の生成は`options.deterministic`に従う
+fn archive_records(filename: &str, num: usize, options: &GenOptions) -> Result<Vec<SeedRecord>, ScenarioError> {
+    let mut rng_for_seed = entropy_rng(options.deterministic)?;
+    Ok((1..num+1).map(|i| SeedRecord {
+                     file: options.file_name(filename, i, num, "csv"),
+                     seed: norm::Seed(rng_for_seed.next_u64()),
+                 })
+                 .collect())
+}
+
+
+// アーカイブに書き込む際に一度に生成する乱数列の個数
+const ARCHIVE_CHUNK: usize = 64;
+
+// 乱数列を`ARCHIVE_CHUNK`個ずつ並列に生成し，記録の順にCSV形式の文字列を`write`に渡す
+// 全ての乱数列を同時にメモリに保持しないようにする
+fn for_each_csv_chunked<F>(scenario: &process_param::norm::Scenario, records: &[SeedRecord], mut write: F) -> Result<(), Box<dyn std::error::Error>>
+    where F: FnMut(&SeedRecord, &str) -> Result<(), Box<dyn std::error::Error>>
+{
+    for chunk in records.chunks(ARCHIVE_CHUNK) {
+        let csvs = chunk.par_iter()
+                        .map(|r| norm::RandomScenario::from_scenario_seed(scenario, r.seed)
+                                     .map_err(ScenarioError::from)
+                                     .and_then(|randoms| randoms.to_csv_string()))
+                        .collect::<Result<Vec<String>, ScenarioError>>()?;
+        for (record, csv) in chunk.iter().zip(csvs.iter()) {
+            write(record, csv)?;
+        }
+    }
+    Ok(())
+}
+
+
+// 複数のファイルをまとめるアーカイブへの書き込み
+trait ArchiveWriter {
+    // `name`という名前のファイルとして`data`を追加
+    fn append(&mut self, name: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>>;
+    // アーカイブを閉じる
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+// csvファイル，seed値の記録，3シグマの管理限界の情報をアーカイブに書き込む
+fn wtr_archive(mut archive: Box<dyn ArchiveWriter>, scenario: &process_param::norm::Scenario, records: &[SeedRecord], options: &GenOptions) -> Result<(), Box<dyn std::error::Error>> {
+    for_each_csv_chunked(scenario, records, |record, csv| archive.append(&record.file, csv.as_bytes()))?;
+    let mut seeds = Vec::new();
+    options.write_seed_records(&mut seeds, records)?;
+    archive.append(options.seed_file_name(), &seeds)?;
+    let cl_info = norm_control_limit_info(scenario, 3.0)?;
+    archive.append(options.control_limit_file.as_deref().unwrap_or("controlLimit.txt"), cl_info.as_bytes())?;
+    archive.finish()
+}
+
+
+#[cfg(feature = "zip")]
+impl ArchiveWriter for zip::ZipWriter<BufWriter<File>> {
+    fn append(&mut self, name: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let file_options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        self.start_file(name, file_options)?;
+        self.write_all(data)?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), Box<dyn std::error::Error>> {
+        zip::ZipWriter::finish(&mut *self)?.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tar-gz")]
+impl ArchiveWriter for tar::Builder<flate2::write::GzEncoder<BufWriter<File>>> {
+    fn append(&mut self, name: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs()));
+        header.set_cksum();
+        self.append_data(&mut header, name, data)?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn std::error::Error>> {
+        (*self).into_inner()?.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+// tar.gzファイルを作成
+// 各ファイルは追加した時点でgzipストリームに書き出される
+#[cfg(feature = "tar-gz")]
+fn create_tar_gz<P: AsRef<Path>>(path_tar_gz: &P) -> Result<Box<dyn ArchiveWriter>, Box<dyn std::error::Error>> {
+    let encoder = flate2::write::GzEncoder::new(BufWriter::new(File::create(path_tar_gz)?), flate2::Compression::default());
+    Ok(Box::new(tar::Builder::new(encoder)))
+}
+
+#[cfg(not(feature = "tar-gz"))]
+fn create_tar_gz<P: AsRef<Path>>(_path_tar_gz: &P) -> Result<Box<dyn ArchiveWriter>, Box<dyn std::error::Error>> {
+    Err(Box::new(ScenarioError::new(ScenarioErrorKind::Other, "tar.gz output requires the `tar-gz` feature.")))
+}
+
+// zipファイルを作成
+#[cfg(feature = "zip")]
+fn create_zip<P: AsRef<Path>>(path_zip: &P) -> Result<Box<dyn ArchiveWriter>, Box<dyn std::error::Error>> {
+    Ok(Box::new(zip::ZipWriter::new(BufWriter::new(File::create(path_zip)?))))
+}
+
+#[cfg(not(feature = "zip"))]
+fn create_zip<P: AsRef<Path>>(_path_zip: &P) -> Result<Box<dyn ArchiveWriter>, Box<dyn std::error::Error>> {
+    Err(Box::new(ScenarioError::new(ScenarioErrorKind::Other, "zip output requires the `zip` feature.")))
+}
+
+
+/// 生成した乱数列を指定した個数分tomlファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// 
+/// # 注意
+/// 出力ファイルは「シナリオ名_番号.toml」となります．  
+/// 
+/// # 使用例
+/// ```
+/// # use rand_scenario::gen_norm_rand_toml;
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let dir_out = Path::new("test/gen_norm_rand_toml");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// gen_norm_rand_toml(&path_scenario, &dir_out, 10).unwrap();
+/// ```
+pub fn gen_norm_rand_toml<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_norm_rand_toml_with(path_scenario, dir_out, num, &GenOptions::default())
+}
+
+
+/// 出力方法を指定して，生成した乱数列を指定した個数分tomlファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// 出力ファイルは[`gen_norm_rand_toml`]と同じです．
+/// ファイル名は`options.zero_pad`および`options.filename_template`に従います（拡張子`{ext}`は`toml`）．
+/// `options.gzip`は無視されます．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::{gen_norm_rand_toml_with, GenOptions};
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let dir_out = Path::new("test/gen_norm_rand_toml_with");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// let options = GenOptions { filename_template: Some("run{index}_{stem}.{ext}".to_string()), ..GenOptions::default() };
+/// gen_norm_rand_toml_with(&path_scenario, &dir_out, 3, &options).unwrap();
+/// assert!(dir_out.join("run1_test_scenario.toml").exists());
+///
+/// // {index}を含まない書式はエラー
+/// let options = GenOptions { filename_template: Some("{stem}.{ext}".to_string()), ..GenOptions::default() };
+/// assert!(gen_norm_rand_toml_with(&path_scenario, &Path::new("test/gen_norm_rand_toml_with_invalid"), 3, &options).is_err());
+/// // パスの区切り文字を含む書式はエラー
+/// let options = GenOptions { filename_template: Some("../{stem}_{index}.{ext}".to_string()), ..GenOptions::default() };
+/// assert!(gen_norm_rand_toml_with(&path_scenario, &Path::new("test/gen_norm_rand_toml_with_invalid"), 3, &options).is_err());
+/// ```
+pub fn gen_norm_rand_toml_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = norm::load_scenario(path_scenario)?;
+    norm::validate(&scenario)?;
+    options.validate()?;
+    // ファイルパスの準備
+    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
+    create_dir(dir_out).map_err(|e| dir_out_error(dir_out, e))?;
+    let csvs = options.output_paths_ext(dir_out, filename, num, "toml");
+
+    let randoms = norm::RandomScenario::from_scenario_multiple_with(&scenario, num, options)?;
+    let records = write_randoms_par(&randoms, &csvs, |r, fb| { r.to_toml(fb)?; Ok(r.get_seed()) })?;
+    if options.with_manifest {
+        wtr_manifest(dir_out, &randoms, &csvs)?;
+    }
+    if options.with_checksums {
+        wtr_checksums(dir_out, &csvs)?;
+    }
+    Ok(seed_list(records))
+}
+
+
+/// 管理図を併用して生成した乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// 
+/// # 注意
+/// 出力ファイルは「シナリオ名_番号.csv」となります．  
+/// また，各乱数生成に用いたseed値は「seed.txt」，管理図の管理限界は「controlLimit.txt」に記録します．
+/// 管理外れ状態の判定は[`norm::RandomScenario::from_scenario_seed_controlchart`]と同じくシナリオの3シグマの規則に従い，管理限界の幅は変更できません．
+/// 幅を変更する場合は[`gen_norm_rand_controlchart_csv_sigma`]を用います．
+/// 
+/// # 使用例
+/// ```
+/// # use rand_scenario::gen_norm_rand_controlchart_csv;
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let dir_out = Path::new("test/gen_norm_rand_controlchart_csv");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// gen_norm_rand_controlchart_csv(&path_scenario, &dir_out, 10).unwrap();
+/// ```
+pub fn gen_norm_rand_controlchart_csv<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_norm_rand_controlchart_csv_with(path_scenario, dir_out, num, &GenOptions::default())
+}
+
+
+/// 出力方法を指定して，管理図を併用して生成した乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// 出力ファイルは[`gen_norm_rand_controlchart_csv`]と同じです．
+/// ただし，`options.gzip`が`true`の場合は「シナリオ名_番号.csv.gz」となります．
+/// `options.zero_pad`が`true`の場合は，番号を`num`の桁数まで0で埋めます（例：`num = 1000`のとき「シナリオ名_0001.csv」）．
+/// `options.filename_template`を指定した場合は，その書式に従ったファイル名となります．
+/// seed値の記録は`options.seed_file`のファイル名で，`options.seed_format`の形式で出力します．
+/// `options.with_manifest`が`true`の場合は，管理外れ状態を検出した時点を含む全ファイルの一覧を「manifest.json」に記録します．
+/// `options.resume`および`options.with_checksums`の扱いは[`gen_norm_rand_csv_with`]と同じです．
+pub fn gen_norm_rand_controlchart_csv_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = norm::load_scenario(path_scenario)?;
+    norm::validate(&scenario)?;
+    options.validate()?;
+    // ファイルパスの準備
+    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
+    options.create_dir_out(dir_out)?;
+    let csvs = options.output_paths(dir_out, filename, num);
+
+    let (done, pending) = options.split_done(dir_out, &csvs)?;
+    let journal = options.open_journal(dir_out)?;
+    let randoms = norm::RandomScenario::from_scenario_controlchart_multiple_with(&scenario, pending.len(), options)?;
+    let new_records = write_randoms_par(&randoms, &pending, |r, fb| {
+        options.write_csv(r, fb)?;
+        journal.append(fb, r.get_seed(), r.num_timepoints())?;
+        Ok(r.get_seed())
+    })?;
+    let records = merge_seed_records(&csvs, done, new_records);
+    options.wtr_seed(dir_out, &records)?;
+    journal.finish()?;
+    if options.with_manifest {
+        wtr_manifest(dir_out, &randoms, &pending)?;
+    }
+    if options.with_checksums {
+        wtr_checksums(dir_out, &csvs)?;
+    }
+
+    wtr_norm_control_limit(dir_out, options.control_limit_file.as_deref().unwrap_or("controlLimit.txt"), &scenario, 3.0)?;
+
+    Ok(seed_list(records))
+}
+
+
+/// 管理限界の幅を指定し，管理図を併用して生成した乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `sigma_mult` - 管理限界の幅（シグマの倍数）
+///
+/// # 注意
+/// 出力ファイルは[`gen_norm_rand_controlchart_csv`]と同じです．
+/// 「controlLimit.txt」には`sigma_mult`シグマの管理限界を記録します．
+/// 各乱数列は[`norm::RandomScenario::from_scenario_seed_controlchart_sigma`]で生成するため，
+/// `sigma_mult = 3.0`であっても[`gen_norm_rand_controlchart_csv`]とは判定の規則が異なり，同じseed値から同じ乱数列になるとは限りません．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::gen_norm_rand_controlchart_csv_sigma;
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let dir_out = Path::new("test/gen_norm_rand_controlchart_csv_sigma");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// gen_norm_rand_controlchart_csv_sigma(&path_scenario, &dir_out, 10, 2.5).unwrap();
+/// ```
+pub fn gen_norm_rand_controlchart_csv_sigma<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, sigma_mult: f64) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_norm_rand_controlchart_csv_sigma_with(path_scenario, dir_out, num, sigma_mult, &GenOptions::default())
+}
+
+
+/// 出力方法と管理限界の幅を指定し，管理図を併用して生成した乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `sigma_mult` - 管理限界の幅（シグマの倍数）
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// 出力ファイルと`options`の扱いは[`gen_norm_rand_controlchart_csv_with`]と同じです．
+/// 管理限界のファイルには`sigma_mult`シグマの管理限界を記録します．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::{gen_norm_rand_controlchart_csv_sigma_with, GenOptions};
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let dir_out = Path::new("test/gen_norm_rand_controlchart_csv_sigma_with");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// let options = GenOptions {
+///     zero_pad: true,
+///     seed_file: Some("seeds.csv".to_string()),
+///     control_limit_file: Some("limits.txt".to_string()),
+///     ..GenOptions::default()
+/// };
+/// gen_norm_rand_controlchart_csv_sigma_with(&path_scenario, &dir_out, 10, 2.5, &options).unwrap();
+/// assert!(dir_out.join("test_scenario_01.csv").exists());
+/// assert!(dir_out.join("seeds.csv").exists());
+/// assert!(dir_out.join("limits.txt").exists());
+/// ```
+pub fn gen_norm_rand_controlchart_csv_sigma_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, sigma_mult: f64, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = norm::load_scenario(path_scenario)?;
+    norm::validate(&scenario)?;
+    options.validate()?;
+    // ファイルパスの準備
+    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
+    options.create_dir_out(dir_out)?;
+    let csvs = options.output_paths(dir_out, filename, num);
+
+    let (done, pending) = options.split_done(dir_out, &csvs)?;
+    let journal = options.open_journal(dir_out)?;
+    let randoms = norm::RandomScenario::from_scenario_controlchart_multiple_sigma_with(&scenario, pending.len(), sigma_mult, options)?;
+    let new_records = write_randoms_par(&randoms, &pending, |r, fb| {
+        options.write_csv(r, fb)?;
+        journal.append(fb, r.get_seed(), r.num_timepoints())?;
+        Ok(r.get_seed())
+    })?;
+    let records = merge_seed_records(&csvs, done, new_records);
+    options.wtr_seed(dir_out, &records)?;
+    journal.finish()?;
+    if options.with_manifest {
+        wtr_manifest(dir_out, &randoms, &pending)?;
+    }
+    if options.with_checksums {
+        wtr_checksums(dir_out, &csvs)?;
+    }
+
+    wtr_norm_control_limit(dir_out, options.control_limit_file.as_deref().unwrap_or("controlLimit.txt"), &scenario, sigma_mult)?;
+
+    Ok(seed_list(records))
+}
+
+
+/// 管理図を併用して生成した乱数列を指定した個数分tomlファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// 
+/// # 注意
+/// 出力ファイルは「シナリオ名_番号.toml」となります．
+/// また管理図の管理限界は「controlLimit.txt」に保存されます．
+/// 
+/// # 使用例
+/// ```
+/// # use rand_scenario::gen_norm_rand_controlchart_toml;
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let dir_out = Path::new("test/gen_norm_rand_controlchart_toml");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// gen_norm_rand_controlchart_toml(&path_scenario, &dir_out, 10).unwrap();
+/// ```
+pub fn gen_norm_rand_controlchart_toml<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_norm_rand_controlchart_toml_with(path_scenario, dir_out, num, &GenOptions::default())
+}
+
+
+/// 出力方法を指定して，管理図を併用して生成した乱数列を指定した個数分tomlファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// 出力ファイルは[`gen_norm_rand_controlchart_toml`]と同じです．
+/// ファイル名は`options.zero_pad`および`options.filename_template`に従います（拡張子`{ext}`は`toml`）．
+/// `options.gzip`は無視されます．
+pub fn gen_norm_rand_controlchart_toml_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = norm::load_scenario(path_scenario)?;
+    norm::validate(&scenario)?;
+    options.validate()?;
+    // ファイルパスの準備
+    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
+    create_dir(dir_out).map_err(|e| dir_out_error(dir_out, e))?;
+    let csvs = options.output_paths_ext(dir_out, filename, num, "toml");
+
+    let randoms = norm::RandomScenario::from_scenario_controlchart_multiple_with(&scenario, num, options)?;
+    let records = write_randoms_par(&randoms, &csvs, |r, fb| { r.to_toml(fb)?; Ok(r.get_seed()) })?;
+    if options.with_manifest {
+        wtr_manifest(dir_out, &randoms, &csvs)?;
+    }
+    if options.with_checksums {
+        wtr_checksums(dir_out, &csvs)?;
+    }
+
+    wtr_norm_control_limit(dir_out, options.control_limit_file.as_deref().unwrap_or("controlLimit.txt"), &scenario, 3.0)?;
+
+    Ok(seed_list(records))
+}
+
+
+/// ポアソン分布に従う乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+///
+/// # 注意
+/// 出力ファイルは「シナリオ名_番号.csv」となります．
+/// また，各乱数生成に用いたseed値は「seed.txt」に記録します．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::gen_pois_rand_csv;
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario_pois.toml");
+/// let dir_out = Path::new("test/gen_pois_rand_csv");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// gen_pois_rand_csv(&path_scenario, &dir_out, 10).unwrap();
+/// ```
+pub fn gen_pois_rand_csv<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_pois_rand_csv_with(path_scenario, dir_out, num, &GenOptions::default())
+}
+
+
+/// 出力方法を指定して，ポアソン分布に従う乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// ファイル名（`options.zero_pad`，`options.filename_template`），seed値の記録（`options.seed_file`，`options.seed_format`），
+/// `options.with_checksums`は[`gen_norm_rand_csv_with`]と同様に扱います．
+/// `options.gzip`，`options.resume`，`options.with_manifest`は正規分布の乱数列のみが対応するため，指定した場合はエラーとなります．
+pub fn gen_pois_rand_csv_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = process_param::pois::Scenario::from_toml(path_scenario)?;
+    pois::validate(&scenario)?;
+    gen_rand_files_with(path_scenario, dir_out, num, options, "csv",
+                        |num| Ok(pois::RandomScenario::from_scenario_multiple_with(&scenario, num, options)?),
+                        |r: &pois::RandomScenario, fb| { r.to_csv(fb)?; Ok(r.get_seed()) })
+}
+
+
+/// 指数分布に従う乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+///
+/// # 注意
+/// 出力ファイルは「シナリオ名_番号.csv」となります．
+/// また，各乱数生成に用いたseed値は「seed.txt」に記録します．
+/// シナリオの書式は[`exp`]を参照してください．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::gen_exp_rand_csv;
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario_exp.toml");
+/// let dir_out = Path::new("test/gen_exp_rand_csv");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// gen_exp_rand_csv(&path_scenario, &dir_out, 10).unwrap();
+/// ```
+pub fn gen_exp_rand_csv<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_exp_rand_csv_with(path_scenario, dir_out, num, &GenOptions::default())
+}
+
+
+/// 出力方法を指定して，指数分布に従う乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// ファイル名（`options.zero_pad`，`options.filename_template`），seed値の記録（`options.seed_file`，`options.seed_format`），
+/// `options.with_checksums`は[`gen_norm_rand_csv_with`]と同様に扱います．
+/// `options.gzip`，`options.resume`，`options.with_manifest`は正規分布の乱数列のみが対応するため，指定した場合はエラーとなります．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::{gen_exp_rand_csv_with, GenOptions};
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario_exp.toml");
+/// let dir_out = Path::new("test/gen_exp_rand_csv_with");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// let options = GenOptions { filename_template: Some("run{index}.{ext}".to_string()), ..GenOptions::default() };
+/// gen_exp_rand_csv_with(&path_scenario, &dir_out, 3, &options).unwrap();
+/// assert!(dir_out.join("run3.csv").exists());
+/// assert!(dir_out.join("seed.txt").exists());
+///
+/// // 正規分布の乱数列のみが対応する設定はエラー
+/// let options = GenOptions { gzip: true, ..GenOptions::default() };
+/// assert!(gen_exp_rand_csv_with(&path_scenario, &Path::new("test/gen_exp_rand_csv_with_gzip"), 3, &options).is_err());
+///
+/// // 既存のディレクトリへの出力はパニックせずにエラーを返す
+/// assert!(gen_exp_rand_csv_with(&path_scenario, &dir_out, 3, &GenOptions::default()).is_err());
+///
+/// // 不正なシナリオでは出力先のディレクトリを作成せずにエラーを返す
+/// let invalid = Path::new("test/test_scenario_exp_invalid.toml");
+/// std::fs::write(&invalid, "n = 5\nparameter = [{tau = 10, lambda = -1.0}]").unwrap();
+/// let dir_invalid = Path::new("test/gen_exp_rand_csv_with_invalid");
+/// # remove_dir_all(dir_invalid.clone()).ok();
+/// assert!(gen_exp_rand_csv_with(&invalid, &dir_invalid, 3, &GenOptions::default()).is_err());
+/// assert!(!dir_invalid.exists());
+/// ```
+pub fn gen_exp_rand_csv_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = exp::Scenario::from_toml(path_scenario)?;
+    exp::validate(&scenario)?;
+    gen_rand_files_with(path_scenario, dir_out, num, options, "csv",
+                        |num| Ok(exp::RandomScenario::from_scenario_multiple_with(&scenario, num, options)?),
+                        |r: &exp::RandomScenario, fb| { r.to_csv(fb)?; Ok(r.get_seed()) })
+}
+
+
+/// 二項分布に従う乱数列（各時点の不適合品数）を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+///
+/// # 注意
+/// 出力ファイルは「シナリオ名_番号.csv」となります．
+/// また，各乱数生成に用いたseed値は「seed.txt」に記録します．
+/// シナリオの書式は[`binom`]を参照してください．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::gen_binom_rand_csv;
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario_binom.toml");
+/// let dir_out = Path::new("test/gen_binom_rand_csv");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// gen_binom_rand_csv(&path_scenario, &dir_out, 10).unwrap();
+/// ```
+pub fn gen_binom_rand_csv<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_binom_rand_csv_with(path_scenario, dir_out, num, &GenOptions::default())
+}
+
+
+/// 出力方法を指定して，二項分布に従う乱数列（各時点の不適合品数）を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// ファイル名（`options.zero_pad`，`options.filename_template`），seed値の記録（`options.seed_file`，`options.seed_format`），
+/// `options.with_checksums`は[`gen_norm_rand_csv_with`]と同様に扱います．
+/// `options.gzip`，`options.resume`，`options.with_manifest`は正規分布の乱数列のみが対応するため，指定した場合はエラーとなります．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::{gen_binom_rand_csv_with, GenOptions};
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario_binom.toml");
+/// let dir_out = Path::new("test/gen_binom_rand_csv_with");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// let options = GenOptions { zero_pad: true, seed_file: Some("seeds.csv".to_string()), ..GenOptions::default() };
+/// gen_binom_rand_csv_with(&path_scenario, &dir_out, 10, &options).unwrap();
+/// assert!(dir_out.join("test_scenario_binom_01.csv").exists());
+/// assert!(dir_out.join("seeds.csv").exists());
+/// ```
+pub fn gen_binom_rand_csv_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = binom::Scenario::from_toml(path_scenario)?;
+    binom::validate(&scenario)?;
+    gen_rand_files_with(path_scenario, dir_out, num, options, "csv",
+                        |num| Ok(binom::RandomScenario::from_scenario_multiple_with(&scenario, num, options)?),
+                        |r: &binom::RandomScenario, fb| { r.to_csv(fb)?; Ok(r.get_seed()) })
+}
+
+
+/// ガンマ分布に従う乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+///
+/// # 注意
+/// 出力ファイルは「シナリオ名_番号.csv」となります．
+/// また，各乱数生成に用いたseed値は「seed.txt」に記録します．
+/// シナリオの書式は[`gamma`]を参照してください．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::gen_gamma_rand_csv;
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario_gamma.toml");
+/// let dir_out = Path::new("test/gen_gamma_rand_csv");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// gen_gamma_rand_csv(&path_scenario, &dir_out, 10).unwrap();
+/// ```
+pub fn gen_gamma_rand_csv<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_gamma_rand_csv_with(path_scenario, dir_out, num, &GenOptions::default())
+}
+
+
+/// 出力方法を指定して，ガンマ分布に従う乱数列を指定した個数分csvファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// ファイル名（`options.zero_pad`，`options.filename_template`），seed値の記録（`options.seed_file`，`options.seed_format`），
+/// `options.with_checksums`は[`gen_norm_rand_csv_with`]と同様に扱います．
+/// `options.gzip`，`options.resume`，`options.with_manifest`は正規分布の乱数列のみが対応するため，指定した場合はエラーとなります．
+pub fn gen_gamma_rand_csv_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = gamma::Scenario::from_toml(path_scenario)?;
+    gamma::validate(&scenario)?;
+    gen_rand_files_with(path_scenario, dir_out, num, options, "csv",
+                        |num| Ok(gamma::RandomScenario::from_scenario_multiple_with(&scenario, num, options)?),
+                        |r: &gamma::RandomScenario, fb| { r.to_csv(fb)?; Ok(r.get_seed()) })
+}
+
+
+/// ガンマ分布に従う乱数列を指定した個数分tomlファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+///
+/// # 注意
+/// 出力ファイルは「シナリオ名_番号.toml」となります．
+/// また，各乱数生成に用いたseed値は「seed.txt」に記録します．
+/// シナリオの書式は[`gamma`]を参照してください．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::gen_gamma_rand_toml;
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario_gamma.toml");
+/// let dir_out = Path::new("test/gen_gamma_rand_toml");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// gen_gamma_rand_toml(&path_scenario, &dir_out, 10).unwrap();
+/// ```
+pub fn gen_gamma_rand_toml<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    gen_gamma_rand_toml_with(path_scenario, dir_out, num, &GenOptions::default())
+}
+
+
+/// 出力方法を指定して，ガンマ分布に従う乱数列を指定した個数分tomlファイルで出力
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `dir_out`- 出力するディレクトリ名
+/// * `num` - 出力するファイルの個数
+/// * `options` - 出力方法の設定
+///
+/// # 注意
+/// ファイル名（`options.zero_pad`，`options.filename_template`），seed値の記録（`options.seed_file`，`options.seed_format`），
+/// `options.with_checksums`は[`gen_norm_rand_csv_with`]と同様に扱います．
+/// `options.gzip`，`options.resume`，`options.with_manifest`は正規分布の乱数列のみが対応するため，指定した場合はエラーとなります．
+pub fn gen_gamma_rand_toml_with<P: AsRef<Path>>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>> {
+    let scenario = gamma::Scenario::from_toml(path_scenario)?;
+    gamma::validate(&scenario)?;
+    gen_rand_files_with(path_scenario, dir_out, num, options, "toml",
+                        |num| Ok(gamma::RandomScenario::from_scenario_multiple_with(&scenario, num, options)?),
+                        |r: &gamma::RandomScenario, fb| { r.to_toml(fb)?; Ok(r.get_seed()) })
+}
+
+
+// 正規分布以外の乱数列を，`options`のファイル名とseed値の記録の設定に従って拡張子`ext`のファイルに出力
+// `gen`で指定した個数の乱数列を生成し，`write`で各ファイルに書き出す
+// シナリオは呼び出し側で検証済みとし，不正なシナリオで出力先のディレクトリが残らないようにする
+fn gen_rand_files_with<P, T, G, W>(path_scenario: &P, dir_out: &P, num: usize, options: &GenOptions, ext: &str, gen: G, write: W) -> Result<Vec<(PathBuf, norm::Seed)>, Box<dyn std::error::Error>>
+where
+    P: AsRef<Path>,
+    T: Sync,
+    G: FnOnce(usize) -> Result<Vec<T>, Box<dyn std::error::Error>>,
+    W: Fn(&T, &PathBuf) -> Result<norm::Seed, Box<dyn std::error::Error>> + Sync
+{
+    options.validate()?;
+    if options.gzip || options.resume || options.with_manifest {
+        return Err(Box::new(ScenarioError::new(
+            ScenarioErrorKind::Other,
+            "gzip, resume and manifest output are supported only for normal scenarios."
+        )));
+    }
+    // ファイルパスの準備
+    let filename = path_scenario.as_ref().file_stem().unwrap().to_str().unwrap();
+    options.create_dir_out(dir_out)?;
+    let paths = options.output_paths_ext(dir_out, filename, num, ext);
+
+    let randoms = gen(num)?;
+    let records = write_randoms_par(&randoms, &paths, write)?;
+    options.wtr_seed(dir_out, &records)?;
+    if options.with_checksums {
+        wtr_checksums(dir_out, &paths)?;
+    }
+    Ok(seed_list(records))
+}
+
+
+// 一様乱数[0, 1)を生成
+// 64bitの乱数の上位53bitを用いて，倍精度浮動小数点数の仮数部に一様に割り当てる
+pub(crate) fn rand_uniform<R: RngCore + ?Sized>(rng: &mut R) -> f64 {
+    (rng.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+// 標準正規乱数をn個生成
+// `rand_uniform`による一様乱数(U_1, U_2)の組からBox-Muller法で2個ずつ生成し，nが奇数の場合は最後の1個を捨てる
+pub(crate) fn rand_std_norm_n<R: RngCore + ?Sized>(rng: &mut R, n: usize) -> Vec<f64> {
+    let mut z = Vec::with_capacity(n + 1);
+    while z.len() < n {
+        let u1 = rand_uniform(rng);
+        let u2 = rand_uniform(rng);
+        // 1 - U_1 \in (0, 1]のため対数は有限
+        let r = (-2.0 * (1.0 - u1).ln()).sqrt();
+        let theta = 2.0 * std::f64::consts::PI * u2;
+        z.push(r * theta.cos());
+        z.push(r * theta.sin());
+    }
+    z.truncate(n);
+    z
+}
+
+
+/// seed値から，クレート内部で用いる一様乱数列$ U \in [0, 1) $を生成
+///
+/// 乱数生成器は各`RandomScenario`と同じく，seed値で初期化したMersenne-Twister法（[`rand_mt::Mt64`]）である．
+/// 64bitの乱数$ r $は，上位53bitを倍精度浮動小数点数の仮数部に割り当てて$ U = \lfloor r / 2^{11} \rfloor \cdot 2^{-53} $と変換する．
+/// この変換は指数分布の逆関数法，二項分布，外れ値の判定等で用いるものと同一であり，
+/// 独自の逆関数法による変換を実装したり，同じ雑音を再利用したりする際に用いる．
+///
+/// 正規乱数もこの一様乱数列から生成する．
+/// 先頭から2個1組の$ (U_1, U_2) $をBox-Muller法で
+/// $ Z_1 = \sqrt{-2 \ln(1 - U_1)} \cos(2 \pi U_2) $，$ Z_2 = \sqrt{-2 \ln(1 - U_1)} \sin(2 \pi U_2) $の順に変換し，
+/// 平均$ \mu $，分散$ \sigma^2 $の正規乱数を$ \mu + \sigma Z $とする．
+/// 一度に生成する正規乱数の個数（通常は1時点のサンプル・サイズ）が奇数の場合，最後の組の$ Z_2 $は捨てる．
+///
+/// # 引数
+/// * `seed` - 乱数生成に用いるseed値
+/// * `count` - 生成する一様乱数の個数
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::uniform_stream;
+/// # use rand_scenario::norm::Seed;
+/// # use rand_scenario::exp::{Scenario, RandomScenario};
+/// let us = uniform_stream(Seed(42), 100);
+/// assert_eq!(us.len(), 100);
+/// assert!(us.iter().all(|u| (0.0..1.0).contains(u)));
+/// assert_eq!(us, uniform_stream(Seed(42), 100));
+///
+/// // 指数乱数は同じ一様乱数列を逆関数法で変換したもの
+/// let scenario = Scenario::parse_toml_str("n = 10\nparameter = [{tau = 10, lambda = 2.0}]").unwrap();
+/// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+/// let expected: Vec<f64> = us.iter().map(|u| -(1.0 - u).ln() / 2.0).collect();
+/// let actual: Vec<f64> = randoms.rand_vars().iter().flatten().cloned().collect();
+/// assert_eq!(actual, expected);
+///
+/// // 正規乱数は同じ一様乱数列をBox-Muller法で変換したもの
+/// // test_scenario.tomlの最初の時点は平均0，分散1，サンプル・サイズ10
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = process_param::norm::Scenario::from_toml(&path).unwrap();
+/// let randoms = rand_scenario::norm::RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+/// let expected: Vec<f64> = us[..10].chunks(2)
+///                                  .flat_map(|u| {
+///                                      let r = (-2.0 * (1.0 - u[0]).ln()).sqrt();
+///                                      let theta = 2.0 * std::f64::consts::PI * u[1];
+///                                      [r * theta.cos(), r * theta.sin()]
+///                                  })
+///                                  .collect();
+/// assert_eq!(randoms.rand_vars()[0], expected);
+/// ```
+pub fn uniform_stream(seed: norm::Seed, count: usize) -> Vec<f64> {
+    let mut rng = rand_mt::Mt64::new(seed.0);
+    (0..count).map(|_| rand_uniform(&mut rng)).collect()
+}
+
+
+/// シナリオの時点数（終端）を取得
+///
+/// 展開したパラメータ系列（`Scenario::decomplession`）の長さを時点数とする．
+/// [`norm::validate`]，[`norm::estimate_memory`]，[`norm::RandomScenario::horizon`]等はいずれもこの値を用いるため，
+/// 管理図を併用せずに生成した乱数列の時点数（[`norm::RandomScenario::num_timepoints`]）と一致する．
+///
+/// # 引数
+/// * `scenario` - 乱数生成のシナリオ
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::scenario_horizon;
+/// # use rand_scenario::norm::{RandomScenario, Seed};
+/// # use process_param::norm::Scenario;
+/// let scenario = Scenario::from_toml(&std::path::Path::new("test/test_scenario.toml")).unwrap();
+/// assert_eq!(scenario_horizon(&scenario).unwrap(), 40);
+/// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+/// assert_eq!(randoms.num_timepoints(), scenario_horizon(&scenario).unwrap());
+/// assert_eq!(randoms.horizon().unwrap(), randoms.num_timepoints());
+/// ```
+pub fn scenario_horizon(scenario: &process_param::norm::Scenario) -> Result<usize, ScenarioError> {
+    scenario.decomplession().map(|params| params.len()).map_err(|e| ScenarioError::new(
+        ScenarioErrorKind::OutOfRange,
+        format!("Parameters cannot be decompressed (e.g. non-positive variance): {}", e.message)
+    ).with_source(e))
+}
+
+
+/// 文字列のシナリオから乱数列を生成し，CSV形式の文字列として返す
+///
+/// ファイルの読み書きを一切行わないため，ファイルシステムを持たない環境（wasm32等）でも利用できる．
+/// 出力は[`norm::RandomScenario::to_csv`]と同じ形式．
+///
+/// # 引数
+/// * `scenario_str` - 乱数生成のシナリオを記述したTOML形式の文字列
+/// * `seed` - 乱数生成に用いるseed値
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::generate_csv_string;
+/// # use rand_scenario::norm::{RandomScenario, Seed};
+/// # use process_param::norm::Scenario;
+/// let scenario_str = std::fs::read_to_string("test/test_scenario.toml").unwrap();
+/// let csv = generate_csv_string(&scenario_str, Seed(42)).unwrap();
+/// assert_eq!(csv.lines().count(), 40);
+///
+/// // 同じseed値で生成した乱数列と一致する
+/// let scenario = Scenario::parse_toml_str(&scenario_str).unwrap();
+/// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+/// assert_eq!(csv, randoms.to_csv_string().unwrap());
+///
+/// // 不正なシナリオはエラー
+/// assert!(generate_csv_string("n = ", Seed(42)).is_err());
+/// ```
+pub fn generate_csv_string(scenario_str: &str, seed: norm::Seed) -> Result<String, ScenarioError> {
+    let scenario = process_param::norm::Scenario::parse_toml_str(scenario_str).map_err(|e| ScenarioError::new(
+        ScenarioErrorKind::Parse,
+        format!("Invalid scenario: {e}")
+    ))?;
+    let randoms = norm::RandomScenario::from_scenario_seed(&scenario, seed)?;
+    randoms.to_csv_string()
+}
+
+
+/// seed値の記録から乱数列を再生成してファイルに出力
+///
+/// [`gen_norm_rand_csv`]等が出力した「seed.txt」の`file,seed`の記録を読み込み，
+/// 各seed値で[`norm::RandomScenario::from_scenario_seed`]により乱数列を再生成する．
+/// `seed_file`の拡張子が`.json`の場合は，[`SeedFormat::Json`]で出力したJSON形式の記録として読み込む．
+/// 再生成した乱数列は，記録されたファイル名で`dir_out`に出力する．
+/// 出力形式は記録されたファイルの拡張子（`.csv`，`.csv.gz`，`.toml`）で決まる．
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `seed_file` - seed値を記録したファイルのパス
+/// * `dir_out`- 出力するディレクトリ名（存在しない場合は作成する）
+///
+/// # 注意
+/// seed値は10進数または`0x`から始まる16進数で記録されている必要があり，`u64`に収まらない場合はエラーとなります．
+/// 管理図を併用した乱数列の再生成には[`regenerate_controlchart_from_seed_file`]を利用してください．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::{gen_norm_rand_csv, gen_norm_rand_csv_with, regenerate_from_seed_file, GenOptions, SeedFormat};
+/// # use std::path::Path;
+/// # use std::fs::{remove_dir_all, read_to_string};
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let dir_out = Path::new("test/regenerate_from_seed_file_orig");
+/// let dir_regen = Path::new("test/regenerate_from_seed_file");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// # remove_dir_all(dir_regen.clone()).ok();
+/// gen_norm_rand_csv(&path_scenario, &dir_out, 3).unwrap();
+/// regenerate_from_seed_file(&path_scenario, &dir_out.join("seed.txt").as_path(), &dir_regen).unwrap();
+/// for i in 1..=3 {
+///     let name = format!("test_scenario_{}.csv", i);
+///     assert_eq!(read_to_string(dir_out.join(&name)).unwrap(), read_to_string(dir_regen.join(&name)).unwrap());
+/// }
+///
+/// // JSON形式で記録したseed値から再生成
+/// let dir_out_json = Path::new("test/regenerate_from_seed_file_json_orig");
+/// let dir_regen_json = Path::new("test/regenerate_from_seed_file_json");
+/// # remove_dir_all(dir_out_json.clone()).ok();
+/// # remove_dir_all(dir_regen_json.clone()).ok();
+/// let options = GenOptions { seed_format: SeedFormat::Json, ..GenOptions::default() };
+/// gen_norm_rand_csv_with(&path_scenario, &dir_out_json, 3, &options).unwrap();
+/// regenerate_from_seed_file(&path_scenario, &dir_out_json.join("seed.json").as_path(), &dir_regen_json).unwrap();
+/// for i in 1..=3 {
+///     let name = format!("test_scenario_{}.csv", i);
+///     assert_eq!(read_to_string(dir_out_json.join(&name)).unwrap(), read_to_string(dir_regen_json.join(&name)).unwrap());
+/// }
+/// ```
+pub fn regenerate_from_seed_file<P: AsRef<Path>>(path_scenario: &P, seed_file: &P, dir_out: &P) -> Result<(), Box<dyn std::error::Error>> {
+    regenerate_with(path_scenario, seed_file, dir_out, norm::RandomScenario::from_scenario_seed)
+}
+
+
+/// seed値の記録から管理図を併用した乱数列を再生成してファイルに出力
+///
+/// [`regenerate_from_seed_file`]と同様だが，[`norm::RandomScenario::from_scenario_seed_controlchart`]により再生成する．
+/// [`gen_norm_rand_controlchart_csv`]等が出力した「seed.txt」に対して利用する．
+///
+/// # 引数
+/// * `path_scenario` - 乱数生成のシナリオが記述されたTOMLファイルのパス
+/// * `seed_file` - seed値を記録したファイルのパス
+/// * `dir_out`- 出力するディレクトリ名（存在しない場合は作成する）
+pub fn regenerate_controlchart_from_seed_file<P: AsRef<Path>>(path_scenario: &P, seed_file: &P, dir_out: &P) -> Result<(), Box<dyn std::error::Error>> {
+    regenerate_with(path_scenario, seed_file, dir_out, norm::RandomScenario::from_scenario_seed_controlchart)
+}
+
+
+// seed値の記録（CSV形式またはJSON形式）を読み込み，`generate`で再生成した乱数列を出力
+fn regenerate_with<P, F>(path_scenario: &P, seed_file: &P, dir_out: &P, generate: F) -> Result<(), Box<dyn std::error::Error>>
+where
+    P: AsRef<Path>,
+    F: Fn(&process_param::norm::Scenario, norm::Seed) -> Result<norm::RandomScenario, process_param::ScenarioError> + Sync
+{
+    let scenario = norm::load_scenario(path_scenario)?;
+    norm::validate(&scenario)?;
+    create_dir_all(dir_out)?;
+
+    // seed値の記録の読み込み
+    let mut records = Vec::new();
+    for record in read_seed_file(seed_file.as_ref())? {
+        let name = match Path::new(&record.file).file_name() {
+            Some(name) => name.to_owned(),
+            None => return Err(Box::new(ScenarioError::new(ScenarioErrorKind::Parse, format!("Invalid file name in seed file: {}", record.file)))),
+        };
+        records.push((dir_out.as_ref().join(name), record.seed));
+    }
+
+    records.par_iter()
+           .map(|(path, seed)| {
+               let randoms = generate(&scenario, *seed)?;
+               let name = path.to_str().unwrap();
+               let result = if name.ends_with(".toml") {
+                   randoms.to_toml(path)
+               } else if name.ends_with(".csv.gz") {
+                   write_csv_gz(&randoms, path)
+               } else {
+                   randoms.to_csv(path)
+               };
+               result.map_err(|e| path_error(path, e))
+           })
+           .collect::<Result<Vec<()>, ScenarioError>>()?;
+    Ok(())
+}
+
+
+// seed値の記録を読み込み．拡張子が`.json`の場合はJSON形式，それ以外はCSV形式とする
+// CSV形式のseed値は10進数または`0x`から始まる16進数の文字列として読み込む
+fn read_seed_file(seed_file: &Path) -> Result<Vec<SeedRecord>, Box<dyn std::error::Error>> {
+    if seed_file.extension().and_then(|e| e.to_str()) == Some("json") {
+        return Ok(serde_json::from_reader(BufReader::new(File::open(seed_file)?))?)
+    }
+    #[derive(serde::Deserialize)]
+    struct SeedRecordStr {
+        file: String,
+        seed: String,
+    }
+    let mut rdr = csv::Reader::from_path(seed_file)?;
+    let mut records = Vec::new();
+    for result in rdr.deserialize() {
+        let record: SeedRecordStr = result?;
+        let seed = match record.seed.trim().parse::<norm::Seed>() {
+            Ok(s) => s,
+            Err(e) => return Err(Box::new(ScenarioError::new(ScenarioErrorKind::Parse, format!("Invalid seed of {}: {}", record.file, e)).with_source(e))),
+        };
+        records.push(SeedRecord { file: record.file, seed });
+    }
+    Ok(records)
+}
+
+
+// seed値の記録（seed.txtの1行）
+#[derive(Serialize, Deserialize)]
+struct SeedRecord {
+    file: String,
+    seed: norm::Seed,
+}
+
+
+// seed値の逐次記録（seed値のファイル名.partialの1行）
+// 行数はファイルが末尾まで書き出されたかの確認に用いる
+#[derive(Serialize, Deserialize)]
+struct JournalRecord {
+    file: String,
+    seed: norm::Seed,
+    rows: usize,
+}
+
+
+// 書き出しを終えたファイルのseed値を1件ずつ追記する
+// 追記のたびにフラッシュするため，プロセスが異常終了しても書き出し済みのファイルの記録は残る
+// `wtr`が`None`の場合は何も記録しない
+struct SeedJournal {
+    path: PathBuf,
+    wtr: Option<Mutex<csv::Writer<File>>>,
+}
+
+impl SeedJournal {
+    // 逐次記録を開き，以前の記録に追記する
+    fn open(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = std::fs::OpenOptions::new()
+                       .create(true)
+                       .append(true)
+                       .open(&path)?;
+        let wtr = csv::WriterBuilder::new().has_headers(false).from_writer(file);
+        Ok(SeedJournal { path, wtr: Some(Mutex::new(wtr)) })
+    }
+
+    // 何も記録しない逐次記録
+    fn disabled() -> Self {
+        SeedJournal { path: PathBuf::new(), wtr: None }
+    }
+
+    // 書き出しを終えたファイルのseed値と行数を追記
+    fn append(&self, path: &PathBuf, seed: norm::Seed, rows: usize) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(wtr) = &self.wtr {
+            let record = JournalRecord { file: path.to_str().unwrap().to_string(), seed, rows };
+            let mut wtr = wtr.lock().unwrap_or_else(|e| e.into_inner());
+            wtr.serialize(record)?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+
+    // seed値のファイルを出力し終えたら，逐次記録は不要となるため削除する
+    fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(wtr) = self.wtr {
+            drop(wtr);
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    // 逐次記録を読み込み．ファイルが無い場合は空とする
+    // 異常終了で最終行が途中までしか書かれていない場合は，その行を読み飛ばす
+    fn read(path: &Path) -> Result<Vec<JournalRecord>, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Vec::new())
+        }
+        let mut rdr = csv::ReaderBuilder::new().has_headers(false).from_path(path)?;
+        Ok(rdr.deserialize().filter_map(|r| r.ok()).collect())
+    }
+}
+
+
+// 出力済みのファイルが末尾まで書き出されているか
+// 空でなく改行で終わり，行数が分かる場合はその行数と一致する必要がある
+fn is_complete_csv(path: &Path, rows: Option<usize>) -> bool {
+    match read_output(path) {
+        Ok(bytes) => !bytes.is_empty()
+                     && bytes.ends_with(b"\n")
+                     && rows.map_or(true, |n| bytes.iter().filter(|b| **b == b'\n').count() == n),
+        Err(_) => false,
+    }
+}
+
+// 出力済みのファイルの内容．gzipで圧縮したファイルは展開する
+#[cfg(feature = "gzip")]
+fn read_output(path: &Path) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut bytes = Vec::new();
+    if path.extension().map_or(false, |ext| ext == "gz") {
+        flate2::read::GzDecoder::new(BufReader::new(File::open(path)?)).read_to_end(&mut bytes)?;
+    } else {
+        BufReader::new(File::open(path)?).read_to_end(&mut bytes)?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn read_output(path: &Path) -> std::io::Result<Vec<u8>> {
+    std::fs::read(path)
+}
+
+
+// seed値の記録を(ファイルパス, seed値)の組に変換
+fn seed_list(records: Vec<SeedRecord>) -> Vec<(PathBuf, norm::Seed)> {
+    records.into_iter().map(|r| (PathBuf::from(r.file), r.seed)).collect()
+}
+
+
+// 出力済みと新たに出力したseed値の記録を合わせ，ファイル番号順に並べる
+fn merge_seed_records(paths: &[PathBuf], done: Vec<SeedRecord>, new_records: Vec<SeedRecord>) -> Vec<SeedRecord> {
+    let order: HashMap<&str, usize> = paths.iter()
+                                           .enumerate()
+                                           .filter_map(|(i, p)| p.to_str().map(|f| (f, i)))
+                                           .collect();
+    let mut records: Vec<SeedRecord> = done.into_iter().chain(new_records).collect();
+    records.sort_by_key(|r| order.get(r.file.as_str()).copied());
+    records
+}
+
+
+// 乱数列を並列にファイルへ書き出し，ファイル番号順にseed値の記録を返す
+// `write`は書き出した乱数列のseed値を返す
+// csv::Writer等はスレッド間で共有せず，ファイルごとに`write`の内部で作成する
+fn write_randoms_par<T, F>(randoms: &[T], paths: &[PathBuf], write: F) -> Result<Vec<SeedRecord>, ScenarioError>
+where
+    T: Sync,
+    F: Fn(&T, &PathBuf) -> Result<norm::Seed, Box<dyn std::error::Error>> + Sync
+{
+    randoms.par_iter()
+           .zip(paths.par_iter())
+           .map(|(r, path)| match write(r, path) {
+               Ok(seed) => Ok(SeedRecord {file: path.to_str().unwrap().to_string(), seed}),
+               Err(e) => Err(ScenarioError::new(ScenarioErrorKind::Other, format!("{:?}: {}", path, e))),
+           })
+           .collect()
+}
+
+
+// 生成を終えて書き出しを待つ乱数列の上限（チャネルの容量）
+// 保持する乱数列はチャネル内のこの個数と，生成中・書き出し中のものに限られるため，メモリ使用量は出力するファイル数によらない
+#[cfg(feature = "parallel")]
+const PIPELINE_DEPTH: usize = 64;
+
+
+// 乱数列の生成とファイルへの書き出しを並行して行い，ファイル番号順にseed値の記録とmanifest.jsonの要素を返す
+// rayonのワーカーが`seeds`から乱数列を生成して容量`PIPELINE_DEPTH`のチャネルに送り，
+// 書き出し用のスレッドがチャネルから受け取った順に`write`で`paths`の同じ番号のファイルへ書き出す
+// 全ての乱数列を生成してから書き出すのではなく，生成と書き出しを重ねることで，同時に保持する乱数列を上限までに抑える
+// 書き出し用のスレッドはrayonのスレッドプールの外で動くため，`scenario_parallelism`によるスレッド数の上限を崩さないよう1本に限る
+#[cfg(feature = "parallel")]
+fn gen_write_pipeline<F>(scenario: &process_param::norm::Scenario, seeds: &[norm::Seed], paths: &[PathBuf], write: F) -> Result<Vec<(SeedRecord, ManifestEntry)>, ScenarioError>
+where
+    F: Fn(&norm::RandomScenario, &PathBuf) -> Result<(), Box<dyn std::error::Error>> + Sync
+{
+    // 全ての乱数列で1つのシナリオを共有する
+    let shared = Arc::new(scenario.clone());
+    let (sender, receiver) = crossbeam_channel::bounded::<(usize, Result<norm::RandomScenario, ScenarioError>)>(PIPELINE_DEPTH);
+    // 書き出し用のスレッドが失敗したら，生成側はそれ以降の乱数列を生成しない
+    let failed = AtomicBool::new(false);
+    std::thread::scope(|s| {
+        let (write, failed) = (&write, &failed);
+        let writer = s.spawn(move || -> Result<Vec<(usize, (SeedRecord, ManifestEntry))>, ScenarioError> {
+            let mut written = Vec::with_capacity(paths.len());
+            for (i, randoms) in receiver {
+                let path = &paths[i];
+                let result = randoms.and_then(|randoms| {
+                    write(&randoms, path).map_err(|e| path_error(path, e))?;
+                    Ok((SeedRecord {file: path.to_str().unwrap().to_string(), seed: randoms.get_seed()}, ManifestEntry::new(&randoms, path)))
+                });
+                match result {
+                    Ok(w) => written.push((i, w)),
+                    Err(e) => {
+                        failed.store(true, Ordering::Relaxed);
+                        return Err(e)
+                    },
+                }
+            }
+            Ok(written)
+        });
+        // 送信の失敗は書き出し側が終了したことを表し，そのエラーは`join`で受け取る
+        let _ = seeds.par_iter()
+                     .enumerate()
+                     .try_for_each(|(i, seed)| {
+                         if failed.load(Ordering::Relaxed) {
+                             return Err(());
+                         }
+                         let randoms = norm::RandomScenario::from_shared_scenario_seed(&shared, *seed).map_err(ScenarioError::from);
+                         sender.send((i, randoms)).map_err(|_| ())
+                     });
+        drop(sender);
+        let mut written = writer.join().unwrap_or_else(|e| std::panic::resume_unwind(e))?;
+        // 書き出しの順によらずファイル番号順に並べる
+        written.sort_by_key(|(i, _)| *i);
+        Ok(written.into_iter().map(|(_, w)| w).collect())
+    })
+}
+
+// `parallel` featureが無効な場合は，乱数列を1つずつ生成して書き出す
+#[cfg(not(feature = "parallel"))]
+fn gen_write_pipeline<F>(scenario: &process_param::norm::Scenario, seeds: &[norm::Seed], paths: &[PathBuf], write: F) -> Result<Vec<(SeedRecord, ManifestEntry)>, ScenarioError>
+where
+    F: Fn(&norm::RandomScenario, &PathBuf) -> Result<(), Box<dyn std::error::Error>>
+{
+    let shared = Arc::new(scenario.clone());
+    seeds.iter()
+         .zip(paths.iter())
+         .map(|(seed, path)| {
+             let randoms = norm::RandomScenario::from_shared_scenario_seed(&shared, *seed)?;
+             write(&randoms, path).map_err(|e| path_error(path, e))?;
+             Ok((SeedRecord {file: path.to_str().unwrap().to_string(), seed: *seed}, ManifestEntry::new(&randoms, path)))
+         })
+         .collect()
+}
+
+
+// 出力したファイルの一覧（manifest.jsonの1要素）
+#[derive(Serialize)]
+struct ManifestEntry {
+    file: String,
+    seed: norm::Seed,
+    num_timepoints: usize,
+    signal_index: Option<usize>,
+}
+
+impl ManifestEntry {
+    // 乱数列と出力先のパスから作成
+    fn new(randoms: &norm::RandomScenario, path: &PathBuf) -> Self {
+        ManifestEntry{
+            file: path.to_str().unwrap().to_string(),
+            seed: randoms.get_seed(),
+            num_timepoints: randoms.rand_vars().len(),
+            signal_index: randoms.signal().map(|s| s.index),
+        }
+    }
+}
+
+
+// 出力したファイルの一覧をmanifest.jsonに書き出し
+// 各ファイルのパス，seed値，時点数，（管理図を併用した場合は）管理外れ状態を検出した時点を記録する
+fn wtr_manifest<P: AsRef<Path>>(path_dir: &P, randoms: &[norm::RandomScenario], paths: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+    let entries: Vec<ManifestEntry> = randoms.iter()
+                                             .zip(paths.iter())
+                                             .map(|(r, path)| ManifestEntry::new(r, path))
+                                             .collect();
+    wtr_manifest_entries(path_dir, &entries)
+}
+
+
+// manifest.jsonの要素を書き出し
+fn wtr_manifest_entries<P: AsRef<Path>>(path_dir: &P, entries: &[ManifestEntry]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut wtr_mf = BufWriter::new(File::create(
+        path_dir.as_ref().join(Path::new("manifest.json"))
+        )?);
+    serde_json::to_writer_pretty(&mut wtr_mf, entries)?;
+    wtr_mf.flush()?;
+
+    Ok(())
+}
+
+
+// ファイルの内容のSHA-256を16進数の文字列で返す
+#[cfg(feature = "checksum")]
+fn sha256_hex<P: AsRef<Path>>(path: &P) -> Result<String, Box<dyn std::error::Error>> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut BufReader::new(File::open(path)?), &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+
+// 出力したファイルのSHA-256をchecksums.txtに書き出し
+// 書式は`sha256sum`コマンドと同じく「ハッシュ値  ファイル名」とする
+#[cfg(feature = "checksum")]
+fn wtr_checksums<P: AsRef<Path>>(path_dir: &P, paths: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+    let lines = paths.par_iter()
+                     .map(|path| {
+                         let name = path.file_name().unwrap().to_str().unwrap();
+                         let hash = sha256_hex(path).map_err(|e| path_error(path, e))?;
+                         Ok(format!("{}  {}\n", hash, name))
+                     })
+                     .collect::<Result<Vec<String>, ScenarioError>>();
+    let mut wtr_cs = BufWriter::new(File::create(
+        path_dir.as_ref().join(Path::new("checksums.txt"))
+        )?);
+    for line in lines? {
+        wtr_cs.write_all(line.as_bytes())?;
+    }
+    wtr_cs.flush()?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "checksum"))]
+fn wtr_checksums<P: AsRef<Path>>(_path_dir: &P, _paths: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+    Err(Box::new(ScenarioError::new(ScenarioErrorKind::Other, "Checksums require the `checksum` feature.")))
+}
+
+
+/// 「checksums.txt」に記録されたSHA-256と，ディレクトリ内の各ファイルを照合
+///
+/// # 引数
+/// * `dir` - 「checksums.txt」を含むディレクトリ
+///
+/// # 注意
+/// SHA-256が一致しないファイルおよび存在しないファイルのパスを返します．
+/// 全てのファイルが一致した場合は空のベクタを返します．
+/// `checksum` featureが必要です．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::{gen_norm_rand_csv_with, verify_checksums, GenOptions};
+/// # use std::path::Path;
+/// # use std::fs::remove_dir_all;
+/// let path_scenario = Path::new("test/test_scenario.toml");
+/// let dir_out = Path::new("test/verify_checksums");
+/// # remove_dir_all(dir_out.clone()).ok();
+/// let options = GenOptions { with_checksums: true, ..GenOptions::default() };
+/// gen_norm_rand_csv_with(&path_scenario, &dir_out, 3, &options).unwrap();
+/// assert!(verify_checksums(&dir_out).unwrap().is_empty());
+///
+/// // ファイルを書き換えると不一致として報告される
+/// std::fs::write(dir_out.join("test_scenario_2.csv"), "corrupted").unwrap();
+/// assert_eq!(verify_checksums(&dir_out).unwrap(), vec![dir_out.join("test_scenario_2.csv")]);
+/// ```
+#[cfg(feature = "checksum")]
+pub fn verify_checksums<P: AsRef<Path>>(dir: &P) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let dir = dir.as_ref();
+    let contents = std::fs::read_to_string(dir.join(Path::new("checksums.txt")))?;
+    let mut mismatches = Vec::new();
+    for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+        let (hash, name) = match line.split_once("  ") {
+            Some(pair) => pair,
+            None => return Err(Box::new(ScenarioError::new(ScenarioErrorKind::Parse, format!("Invalid line in checksums.txt: {:?}", line)))),
+        };
+        let path = dir.join(Path::new(name));
+        match sha256_hex(&path) {
+            Ok(actual) if actual == hash => (),
+            _ => mismatches.push(path),
+        }
+    }
+    Ok(mismatches)
+}
+
+
+// 正規分布に従うプロセスについて，sigma_multシグマの管理限界の情報
+fn norm_control_limit_info(scenario: &process_param::norm::Scenario, sigma_mult: f64) -> Result<String, Box<dyn std::error::Error>> {
+    let (mu_0, sigma_0_2) = scenario.param_in_control();
+    let norm::ControlLimits { lcl_xbar, ucl_xbar, lcl_s, ucl_s } = norm::control_limits(scenario, sigma_mult)?;
+    Ok(format!("μ_0, {mu_0}\nσ_0^2, {sigma_0_2}\n\nbarX control chart\nLCL, {lcl_xbar}\nUCL, {ucl_xbar}\n\ns control chart\nLCL, {lcl_s}\nUCL, {ucl_s}"))
+}
+
+// 正規分布に従うプロセスについて，sigma_multシグマの管理限界の情報を書き出し
+fn wtr_norm_control_limit<P: AsRef<Path>>(path_dir: &P, file_name: &str, scenario: &process_param::norm::Scenario, sigma_mult: f64) -> Result<(), Box<dyn std::error::Error>> {
+    let cl_info = norm_control_limit_info(scenario, sigma_mult)?;
+    let mut wtr_cl = BufWriter::new(File::create(
+        path_dir.as_ref().join(Path::new(file_name))
+        )?);
     wtr_cl.write_all(cl_info.as_bytes())?;
     wtr_cl.flush()?;
     
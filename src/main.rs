@@ -2,21 +2,264 @@ extern crate rand_scenario;
 use std::path::Path;
 use std::str::FromStr;
 use std::env;
-use rand_scenario::gen_norm_rand_csv;
+use std::process;
+use std::io::{self, Read, Write};
+use rand_scenario::{gen_norm_rand_csv_with, gen_norm_rand_csv_from_str, gen_norm_rand_csv_dir_with, gen_norm_rand_zip, gen_norm_rand_tar_gz, plan_norm_rand_csv, generate_batch, GenOptions};
+use process_param::norm::Scenario;
+use rand_scenario::norm;
+
 fn main() {
-    println!("Generate random variables with scenario.");
+    let args: Vec<String> = env::args().skip(1).collect();
+    // サブコマンドの判定（省略時はgenerate）
+    match args.first().map(|a| a.as_str()) {
+        Some("validate") => validate(&args[1..]),
+        Some("convert") => convert(&args[1..]),
+        Some("generate") => generate(&args[1..]),
+        _ => generate(&args),
+    }
+}
+
+// シナリオのファイル形式を変換（TOML ⇄ JSON）
+fn convert(args: &[String]) {
+    if args.len() != 2 {
+        eprintln!("Error: Need just 2 argments\n\tFor example...\n\tcargo run convert scenario.toml scenario.json");
+        process::exit(1);
+    }
+    if let Err(err) = norm::convert_scenario(&Path::new(&args[0]), &Path::new(&args[1])) {
+        eprintln!("Error: Scenario {} cannot be converted: {}", args[0], err);
+        process::exit(1);
+    }
+    println!("Scenario {} is converted to {}.", args[0], args[1]);
+}
+
+// シナリオを検証し，変化点と各区間のパラメータを表示
+fn validate(args: &[String]) {
+    if args.len() != 1 {
+        eprintln!("Error: Need just 1 argment\n\tFor example...\n\tcargo run validate scenario.toml");
+        process::exit(1);
+    }
+    let path_scenario = Path::new(&args[0]);
+    let scenario = match norm::load_scenario(&path_scenario) {
+        Ok(s) => s,
+        Err(err) => {
+            eprintln!("Error: Scenario {} cannot be parsed: {}", args[0], err);
+            process::exit(1);
+        },
+    };
+    if let Err(err) = norm::validate(&scenario) {
+        eprintln!("Error: Scenario {} is invalid: {}", args[0], err);
+        process::exit(1);
+    }
+
+    // validateが成功していれば変化点とパラメータの展開は成功する
+    let change_points = norm::change_points(&scenario).unwrap();
+    let params = scenario.decomplession().unwrap();
+    println!("Scenario {} is valid.", args[0]);
+    println!("n = {}, horizon = {}", scenario.n(), params.len());
+    println!("change points = {:?}", change_points);
+    let starts = std::iter::once(0).chain(change_points.iter().cloned());
+    let ends = change_points.iter().cloned().chain(std::iter::once(params.len()));
+    for (i, (start, end)) in starts.zip(ends).enumerate() {
+        println!("segment {}: t = {}..{}, start = {:?}, end = {:?}", i + 1, start, end, params[start], params[end - 1]);
+    }
+}
+
+// シナリオから乱数列を生成してCSVファイルに出力
+fn generate(args: &[String]) {
     // 引数の確認
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 4 {
-        panic!("Error: Need just 3 argments\n\tFor example...\n\tcargo run scenario.toml outdir number_of_files(such as 10)");
+    let (flags, positional): (Vec<&String>, Vec<&String>) = args.iter().partition(|a| a.starts_with("--"));
+    if positional.len() != 3 {
+        panic!("Error: Need just 3 argments\n\tFor example...\n\tcargo run scenario.toml outdir number_of_files(such as 10) [--gzip] [--manifest] [--zero-pad] [--resume] [--checksums] [--dry-run] [--zip] [--tar-gz] [--scenario-parallelism=N]");
+    }
+    // 出力先に"-"を指定した場合は標準出力に書き出すため，標準出力には乱数列以外を出力しない
+    let to_stdout = positional[1] == "-";
+    if !to_stdout {
+        println!("Generate random variables with scenario.");
+    }
+    let mut options = GenOptions::default();
+    let mut dry_run = false;
+    let mut zip = false;
+    let mut tar_gz = false;
+    for flag in flags {
+        match flag.as_str() {
+            "--gzip" => options.gzip = true,
+            "--manifest" => options.with_manifest = true,
+            "--zero-pad" => options.zero_pad = true,
+            "--resume" => options.resume = true,
+            "--checksums" => options.with_checksums = true,
+            "--dry-run" => dry_run = true,
+            "--zip" => zip = true,
+            "--tar-gz" => tar_gz = true,
+            _ => match flag.strip_prefix("--scenario-parallelism=") {
+                Some(threads) => options.scenario_parallelism = Some(usize::from_str(threads).expect("--scenario-parallelism requires a number of threads such as --scenario-parallelism=4.")),
+                None => panic!("Error: Unknown option {}", flag),
+            },
+        }
+    }
+    let dir_out = Path::new(positional[1]);
+    let num = usize::from_str(positional[2]).expect("Third argument is the number of file to be generated. Therefore, a numberis required.");
+
+    // シナリオに"-"を指定した場合は標準入力から読み込む
+    let scenario_toml = if positional[0] == "-" {
+        let mut scenario_toml = String::new();
+        io::stdin().read_to_string(&mut scenario_toml).expect("Scenario cannot be read from stdin.");
+        Some(scenario_toml)
+    } else {
+        None
+    };
+
+    // 実行せずに計画のみ表示
+    // 計画は1つのシナリオからディレクトリへ出力する場合のみ作成できるため，他の出力方法との併用はエラーとする
+    if dry_run {
+        if to_stdout || zip || tar_gz || Path::new(positional[0]).is_dir() {
+            eprintln!("Error: --dry-run can be used only when generating CSV files from one scenario into a directory.");
+            process::exit(1);
+        }
+        print_plan(positional[0], scenario_toml.as_deref(), &dir_out, num, &options);
+        return;
+    }
+
+    // 1つの乱数列を標準出力に書き出す
+    if to_stdout {
+        if num != 1 {
+            eprintln!("Error: Output \"-\" (stdout) can be used only when the number of files is 1, but {} is given.", num);
+            process::exit(1);
+        }
+        write_stdout(positional[0], scenario_toml.as_deref());
+        return;
+    }
+
+    // シナリオにディレクトリを指定した場合は，ディレクトリ内の全てのシナリオから生成する
+    if Path::new(positional[0]).is_dir() {
+        generate_dir(positional[0], &dir_out, num, &options);
+        return;
+    }
+
+    // 1つのzipファイルに出力する
+    if zip {
+        write_archive(positional[0], scenario_toml.is_some(), &archive_path(positional[1], "zip"), num, &options, gen_norm_rand_zip);
+        return;
+    }
+    // 1つのtar.gzファイルに出力する
+    if tar_gz {
+        write_archive(positional[0], scenario_toml.is_some(), &archive_path(positional[1], "tar.gz"), num, &options, gen_norm_rand_tar_gz);
+        return;
     }
-    let path_scenario = Path::new(&args[1]);
-    let dir_out = Path::new(&args[2]);
-    let num = usize::from_str(&args[3]).expect("Third argument is the number of file to be generated. Therefore, a numberis required.");
 
     // ファイル生成
-    match gen_norm_rand_csv(&path_scenario, &dir_out, num) {
-            Ok(_) => println!("Number of {} files generated at {}.", num, &args[2]),
+    let result = match &scenario_toml {
+        Some(scenario_toml) => gen_norm_rand_csv_from_str(scenario_toml, "stdin", &dir_out, num, &options),
+        None => gen_norm_rand_csv_with(&Path::new(positional[0]), &dir_out, num, &options),
+    };
+    match result {
+            Ok(_) => println!("Number of {} files generated at {}.", num, positional[1]),
             Err(err) => panic!("{:?}", err),
     }
 }
+
+// ディレクトリ内の全てのシナリオから乱数列を生成し，シナリオごとの成否を表示
+// 1つでも失敗したシナリオがあれば終了コード1で終了する
+fn generate_dir(scenario_dir: &str, out_root: &Path, num: usize, options: &GenOptions) {
+    let results = match gen_norm_rand_csv_dir_with(&Path::new(scenario_dir), &out_root, num, options) {
+        Ok(results) => results,
+        Err(err) => {
+            eprintln!("Error: Scenario directory {} cannot be read: {}", scenario_dir, err);
+            process::exit(1);
+        },
+    };
+    let mut num_failed = 0;
+    for r in results.iter() {
+        match &r.result {
+            Ok(files) => println!("OK    {:?}: {} files generated at {:?}", r.scenario, files.len(), r.dir_out),
+            Err(err) => {
+                num_failed += 1;
+                println!("ERROR {:?}: {}", r.scenario, err);
+            },
+        }
+    }
+    println!("{} of {} scenarios succeeded.", results.len() - num_failed, results.len());
+    if num_failed > 0 {
+        process::exit(1);
+    }
+}
+
+// アーカイブのパス．出力先が拡張子`ext`で終わらない場合は付け加える
+fn archive_path(dir_out: &str, ext: &str) -> String {
+    if dir_out.ends_with(&format!(".{}", ext)) {
+        dir_out.to_string()
+    } else {
+        format!("{}.{}", dir_out, ext)
+    }
+}
+
+// 全てのcsvファイルとseed値の記録を1つのアーカイブに出力
+fn write_archive<F>(scenario_arg: &str, from_stdin: bool, path_archive: &str, num: usize, options: &GenOptions, gen: F)
+    where F: Fn(&&Path, &&Path, usize, &GenOptions) -> Result<Vec<(std::path::PathBuf, norm::Seed)>, Box<dyn std::error::Error>>
+{
+    if from_stdin {
+        eprintln!("Error: Archive output requires a scenario file, not stdin.");
+        process::exit(1);
+    }
+    match gen(&Path::new(scenario_arg), &Path::new(path_archive), num, options) {
+        Ok(_) => println!("Number of {} files generated in {}.", num, path_archive),
+        Err(err) => {
+            eprintln!("Error: Archive {} cannot be generated: {}", path_archive, err);
+            process::exit(1);
+        },
+    }
+}
+
+// 乱数列を1つ生成し，CSV形式で標準出力に書き出す
+// 再現できるよう，seed値は標準エラー出力に表示する
+fn write_stdout(scenario_arg: &str, scenario_toml: Option<&str>) {
+    let scenario = match scenario_toml {
+        Some(scenario_toml) => Scenario::parse_toml_str(scenario_toml).map_err(|e| e.to_string()),
+        None => norm::load_scenario(&Path::new(scenario_arg)).map_err(|e| e.to_string()),
+    };
+    let csv = scenario.and_then(|s| generate_batch(&s, 1).map_err(|e| e.to_string()))
+                      .and_then(|randoms| {
+                          eprintln!("seed = {}", randoms[0].get_seed());
+                          randoms[0].to_csv_string().map_err(|e| e.to_string())
+                      });
+    match csv {
+        Ok(csv) => {
+            if let Err(err) = io::stdout().lock().write_all(csv.as_bytes()) {
+                eprintln!("Error: Random variables cannot be written to stdout: {}", err);
+                process::exit(1);
+            }
+        },
+        Err(err) => {
+            eprintln!("Error: Random variables cannot be generated from scenario {}: {}", scenario_arg, err);
+            process::exit(1);
+        },
+    }
+}
+
+// 乱数を生成せずに，出力するファイル，変化点，ディスク使用量の見積もりを表示
+fn print_plan(scenario_arg: &str, scenario_toml: Option<&str>, dir_out: &Path, num: usize, options: &GenOptions) {
+    let (scenario, filename) = match scenario_toml {
+        Some(scenario_toml) => (Scenario::parse_toml_str(scenario_toml).map_err(|e| e.to_string()), "stdin".to_string()),
+        None => {
+            let path_scenario = Path::new(scenario_arg);
+            (norm::load_scenario(&path_scenario).map_err(|e| e.to_string()),
+             path_scenario.file_stem().unwrap().to_str().unwrap().to_string())
+        },
+    };
+    let plan = scenario.and_then(|s| plan_norm_rand_csv(&s, &filename, &dir_out, num, options).map_err(|e| e.to_string()));
+    let plan = match plan {
+        Ok(plan) => plan,
+        Err(err) => {
+            eprintln!("Error: Plan for scenario {} cannot be made: {}", scenario_arg, err);
+            process::exit(1);
+        },
+    };
+    println!("Dry run: nothing is written.");
+    println!("Output directory: {:?}", dir_out);
+    println!("Number of files: {}", plan.files.len());
+    for file in plan.files.iter() {
+        println!("  {:?}", file);
+    }
+    println!("Change points: {:?}", plan.change_points);
+    println!("Estimated disk usage: {} bytes", plan.estimated_bytes);
+    println!("Estimated memory usage: {} bytes", plan.estimated_memory);
+}
@@ -0,0 +1,356 @@
+//! 多変量正規分布に従う乱数生成プログラム
+//!
+//! 各時点で$ p $次元のベクトル観測値を$ n $個生成する．
+//! 標準正規乱数は[`crate::uniform_stream`]と同じ一様乱数からBox-Muller法で生成し，
+//! 区間ごとの分散共分散行列$ \Sigma = L L^\top $のCholesky分解$ L $を用いて
+//! $ \boldsymbol{x} = \boldsymbol{\mu} + L \boldsymbol{z} $と変換する．
+//!
+//! # シナリオの書式
+//! 区間の終端となる変化点`tau`，平均ベクトル`mean`，分散共分散行列`cov`を並べる．
+//!
+//! ```toml
+//! n = 5
+//! parameter = [
+//!     {tau = 15, mean = [0.0, 0.0], cov = [[1.0, 0.5], [0.5, 1.0]]},
+//!     {tau = 30, mean = [1.0, 0.0], cov = [[1.0, 0.5], [0.5, 1.0]]},
+//! ]
+//! ```
+
+use super::{GenOptions, ScenarioError, ScenarioErrorKind};
+use super::piecewise;
+
+extern crate serde;
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+use std::fs;
+extern crate toml;
+
+extern crate process_param;
+
+pub use super::norm::Seed;
+
+
+/// 多変量正規分布に従う乱数生成のシナリオ
+///
+/// # 引数
+/// * `n` - サンプル・サイズ
+/// * `parameter` - 各区間のパラメータ．変化点の昇順に並べる．
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    n: usize,
+    parameter: Vec<ChangePoint>,
+}
+
+/// 多変量正規分布のシナリオにおける1区間
+///
+/// # 引数
+/// * `tau` - 区間の終端となる時点
+/// * `mean` - 平均ベクトル
+/// * `cov` - 分散共分散行列
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChangePoint {
+    tau: usize,
+    mean: Vec<f64>,
+    cov: Vec<Vec<f64>>,
+}
+
+// 展開した区間の情報（区間長，平均ベクトル，Cholesky因子）
+struct Segment {
+    len: usize,
+    mean: Vec<f64>,
+    chol: Vec<Vec<f64>>,
+}
+
+impl Scenario {
+    /// サンプル・サイズを取得
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// 観測値の次元を取得
+    pub fn dim(&self) -> usize {
+        self.parameter.first().map_or(0, |cp| cp.mean.len())
+    }
+
+    /// TOMLファイルからシナリオを読み込み
+    ///
+    /// # 引数
+    /// * `path` - シナリオが記述されたTOMLファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::mvnorm::Scenario;
+    /// let path = std::path::Path::new("test/test_scenario_mvnorm.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// assert_eq!(scenario.dim(), 2);
+    /// ```
+    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+    /// TOML形式の文字列からシナリオを読み込み
+    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(toml::from_str(toml_str)?)
+    }
+
+    /// TOML形式の文字列に変換
+    pub fn to_toml_string(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    // 各区間の長さとCholesky因子を計算
+    fn segments(&self) -> Result<Vec<Segment>, ScenarioError> {
+        let p = self.dim();
+        let segments = piecewise::segments(&self.parameter, |cp| cp.tau, |cp| {
+            if cp.mean.len() != p || cp.cov.len() != p || cp.cov.iter().any(|row| row.len() != p) {
+                return Err(ScenarioError::new(
+                    ScenarioErrorKind::OutOfRange,
+                    format!("Dimension of mean or cov at tau = {} doesn't match p = {}.", cp.tau, p)
+                ));
+            }
+            Ok((cp.mean.clone(), cholesky(&cp.cov)?))
+        })?;
+        Ok(segments.into_iter()
+                   .map(|(len, (mean, chol))| Segment{ len, mean, chol })
+                   .collect())
+    }
+}
+
+
+// Cholesky分解 Σ = L L^T の下三角行列Lを計算
+// Σが対称でない場合，正定値でない場合はエラー
+fn cholesky(cov: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, ScenarioError> {
+    let p = cov.len();
+    // 分解には下三角部分のみを用いるため，上三角部分と一致することを先に確認する
+    for i in 0..p {
+        for j in 0..i {
+            if cov[i][j] != cov[j][i] {
+                return Err(ScenarioError::new(
+                    ScenarioErrorKind::OutOfRange,
+                    format!("Covariance matrix is not symmetric: cov[{i}][{j}] = {} but cov[{j}][{i}] = {}.", cov[i][j], cov[j][i])
+                ));
+            }
+        }
+    }
+    let mut l = vec![vec![0.0; p]; p];
+    for i in 0..p {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+            if i == j {
+                let d = cov[i][i] - sum;
+                if d.is_nan() || d <= 0.0 {
+                    return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Covariance matrix is not positive-definite: {:?}", cov)));
+                }
+                l[i][j] = d.sqrt();
+            } else {
+                l[i][j] = (cov[i][j] - sum) / l[j][j];
+            }
+        }
+    }
+    Ok(l)
+}
+
+
+/// シナリオを検証
+///
+/// 以下の場合はエラーを返す．
+/// * サンプル・サイズ`n`が正でない
+/// * パラメータ区間が無い
+/// * 変化点が正でない，または狭義単調増加でない
+/// * 平均ベクトル`mean`の次元が0，または各区間の`mean`，`cov`の次元が一致しない
+/// * 分散共分散行列`cov`が対称でない，または正定値でない
+///
+/// # 引数
+/// * `scenario` - 検証するシナリオ
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::mvnorm::{Scenario, validate};
+/// let path = std::path::Path::new("test/test_scenario_mvnorm.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// assert!(validate(&scenario).is_ok());
+///
+/// // 空の平均ベクトル
+/// let empty_mean = Scenario::parse_toml_str("n = 5\nparameter = [{tau = 10, mean = [], cov = []}]").unwrap();
+/// assert!(validate(&empty_mean).is_err());
+///
+/// // パラメータ区間が無い
+/// let empty = Scenario::parse_toml_str("n = 5\nparameter = []").unwrap();
+/// assert!(validate(&empty).is_err());
+/// ```
+pub fn validate(scenario: &Scenario) -> Result<(), ScenarioError> {
+    piecewise::validate_size(scenario.n, scenario.parameter.len())?;
+    if scenario.dim() == 0 {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Dimension of mean must be positive, but p = 0."));
+    }
+    scenario.segments()?;
+    Ok(())
+}
+
+
+/// シナリオから生成した多変量正規乱数を格納
+///
+/// # 引数
+/// * `scenario` - 乱数生成に利用したシナリオ
+/// * `seed` - 乱数生成に利用したシード値
+/// * `random_variables` - 生成された乱数列．`random_variables[t][i][j]`は時点tにおけるi番目のサンプルの第j成分．
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RandomScenario {
+    scenario: Scenario,
+    seed: Seed,
+    random_variables: Vec<Vec<Vec<f64>>>
+}
+
+extern crate rand_mt;
+use rand_mt::Mt64;
+impl RandomScenario {
+    /// 乱数列を取得
+    pub fn rand_vars(&self) -> &Vec<Vec<Vec<f64>>> {
+        &self.random_variables
+    }
+
+    /// seedを取得
+    pub fn get_seed(&self) -> Seed {
+        self.seed
+    }
+
+
+    /// Scenarioから乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, ScenarioError> {
+        Self::from_scenario_with(scenario, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario`]と同様だが，`options.deterministic`が`true`の場合は
+    /// 暗黙のエントロピーでseed値を生成せずにエラーを返す．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::GenOptions;
+    /// # use rand_scenario::mvnorm::{Scenario, RandomScenario};
+    /// let path = std::path::Path::new("test/test_scenario_mvnorm.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_multiple_with(&scenario, 4, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &GenOptions::default()).is_ok());
+    /// ```
+    pub fn from_scenario_with(scenario: &Scenario, options: &GenOptions) -> Result<Self, ScenarioError> {
+        Self::from_scenario_seed(scenario, piecewise::entropy_seed(options.deterministic)?)
+    }
+
+    /// Seedを指定してScenarioから乱数列を生成
+    ///
+    /// 生成の前に[`validate`]でシナリオを検証する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::mvnorm::{Scenario, RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario_mvnorm.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.rand_vars().len(), 40);
+    /// assert_eq!(randoms.rand_vars()[0].len(), 5);
+    /// assert_eq!(randoms.rand_vars()[0][0].len(), 2);
+    ///
+    /// // 正定値でない分散共分散行列はエラー
+    /// let singular = Scenario::parse_toml_str(r#"
+    /// n = 5
+    /// parameter = [{tau = 10, mean = [0.0, 0.0], cov = [[1.0, 1.0], [1.0, 1.0]]}]
+    /// "#).unwrap();
+    /// assert!(RandomScenario::from_scenario_seed(&singular, Seed(42)).is_err());
+    ///
+    /// // 対称でない分散共分散行列はエラー
+    /// let asymmetric = Scenario::parse_toml_str(r#"
+    /// n = 5
+    /// parameter = [{tau = 10, mean = [0.0, 0.0], cov = [[1.0, 0.5], [0.2, 1.0]]}]
+    /// "#).unwrap();
+    /// assert!(RandomScenario::from_scenario_seed(&asymmetric, Seed(42)).is_err());
+    /// ```
+    pub fn from_scenario_seed(scenario: &Scenario, seed: Seed) -> Result<Self, ScenarioError> {
+        validate(scenario)?;
+        let random_variables = Self::gen_random(&scenario, seed)?;
+        Ok(RandomScenario{ scenario: scenario.clone(), seed, random_variables })
+    }
+
+    // 乱数生成コア
+    fn gen_random(scenario: &Scenario, seed: Seed) -> Result<Vec<Vec<Vec<f64>>>, ScenarioError> {
+        let mut rng = Mt64::new(seed.0);
+        let segments = scenario.segments()?;
+        let n = scenario.n();
+        let p = scenario.dim();
+        let mut randoms = Vec::new();
+        for seg in segments.iter() {
+            for _t in 0..seg.len {
+                let row = (0..n).map(|_| {
+                    let z = super::rand_std_norm_n(&mut rng, p);
+                    (0..p).map(|i| seg.mean[i] + (0..=i).map(|k| seg.chol[i][k] * z[k]).sum::<f64>())
+                          .collect()
+                }).collect();
+                randoms.push(row);
+            }
+        }
+        Ok(randoms)
+    }
+
+    /// Scenarioから複数の乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    pub fn from_scenario_multiple(scenario: &Scenario, num: usize) -> Result<Vec<Self>, ScenarioError> {
+        Self::from_scenario_multiple_with(scenario, num, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_multiple`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    /// 使用例は[`RandomScenario::from_scenario_with`]を参照．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    pub fn from_scenario_multiple_with(scenario: &Scenario, num: usize, options: &GenOptions) -> Result<Vec<Self>, ScenarioError> {
+        piecewise::gen_multiple(num, options.deterministic, |seed| Self::from_scenario_seed(scenario, seed))
+    }
+
+
+    /// 乱数列をCSVとして出力
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::mvnorm::{Scenario, RandomScenario};
+    /// let path_scenario = std::path::Path::new("test/test_scenario_mvnorm.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_mvnorm.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv(&path_csv).unwrap();
+    /// ```
+    ///
+    /// # 注意: 出力されるCSVファイルの見方
+    ///
+    /// 行方向（横）に同一時点でのn個のベクトル観測値を平坦化して並べる．
+    /// すなわち，1行は$ x_{1,1}, \dots, x_{1,p}, x_{2,1}, \dots, x_{n,p} $の$ n \times p $列からなる
+    /// （$ x_{i,j} $はi番目のサンプルの第j成分）．
+    /// 列方向（縦）は，時系列の昇順に並んでいる．
+    pub fn to_csv<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        piecewise::write_csv_rows(path, self.rand_vars().iter().map(|rnds| rnds.iter().flatten().collect::<Vec<&f64>>()))
+    }
+}
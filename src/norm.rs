@@ -1,33 +1,212 @@
 //! 正規分布に従う乱数生成プログラム
-// use super::ScenarioError;
+use super::{GenOptions, ScenarioError, ScenarioErrorKind};
+// 管理図の監視統計量は欠測値（NaN）を除いて計算する
+use super::data::{sample_mean, sample_stddev};
 
 extern crate serde;
 use serde::{Serialize, Deserialize};
 use std::path::Path;
 use std::fs;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::str::FromStr;
+use std::fmt;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 extern crate toml;
+#[cfg(feature = "gzip")]
+use flate2::{write::GzEncoder, Compression};
+#[cfg(feature = "sobol")]
+use sobol::{Sobol, params::JoeKuoD6};
+#[cfg(feature = "parquet")]
+use arrow::{array::{ArrayRef, Float64Array, UInt64Array}, datatypes::{DataType, Field, Schema}, record_batch::RecordBatch};
+#[cfg(feature = "parquet")]
+use parquet::arrow::ArrowWriter;
 
 extern crate process_param;
-use process_param::{Process, ProcessSimulator};
+use process_param::Process;
 use process_param::norm::{Scenario, Parameter};
 
 
+// 管理図を併用した乱数生成で，最後の変化点以降の時点数として予め確保する容量
+const CONTROLCHART_TAIL_CAPACITY: usize = 64;
+
+
+// 管理図を併用した乱数生成で，制限時刻を過ぎていればエラーとする
+fn check_deadline(deadline: Option<Instant>) -> Result<(), ScenarioError> {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => Err(ScenarioError::new(
+            ScenarioErrorKind::Timeout,
+            "Control chart does not alart before the timeout."
+        )),
+        _ => Ok(()),
+    }
+}
+
+
+/// 制限時間を指定して管理図を併用した複数の乱数列を生成した結果
+///
+/// [`RandomScenario::from_scenario_controlchart_multiple_timeout`]が返す．
+///
+/// # 引数
+/// * `randoms` - 制限時間内に生成を終えた乱数列（seed値の生成順）
+/// * `timed_out` - 制限時間内に管理外れ状態を検出しなかった乱数列のseed値
+#[derive(Clone, Debug)]
+pub struct ControlChartBatch {
+    pub randoms: Vec<RandomScenario>,
+    pub timed_out: Vec<Seed>,
+}
+
+
 /// Seed値の型
-pub type Seed = u64;
+///
+/// 文字列からは10進数，または`0x`から始まる16進数（例：`0xDEADBEEF`）として読み取る．
+/// 表示は10進数で行い，serdeでは整数として扱う．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::norm::Seed;
+/// let seed: Seed = "0xDEADBEEF".parse().unwrap();
+/// assert_eq!(seed, Seed(3735928559));
+/// assert_eq!("3735928559".parse::<Seed>().unwrap(), seed);
+/// assert_eq!(seed.to_string(), "3735928559");
+/// assert!("-1".parse::<Seed>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Seed(pub u64);
+
+impl From<u64> for Seed {
+    fn from(seed: u64) -> Self {
+        Seed(seed)
+    }
+}
+
+impl From<Seed> for u64 {
+    fn from(seed: Seed) -> Self {
+        seed.0
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Seed {
+    type Err = ScenarioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => s.parse::<u64>(),
+        };
+        match parsed {
+            Ok(seed) => Ok(Seed(seed)),
+            Err(e) => Err(ScenarioError::new(ScenarioErrorKind::Parse, format!("Seed {s:?} doesn't fit u64: {e}")).with_source(e)),
+        }
+    }
+}
 
 /// シナリオから生成した乱数を格納
 ///
 /// # 引数
-/// * `scenario` - 乱数生成に利用したシナリオ
+/// * `scenario` - 乱数生成に利用したシナリオ．複数の乱数列を生成した場合は1つのシナリオを共有する．
 /// * `seed` - 乱数生成に利用したシード値
 /// * `random_variables` - 生成された乱数列
+/// * `signal` - 管理図を併用した場合に，管理外れ状態を検出した時点と規則
+/// * `contaminated` - 外れ値を混入させた場合に，外れ値とした観測値の位置`(時点, サンプル番号)`
+/// * `derived` - 生成した乱数列を加工（ダウンサンプリング等）して得た場合に`true`
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RandomScenario {
-    scenario: Scenario,
+    scenario: Arc<Scenario>,
     seed: Seed,
-    random_variables: Vec<Vec<<Parameter as Process>::Observation>>
+    random_variables: Vec<Vec<<Parameter as Process>::Observation>>,
+    #[serde(default)]
+    signal: Option<Signal>,
+    #[serde(default)]
+    contaminated: Vec<(usize, usize)>,
+    #[serde(default)]
+    derived: bool,
+}
+
+
+/// 管理図が管理外れ状態を検出したことを示すシグナル
+///
+/// # 引数
+/// * `index` - 管理外れ状態を検出した時点のインデックス（0始まり）
+/// * `rule` - 管理外れ状態の判定に用いた規則
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signal {
+    pub index: usize,
+    pub rule: SignalRule,
+}
+
+
+/// 管理外れ状態の判定規則
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignalRule {
+    /// $ \bar{X} $管理図またはs管理図の管理限界外の点（$ n = 1 $の場合はX管理図またはMR管理図の管理限界外の点）
+    Limits,
+    /// 連続する3点中2点が同じ側の2シグマ外（Western Electricルール2）
+    TwoOfThree,
+    /// 連続する5点中4点が同じ側の1シグマ外（Western Electricルール3）
+    FourOfFive,
+    /// 連続する8点が中心線の同じ側（Western Electricルール4）
+    EightInRow,
+    /// EWMA管理図の管理限界外の点
+    Ewma,
+    /// CUSUM管理図の決定区間超過
+    Cusum,
+}
+
+
+/// [`RandomScenario::verify_against_scenario_with`]で区間ごとに用いる統計量
+///
+/// 各観測値$ x_{t,i} $をその時点で真に有効なパラメータで標準化した$ z_{t,i} = (x_{t,i} - \mu_t) / \sigma_t $を用いる．
+/// 区間内の欠測値を除いた観測値の個数を$ m $とすると，いずれの統計量もシナリオと整合する場合に近似的に標準正規分布に従う．
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerifyStatistic {
+    /// 平均のずれ$ \sqrt{m} \, \bar{z} $
+    #[default]
+    Mean,
+    /// 分散の比のずれ$ (\overline{z^2} - 1) / \sqrt{2 / m} $
+    Variance,
+    /// `Mean`と`Variance`の両方
+    MeanAndVariance,
+}
+
+
+/// 管理図に適用するWestern Electricルール
+///
+/// 1点が3シグマの管理限界を外れる規則（ルール1）は常に適用され，
+/// ここで有効にした規則が$ \bar{X} $の系列に追加で適用される．
+/// 既定ではすべて無効であり，ルール1のみの3シグマの管理図となる．
+///
+/// # 注意
+/// ルール1は[`control_limits`]による$ \bar{X} - s $管理図の判定であり，
+/// 既定の規則では[`RandomScenario::from_scenario_seed_controlchart_sigma`]で`sigma_mult = 3.0`とした場合と同じ乱数列となる．
+/// 最尤推定値を`Scenario::out_of_control`で判定する[`RandomScenario::from_scenario_seed_controlchart`]とは一致しない．
+///
+/// # 引数
+/// * `two_of_three` - 連続する3点中2点が同じ側の2シグマ外
+/// * `four_of_five` - 連続する5点中4点が同じ側の1シグマ外
+/// * `eight_in_row` - 連続する8点が中心線の同じ側
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunsRules {
+    pub two_of_three: bool,
+    pub four_of_five: bool,
+    pub eight_in_row: bool,
+}
+
+impl RunsRules {
+    /// すべての規則を有効にする
+    pub fn all() -> Self {
+        RunsRules{ two_of_three: true, four_of_five: true, eight_in_row: true }
+    }
 }
 
 type RandValToml = Vec<Vec<f64>>;
@@ -40,378 +219,4628 @@ struct StrRandValToml {
 
 // TOML形式のRandomScenarioを読み取り・書き込みするための構造体
 // プログラム内で利用する乱数(RandomScenarioScenario)とは若干形式が異なるため別で定義
+// 異なるバージョンで出力したファイルも読めるよう，未知のフィールド（[meta]等）は無視する．
+// そのため`#[serde(deny_unknown_fields)]`は指定しないこと．
+// 他の分布のTOML形式も同様とする．また，後から追加したフィールドは古いファイルに無いため`#[serde(default)]`を指定する．
 #[derive(Debug, Serialize, Deserialize)]
 struct RandomScenarioToml {
     scenario: toml::value::Table,
     seed: String, // u64からだと整数型に変換できない可能性があるため文字列として記述
     random_variables: RandValToml,
+    #[serde(default)]
+    derived: bool, // 加工して得た乱数列の場合のみ出力する
 }
 
-extern crate rand;
-use rand::RngCore;
-extern crate rand_mt;
-use rand_mt::Mt64;
-extern crate rayon;
-use rayon::prelude::*;
-impl RandomScenario {
-    /// 乱数列を取得
-    pub fn rand_vars(&self) -> &Vec<Vec<<Parameter as Process>::Observation>> {
-        &self.random_variables
+// TOML形式の出力に付記する来歴情報（[meta]セクション）
+// 読み込み時には利用しない
+#[derive(Debug, Serialize)]
+struct MetaToml {
+    version: String,    // 出力したクレートのバージョン
+    created_at: String, // 出力日時（UTC，RFC 3339形式）
+    rng: String,        // 乱数生成器の名前
+    scenario_hash: String, // シナリオのTOML表現のハッシュ値（FNV-1a 64bit）
+}
+
+impl MetaToml {
+    fn new(scenario: &Scenario) -> Self {
+        MetaToml {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: utc_timestamp(std::time::SystemTime::now()),
+            rng: "Mt64".to_string(),
+            scenario_hash: format!("{:016x}", fnv1a_64(scenario.to_toml_string().as_bytes())),
+        }
     }
+}
 
-    /// seedを取得
-    pub fn get_seed(&self) -> Seed {
-        self.seed
+// FNV-1a（64bit）によるハッシュ値
+// 実行環境やRustのバージョンによらず同じ値となる
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+// 時刻をUTCのRFC 3339形式の文字列に変換
+// 日付の計算はグレゴリオ暦のcivil_from_daysアルゴリズムによる
+fn utc_timestamp(time: std::time::SystemTime) -> String {
+    let secs = time.duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+    let (days, sod) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, sod / 3600, sod % 3600 / 60, sod % 60)
+}
+
+// シナリオの構造（サンプル・サイズと変化点）をTOML表現から読み取るための構造体
+// process_param::norm::Scenarioは変化点を公開していないため，TOML形式を経由して取得する
+#[derive(Debug, Deserialize)]
+struct ScenarioLayout {
+    n: i64,
+    parameter: Vec<ChangePointLayout>,
+}
+
+// シナリオ中の各変化点
+#[derive(Debug, Deserialize)]
+struct ChangePointLayout {
+    tau: i64,
+    sigma2: toml::value::Table,
+}
+
+impl ScenarioLayout {
+    fn from_scenario(scenario: &Scenario) -> Result<Self, process_param::ScenarioError> {
+        match toml::from_str(&scenario.to_toml_string()) {
+            Ok(layout) => Ok(layout),
+            Err(e) => Err(process_param::ScenarioError{
+                message: format!("Scenario layout cannot be read: {e}")
+            }),
+        }
     }
 
-    /// 最初のパラメータを取得
-    ///
-    /// サンプル自体が従うパラメータを取得する．
-    ///
-    /// # 返り値
-    /// * `param_0` - 最初の状態における正規分布のパラメータ
-    pub fn get_init_param(&self) -> Parameter {
-        let (mu, sigma2) = self.scenario.param_in_control();
-        Parameter::new(mu, sigma2).unwrap()
+    // 各パラメータ区間の終端時点
+    fn taus(&self) -> Vec<i64> {
+        self.parameter.iter().map(|cp| cp.tau).collect()
     }
+}
 
-    /// サンプル平均の従う最初のパラメータを取得
-    ///
-    /// 正規分布には再生性があり，サンプルの平均値も正規分布に従う．
-    /// 乱数生成の最初の状態において，サンプル平均が従う正規分布のパラメータを取得する．
-    ///
-    /// # 返り値
-    /// * `param_barx0` - 最初の状態でサンプル平均が従う正規分布のパラメータ
-    pub fn get_sm_init_param(&self) -> Parameter {
-        let (mu, sigma2) = self.scenario.param_samplemean();
-        Parameter::new(mu, sigma2).unwrap()
+
+// シナリオのパラメータ区間の配列を`edit`で編集したシナリオを作成
+fn edit_scenario_parameter<F: FnOnce(&mut Vec<toml::Value>)>(scenario: &Scenario, edit: F) -> Result<Scenario, ScenarioError> {
+    let mut value: toml::Value = toml::from_str(&scenario.to_toml_string()).map_err(|e| ScenarioError::new(
+        ScenarioErrorKind::Parse,
+        format!("Scenario layout cannot be read: {e}")
+    ).with_source(e))?;
+    if let Some(parameter) = value.get_mut("parameter").and_then(|p| p.as_array_mut()) {
+        edit(parameter);
     }
+    let toml_str = toml::to_string(&value).map_err(|e| ScenarioError::new(
+        ScenarioErrorKind::Parse,
+        format!("Scenario cannot be converted to TOML: {e}")
+    ).with_source(e))?;
+    Scenario::parse_toml_str(&toml_str).map_err(|e| ScenarioError::new(ScenarioErrorKind::Parse, format!("Invalid scenario: {e}")))
+}
 
 
-    /// Scenarioから乱数列を生成
-    ///
-    /// # 引数
-    /// * `scenario` - 乱数生成に用いるシナリオ
-    /// 
-    /// # 使用例
-    /// ```
-    /// extern crate process_param;
-    /// use process_param::norm::Scenario;
-    /// # use rand_scenario::norm::RandomScenario;
-    /// let path = std::path::Path::new("test/test_scenario.toml");
-    /// let scenario = Scenario::from_toml(&path).unwrap();
-    /// let randoms = RandomScenario::from_scenario(&scenario);
-    /// println!("{:?}", randoms);
-    /// ```
-    pub fn from_scenario(scenario: &Scenario) -> Result<Self, process_param::ScenarioError> {
-        let seed = rand::thread_rng().next_u64();
-        Self::from_scenario_seed(scenario, seed)
+// 各パラメータ区間の終端`tau`を`map(区間番号, tau)`で置き換えたシナリオを作成
+fn map_scenario_taus<F: Fn(usize, i64) -> i64>(scenario: &Scenario, map: F) -> Result<Scenario, ScenarioError> {
+    edit_scenario_parameter(scenario, |parameter| {
+        for (i, cp) in parameter.iter_mut().enumerate() {
+            if let Some(tau) = cp.get("tau").and_then(|t| t.as_integer()) {
+                cp["tau"] = toml::Value::Integer(map(i, tau));
+            }
+        }
+    })
+}
+
+
+/// シナリオにおける真の変化点を取得
+///
+/// 各変化点は，パラメータが変化した直後の時点のインデックス（0始まり）で表す．
+/// すなわち，乱数列の`random_variables[tau]`が新しいパラメータに従う最初の行となる．
+/// 最後のパラメータ区間の終端はシナリオの終端であり，変化点には含めない．
+///
+/// # 引数
+/// * `scenario` - 変化点を取得するシナリオ
+pub fn change_points(scenario: &Scenario) -> Result<Vec<usize>, process_param::ScenarioError> {
+    let layout = ScenarioLayout::from_scenario(scenario)?;
+    let taus = layout.taus();
+    let num_cp = taus.len().saturating_sub(1);
+    taus[..num_cp].iter()
+                  .map(|tau| match usize::try_from(*tau) {
+                      Ok(t) => Ok(t),
+                      Err(_) => Err(process_param::ScenarioError{
+                          message: format!("Change point {tau} doesn't convert to usize.")
+                      }),
+                  })
+                  .collect()
+}
+
+
+/// シナリオの妥当性を検証
+///
+/// 乱数生成の前に以下の項目を確認し，不正な場合はその内容を示すエラーを返す．
+/// * サンプル・サイズ$ n $が正であること
+/// * パラメータの区間が1つ以上存在すること
+/// * 変化点が正かつ狭義単調増加であること（すべての変化点がシナリオの終端より前にあること）
+/// * 分散の水準（`level`および`init`）が正であること
+/// * 展開したすべての時点で分散が正であること
+/// * 展開したパラメータ系列の長さ（[`scenario_horizon`](crate::scenario_horizon)）が最後の区間の終端と一致すること
+///
+/// # 引数
+/// * `scenario` - 検証するシナリオ
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::validate;
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// assert!(validate(&scenario).is_ok());
+///
+/// // サンプル・サイズが0
+/// let zero_n = Scenario::parse_toml_str(r#"
+/// n = 0
+/// parameter = [{tau = 10, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}}]
+/// "#).unwrap();
+/// assert!(validate(&zero_n).is_err());
+///
+/// // 変化点が単調増加でない
+/// let not_monotonic = Scenario::parse_toml_str(r#"
+/// n = 5
+/// parameter = [
+///     {tau = 20, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}},
+///     {tau = 10, mu = {type = "Step", level = 1.0}, sigma2 = {type = "Step", level = 1.0}}
+/// ]
+/// "#).unwrap();
+/// assert!(validate(&not_monotonic).is_err());
+///
+/// // 変化点がシナリオの終端と一致
+/// let beyond_horizon = Scenario::parse_toml_str(r#"
+/// n = 5
+/// parameter = [
+///     {tau = 10, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}},
+///     {tau = 10, mu = {type = "Step", level = 1.0}, sigma2 = {type = "Step", level = 1.0}}
+/// ]
+/// "#).unwrap();
+/// assert!(validate(&beyond_horizon).is_err());
+///
+/// // 分散が負
+/// let negative_var = Scenario::parse_toml_str(r#"
+/// n = 5
+/// parameter = [{tau = 10, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = -1.0}}]
+/// "#).unwrap();
+/// assert!(validate(&negative_var).is_err());
+/// ```
+pub fn validate(scenario: &Scenario) -> Result<(), ScenarioError> {
+    let layout = ScenarioLayout::from_scenario(scenario)?;
+    if layout.n <= 0 {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Sample size n must be positive, but n = {}.", layout.n)));
+    }
+    if layout.parameter.is_empty() {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Scenario has no parameter."));
     }
 
-    /// Seedを指定してScenarioから乱数列を生成
+    // 変化点
+    let taus = layout.taus();
+    if taus[0] <= 0 {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Change point must be positive, but tau = {}.", taus[0])));
+    }
+    for (i, w) in taus.windows(2).enumerate() {
+        if w[0] >= w[1] {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Change points must be strictly increasing, but tau_{} = {} and tau_{} = {} (horizon = {}).",
+                                 i + 1, w[0], i + 2, w[1], taus[taus.len() - 1])
+            ));
+        }
+    }
+
+    // 分散
+    for (i, cp) in layout.parameter.iter().enumerate() {
+        for key in ["level", "init"] {
+            if let Some(v) = cp.sigma2.get(key).and_then(|v| v.as_float()) {
+                if v <= 0.0 {
+                    return Err(ScenarioError::new(
+                        ScenarioErrorKind::OutOfRange,
+                        format!("Variance must be positive, but sigma2.{key} = {v} in parameter {}.", i + 1)
+                    ));
+                }
+            }
+        }
+    }
+    // 展開したパラメータ系列の長さ（時点数）と最後の区間の終端
+    let horizon = super::scenario_horizon(scenario)?;
+    if i64::try_from(horizon) != Ok(taus[taus.len() - 1]) {
+        return Err(ScenarioError::new(
+            ScenarioErrorKind::OutOfRange,
+            format!("Horizon {} doesn't match the end of the last parameter tau = {}.", horizon, taus[taus.len() - 1])
+        ));
+    }
+
+    Ok(())
+}
+
+
+/// JSON形式の文字列からシナリオを読み込み
+///
+/// # 引数
+/// * `json` - 乱数生成のシナリオを記述したJSON形式の文字列
+///
+/// # 注意
+/// キーと値の構造はTOML形式のシナリオと同じです．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::norm::parse_json_str;
+/// # use process_param::norm::Scenario;
+/// let scenario = parse_json_str(r#"{
+///     "n": 10,
+///     "parameter": [
+///         {"tau": 15, "mu": {"type": "Step", "level": 0.0}, "sigma2": {"type": "Step", "level": 1.0}},
+///         {"tau": 30, "mu": {"type": "Linear", "grad": 0.1}, "sigma2": {"type": "Step", "level": 2.0}}
+///     ]
+/// }"#).unwrap();
+/// let from_toml = Scenario::parse_toml_str(r#"
+/// n = 10
+/// parameter = [
+///     {tau = 15, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}},
+///     {tau = 30, mu = {type = "Linear", grad = 0.1}, sigma2 = {type = "Step", level = 2.0}}
+/// ]
+/// "#).unwrap();
+/// assert_eq!(scenario.to_toml_string(), from_toml.to_toml_string());
+/// ```
+pub fn parse_json_str(json: &str) -> Result<Scenario, ScenarioError> {
+    // JSONの値をTOMLの値に変換し，TOML形式のシナリオとして読み込む
+    let value: toml::Value = serde_json::from_str(json).map_err(|e| ScenarioError::new(
+        ScenarioErrorKind::Parse,
+        format!("Invalid JSON scenario: {e}")
+    ).with_source(e))?;
+    scenario_from_value(&value)
+}
+
+
+/// YAML形式の文字列からシナリオを読み込み
+///
+/// # 引数
+/// * `yaml` - 乱数生成のシナリオを記述したYAML形式の文字列
+///
+/// # 注意
+/// キーと値の構造はTOML形式のシナリオと同じです．
+/// `yaml` featureを有効にする必要があります．
+///
+/// # 使用例
+/// ```
+/// # #[cfg(feature = "yaml")] {
+/// # use rand_scenario::norm::{parse_yaml_str, RandomScenario, Seed};
+/// # use process_param::norm::Scenario;
+/// let scenario = parse_yaml_str(&std::fs::read_to_string("test/test_scenario.yaml").unwrap()).unwrap();
+/// let from_toml = Scenario::from_toml(&std::path::Path::new("test/test_scenario.toml")).unwrap();
+/// assert_eq!(scenario.to_toml_string(), from_toml.to_toml_string());
+/// // 同じseed値からは同じ乱数列が得られる
+/// assert_eq!(RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap().rand_vars(),
+///            RandomScenario::from_scenario_seed(&from_toml, Seed(42)).unwrap().rand_vars());
+/// # }
+/// ```
+#[cfg(feature = "yaml")]
+pub fn parse_yaml_str(yaml: &str) -> Result<Scenario, ScenarioError> {
+    let value: toml::Value = serde_yaml::from_str(yaml).map_err(|e| ScenarioError::new(
+        ScenarioErrorKind::Parse,
+        format!("Invalid YAML scenario: {e}")
+    ).with_source(e))?;
+    scenario_from_value(&value)
+}
+
+
+// TOMLの値に変換したシナリオを，TOML形式のシナリオとして読み込む
+fn scenario_from_value(value: &toml::Value) -> Result<Scenario, ScenarioError> {
+    let toml_str = toml::to_string(value).map_err(|e| ScenarioError::new(
+        ScenarioErrorKind::Parse,
+        format!("Scenario cannot be converted to TOML: {e}")
+    ).with_source(e))?;
+    Scenario::parse_toml_str(&toml_str).map_err(|e| ScenarioError::new(ScenarioErrorKind::Parse, format!("Invalid scenario: {e}")))
+}
+
+
+/// ファイルからシナリオを読み込み
+///
+/// # 引数
+/// * `path` - 乱数生成のシナリオが記述されたファイルのパス
+///
+/// # 注意
+/// 拡張子が`.toml`の場合はTOML形式，`.json`の場合はJSON形式，
+/// `.yaml`または`.yml`の場合はYAML形式（`yaml` featureが必要）として読み込みます．
+/// それ以外の拡張子の場合はエラーを返します．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::norm::load_scenario;
+/// # use std::path::Path;
+/// let scenario = load_scenario(&Path::new("test/test_scenario.toml")).unwrap();
+/// assert!(rand_scenario::norm::validate(&scenario).is_ok());
+/// assert!(load_scenario(&Path::new("test/test_scenario.txt")).is_err());
+/// ```
+pub fn load_scenario<P: AsRef<Path>>(path: &P) -> Result<Scenario, Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => Ok(Scenario::from_toml(&path)?),
+        Some("json") => Ok(parse_json_str(&fs::read_to_string(path)?)?),
+        Some("yaml") | Some("yml") => load_yaml(path),
+        _ => Err(Box::new(ScenarioError::new(
+            ScenarioErrorKind::Parse,
+            format!("Unrecognized scenario file extension: {:?} (expected .toml, .json, .yaml or .yml).", path)
+        ))),
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn load_yaml(path: &Path) -> Result<Scenario, Box<dyn std::error::Error>> {
+    Ok(parse_yaml_str(&fs::read_to_string(path)?)?)
+}
+
+#[cfg(not(feature = "yaml"))]
+fn load_yaml(_path: &Path) -> Result<Scenario, Box<dyn std::error::Error>> {
+    Err(Box::new(ScenarioError::new(ScenarioErrorKind::Other, "YAML scenarios require the `yaml` feature.")))
+}
+
+
+/// シナリオのファイル形式を変換（TOML ⇄ JSON）
+///
+/// # 引数
+/// * `input` - 変換元のシナリオのファイルのパス
+/// * `output` - 変換先のファイルのパス
+///
+/// # 注意
+/// 入出力の形式はそれぞれの拡張子（`.toml`または`.json`）から判断します．
+/// 変換は一度シナリオとして読み込んでから行うため，不正なシナリオはエラーとなります．
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::norm::{convert_scenario, load_scenario};
+/// # use std::path::Path;
+/// let path_toml = Path::new("test/test_scenario.toml");
+/// let path_json = Path::new("test/convert_scenario.json");
+/// let path_again = Path::new("test/convert_scenario.toml");
+/// convert_scenario(&path_toml, &path_json).unwrap();
+/// convert_scenario(&path_json, &path_again).unwrap();
+/// // TOML → JSON → TOMLで同じシナリオとなる
+/// let original = load_scenario(&path_toml).unwrap();
+/// assert_eq!(load_scenario(&path_json).unwrap().to_toml_string(), original.to_toml_string());
+/// assert_eq!(load_scenario(&path_again).unwrap().to_toml_string(), original.to_toml_string());
+/// ```
+pub fn convert_scenario<P: AsRef<Path>, Q: AsRef<Path>>(input: &P, output: &Q) -> Result<(), Box<dyn std::error::Error>> {
+    let scenario = load_scenario(input)?;
+    let output = output.as_ref();
+    let contents = match output.extension().and_then(|e| e.to_str()) {
+        Some("toml") => scenario.to_toml_string(),
+        Some("json") => {
+            let value: toml::Value = toml::from_str(&scenario.to_toml_string())?;
+            serde_json::to_string_pretty(&value)?
+        },
+        _ => return Err(Box::new(ScenarioError::new(
+            ScenarioErrorKind::Parse,
+            format!("Unrecognized scenario file extension: {:?} (expected .toml or .json).", output)
+        ))),
+    };
+    fs::write(output, contents)?;
+    Ok(())
+}
+
+
+/// [`RandomScenario::plot_png`]の描画設定
+///
+/// # 引数
+/// * `width`, `height` - 画像の幅と高さ（ピクセル）
+/// * `sigma_mult` - 描画する管理限界の幅（シグマの倍数）
+#[cfg(feature = "plot")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlotOptions {
+    pub width: u32,
+    pub height: u32,
+    pub sigma_mult: f64,
+}
+
+#[cfg(feature = "plot")]
+impl Default for PlotOptions {
+    fn default() -> Self {
+        PlotOptions{ width: 800, height: 480, sigma_mult: 3.0 }
+    }
+}
+
+
+/// 乱数列の要約統計量
+///
+/// # 引数
+/// * `mean` - 全観測値の平均
+/// * `variance` - 全観測値の不偏分散
+/// * `min`, `max` - 全観測値の最小値・最大値
+/// * `num_timepoints` - 時点数
+/// * `n` - サンプル・サイズ（最初の時点のもの）
+/// * `run_length` - 管理図を併用した場合に，管理外れ状態を検出するまでの時点数
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    pub mean: f64,
+    pub variance: f64,
+    pub min: f64,
+    pub max: f64,
+    pub num_timepoints: usize,
+    pub n: usize,
+    pub run_length: Option<usize>,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        writeln!(f, "{:<16}{:>16.6}", "mean", self.mean)?;
+        writeln!(f, "{:<16}{:>16.6}", "variance", self.variance)?;
+        writeln!(f, "{:<16}{:>16.6}", "min", self.min)?;
+        writeln!(f, "{:<16}{:>16.6}", "max", self.max)?;
+        writeln!(f, "{:<16}{:>16}", "num_timepoints", self.num_timepoints)?;
+        writeln!(f, "{:<16}{:>16}", "n", self.n)?;
+        match self.run_length {
+            Some(rl) => write!(f, "{:<16}{:>16}", "run_length", rl),
+            None => write!(f, "{:<16}{:>16}", "run_length", "-"),
+        }
+    }
+}
+
+
+/// ヒストグラムを文字列のスパークラインで表示
+///
+/// [`RandomScenario::histogram`]の各階級の度数を，最大の度数に対する割合に応じて`▁`から`█`の8段階の文字で表す．
+///
+/// # 引数
+/// * `histogram` - 階級の中央値と度数の組
+///
+/// # 使用例
+/// ```
+/// # use rand_scenario::norm::sparkline;
+/// assert_eq!(sparkline(&[(0.0, 0), (1.0, 4), (2.0, 8)]), "▁▅█");
+/// ```
+pub fn sparkline(histogram: &[(f64, usize)]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = histogram.iter().map(|(_, c)| *c).max().unwrap_or(0).max(1);
+    histogram.iter()
+             .map(|(_, c)| BARS[(c * (BARS.len() - 1) + max / 2) / max])
+             .collect()
+}
+
+
+/// 平均に重ねる季節変動（正弦波）
+///
+/// 時点$ t $（0始まり）の平均に$ A \sin(2 \pi t / T + \phi) $を加える．
+///
+/// # 引数
+/// * `amplitude` - 振幅$ A $
+/// * `period` - 周期$ T $（時点数，正の値）
+/// * `phase` - 位相$ \phi $（ラジアン）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Seasonal {
+    pub amplitude: f64,
+    pub period: f64,
+    pub phase: f64,
+}
+
+impl Seasonal {
+    /// 時点`t`（0始まり）における季節変動の値
+    pub fn value(&self, t: usize) -> f64 {
+        self.amplitude * (2.0 * std::f64::consts::PI * t as f64 / self.period + self.phase).sin()
+    }
+}
+
+
+/// 2成分の混合正規分布における1区間のパラメータ
+///
+/// 各観測値は確率$ 1 - w $で`first`，確率$ w $で`second`の正規分布に従う．
+///
+/// # 引数
+/// * `first` - 1番目の成分の正規分布
+/// * `second` - 2番目の成分の正規分布
+/// * `weight` - 2番目の成分を選ぶ確率$ w $（0以上1以下）
+#[derive(Clone, Debug)]
+pub struct MixtureSegment {
+    pub first: Parameter,
+    pub second: Parameter,
+    pub weight: f64,
+}
+
+impl MixtureSegment {
+    /// 混合分布としての(平均, 分散)を計算
+    ///
+    /// 平均は$ \mu = (1 - w) \mu_1 + w \mu_2 $，
+    /// 分散は$ \sigma^2 = (1 - w) \sigma_1^2 + w \sigma_2^2 + w (1 - w) (\mu_1 - \mu_2)^2 $となる．
     ///
-    /// # 引数
-    /// * `scenario` - 乱数生成に用いるシナリオ
-    /// * `seed` - 乱数生成に用いるseed値
-    /// 
     /// # 使用例
     /// ```
-    /// extern crate process_param;
-    /// use process_param::norm::Scenario;
-    /// # use rand_scenario::norm::RandomScenario;
-    /// let path = std::path::Path::new("test/test_scenario.toml");
-    /// let scenario = Scenario::from_toml(&path).unwrap();
-    /// let randoms = RandomScenario::from_scenario_seed(&scenario, 42).unwrap();
-    /// println!("{:?}", randoms);
+    /// # use rand_scenario::norm::MixtureSegment;
+    /// use process_param::norm::Parameter;
+    /// let segment = MixtureSegment{
+    ///     first: Parameter::new(0.0, 1.0).unwrap(),
+    ///     second: Parameter::new(4.0, 1.0).unwrap(),
+    ///     weight: 0.25,
+    /// };
+    /// assert_eq!(segment.effective_param(), (1.0, 1.0 + 0.25 * 0.75 * 16.0));
     /// ```
-    pub fn from_scenario_seed(scenario: &Scenario, seed: Seed) -> Result<Self, process_param::ScenarioError> {
-        let random_variables = Self::gen_random(&scenario, seed)?;
-        Ok(RandomScenario{ scenario: scenario.clone(), seed, random_variables })
+    pub fn effective_param(&self) -> (f64, f64) {
+        let (mu_1, sigma2_1) = param_tuple(&self.first);
+        let (mu_2, sigma2_2) = param_tuple(&self.second);
+        let w = self.weight;
+        let mu = (1.0 - w) * mu_1 + w * mu_2;
+        let sigma2 = (1.0 - w) * sigma2_1 + w * sigma2_2 + w * (1.0 - w) * (mu_1 - mu_2).powi(2);
+        (mu, sigma2)
     }
+}
 
-    // 乱数生成コア
-    fn gen_random(scenario: &Scenario, seed: Seed) -> Result<Vec<Vec<<Parameter as Process>::Observation>>, process_param::ScenarioError> {
-        let mut rng = Mt64::new(seed);
-        let dec_param = scenario.decomplession()?;
-        let n = match usize::try_from(scenario.n()){
-            Ok(val) => val,
-            Err(_) => return Err(process_param::ScenarioError{
-                message: "Sample size n doesn't convert to usize.".to_string()
-            }),
-        };
-        Ok(dec_param.iter()
-                    .map(|parameter| Parameter::rand_with_n(parameter, &mut rng, n))
-                    .collect())
+
+/// $ \bar{X} - s $管理図の管理限界
+///
+/// # 引数
+/// * `lcl_xbar`, `ucl_xbar` - $ \bar{X} $管理図の下方・上方管理限界
+/// * `lcl_s`, `ucl_s` - s管理図の下方・上方管理限界
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControlLimits {
+    pub lcl_xbar: f64,
+    pub ucl_xbar: f64,
+    pub lcl_s: f64,
+    pub ucl_s: f64,
+}
+
+
+/// 管理限界の幅を指定して$ \bar{X} - s $管理図の管理限界を計算
+///
+/// シナリオの3シグマ管理限界（`Scenario::control_limit_xbar`および`Scenario::control_limit_s`）を，
+/// 中心線からの幅が`sigma_mult`シグマとなるように拡大・縮小する．
+/// s管理図の中心線は$ c_4 \sigma_0 $であり，下方管理限界は0未満にならない．
+/// `sigma_mult = 3.0`のときシナリオの管理限界と一致する．
+///
+/// # 引数
+/// * `scenario` - 管理限界を計算するシナリオ
+/// * `sigma_mult` - 管理限界の幅（シグマの倍数）
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::control_limits;
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let cl_3 = control_limits(&scenario, 3.0).unwrap();
+/// assert_eq!((cl_3.lcl_xbar, cl_3.ucl_xbar), scenario.control_limit_xbar());
+/// let cl_2 = control_limits(&scenario, 2.0).unwrap();
+/// assert!(cl_3.lcl_xbar < cl_2.lcl_xbar && cl_2.ucl_xbar < cl_3.ucl_xbar);
+/// ```
+pub fn control_limits(scenario: &Scenario, sigma_mult: f64) -> Result<ControlLimits, ScenarioError> {
+    if sigma_mult.is_nan() || sigma_mult <= 0.0 {
+        return Err(ScenarioError::new(
+            ScenarioErrorKind::OutOfRange,
+            format!("Sigma multiplier must be positive, but sigma_mult = {sigma_mult}.")
+        ));
     }
+    let ratio = sigma_mult / 3.0;
+    let (lcl_xbar, ucl_xbar) = scenario.control_limit_xbar();
+    let center_xbar = (lcl_xbar + ucl_xbar) / 2.0;
+    let half_xbar = (ucl_xbar - lcl_xbar) / 2.0 * ratio;
 
-    /// Scenarioから複数の乱数列を生成
+    let (lcl_s, ucl_s) = scenario.control_limit_s();
+    let (_mu_0, sigma2_0) = scenario.param_in_control();
+    let center_s = c4(scenario.n_as_usize()?) * sigma2_0.sqrt();
+    let half_s = (ucl_s - center_s) * ratio;
+    Ok(ControlLimits{
+        lcl_xbar: center_xbar - half_xbar,
+        ucl_xbar: center_xbar + half_xbar,
+        lcl_s: (center_s - half_s).max(0.0),
+        ucl_s: center_s + half_s,
+    })
+}
+
+
+// 不偏標準偏差の補正係数 c4 = √(2/(n-1)) Γ(n/2) / Γ((n-1)/2)
+// r(n) = Γ(n/2) / Γ((n-1)/2) は r(2) = 1/√π, r(n+1) = (n-1) / (2 r(n)) を満たす
+fn c4(n: usize) -> f64 {
+    if n < 2 {
+        return f64::NAN;
+    }
+    let mut r = 1.0 / std::f64::consts::PI.sqrt();
+    for m in 2..n {
+        r = (m - 1) as f64 / (2.0 * r);
+    }
+    (2.0 / (n - 1) as f64).sqrt() * r
+}
+
+
+// 正規分布のパラメータを(μ, σ^2)の組に変換
+fn param_tuple(parameter: &Parameter) -> (f64, f64) {
+    (parameter.mu(), parameter.sigma2())
+}
+
+
+// 正規乱数をn個生成
+// 標準正規乱数は[`crate::uniform_stream`]と同じ一様乱数からBox-Muller法で生成する
+fn rand_norm_n<R: RngCore + ?Sized>(parameter: &Parameter, rng: &mut R, n: usize) -> Vec<f64> {
+    let (mu, sigma2) = param_tuple(parameter);
+    let sigma = sigma2.sqrt();
+    super::rand_std_norm_n(rng, n).into_iter().map(|z| mu + sigma * z).collect()
+}
+
+
+// 各区間の平均を次の区間の先頭の値まで線形に補間したパラメータ系列
+fn drift_params(scenario: &Scenario) -> Result<Vec<Parameter>, process_param::ScenarioError> {
+    let dec_param = scenario.decomplession()?;
+    let cps = change_points(scenario)?;
+    let starts: Vec<usize> = std::iter::once(0).chain(cps.iter().cloned()).collect();
+    let mut params = dec_param.clone();
+    for w in starts.windows(2) {
+        let (start, end) = (w[0], w[1]);
+        let (mu_start, _) = param_tuple(&dec_param[start]);
+        let (mu_end, _) = param_tuple(&dec_param[end]);
+        for t in start..end {
+            let (_, sigma2) = param_tuple(&dec_param[t]);
+            let mu = mu_start + (mu_end - mu_start) * (t - start) as f64 / (end - start) as f64;
+            params[t] = Parameter::new(mu, sigma2)?;
+        }
+    }
+    Ok(params)
+}
+
+
+// Sobol列の2次元分の点を一様乱数として順に返す乱数生成器
+#[cfg(feature = "sobol")]
+struct SobolRng {
+    points: Sobol<f64>,
+    dim: usize,
+    buffer: Option<f64>,
+}
+
+#[cfg(feature = "sobol")]
+impl SobolRng {
+    fn new(dimension_offset: usize) -> Self {
+        let dim = 2 * dimension_offset;
+        let mut points = Sobol::<f64>::new(dim + 2, &JoeKuoD6::standard());
+        // 最初の点は原点のため除く
+        points.next();
+        SobolRng{ points, dim, buffer: None }
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        match self.buffer.take() {
+            Some(u) => u,
+            None => {
+                let point = self.points.next().expect("Sobol sequence is exhausted.");
+                self.buffer = Some(point[self.dim + 1]);
+                point[self.dim]
+            },
+        }
+    }
+}
+
+#[cfg(feature = "sobol")]
+impl RngCore for SobolRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        // [0, 1)の値を上位ビットに割り当てる
+        (self.next_f64() * 18446744073709551616.0) as u64
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+
+// MATLABの変数名として有効か（英字で始まり，英数字と`_`のみからなる`namelengthmax`（63）文字以内）
+#[cfg(feature = "mat")]
+fn is_matlab_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() => (),
+        _ => return false,
+    }
+    name.len() <= 63 && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+
+// MAT-fileのデータ要素（型，バイト数，8バイト境界までの埋め草付きのデータ）を追加
+#[cfg(feature = "mat")]
+fn mat_element(buf: &mut Vec<u8>, data_type: u32, data: &[u8]) {
+    buf.extend_from_slice(&data_type.to_le_bytes());
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+    buf.resize(buf.len() + (8 - data.len() % 8) % 8, 0);
+}
+
+
+// 各時点のパラメータから，乱数列を1時点（1行）ずつ生成する
+// 乱数生成器の状態のみを保持し，生成した行は保持しない
+struct RowGenerator<'a, R: RngCore> {
+    params: std::slice::Iter<'a, Parameter>,
+    rng: R,
+    n: usize,
+}
+
+impl<'a, R: RngCore> RowGenerator<'a, R> {
+    fn new(scenario: &Scenario, dec_param: &'a [Parameter], rng: R) -> Result<Self, process_param::ScenarioError> {
+        let n = match usize::try_from(scenario.n()){
+            Ok(val) => val,
+            Err(_) => return Err(process_param::ScenarioError{
+                message: "Sample size n doesn't convert to usize.".to_string()
+            }),
+        };
+        Ok(RowGenerator{ params: dec_param.iter(), rng, n })
+    }
+}
+
+impl<'a, R: RngCore> Iterator for RowGenerator<'a, R> {
+    type Item = Vec<<Parameter as Process>::Observation>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parameter = self.params.next()?;
+        Some(rand_norm_n(parameter, &mut self.rng, self.n))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.params.size_hint()
+    }
+}
+
+
+/// CSVに出力する値の精度
+///
+/// [`RandomScenario::to_csv_as`]で出力する値の型として用いる．
+/// `f64`（既定の精度）と`f32`に実装している．
+pub trait Precision: Serialize + Copy {
+    /// f64の値をこの型に変換
+    fn from_f64(x: f64) -> Self;
+}
+
+impl Precision for f64 {
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+}
+
+impl Precision for f32 {
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+}
+
+
+// CSVの1行分のレコード．欠測値（NaN）は空欄として出力する
+fn csv_record(rands: &[f64]) -> Vec<Option<f64>> {
+    csv_record_with(rands, |r| r)
+}
+
+// 各値を`format`で変換したCSVの1行分のレコード．欠測値（NaN）は`format`を通さず空欄として出力する
+fn csv_record_with<T, F: Fn(f64) -> T>(rands: &[f64], format: F) -> Vec<Option<T>> {
+    rands.iter().map(|r| if r.is_nan() { None } else { Some(format(*r)) }).collect()
+}
+
+// CSVの1項目分の文字列．欠測値（NaN）は空欄として出力する
+fn csv_field(rand: &f64) -> String {
+    if rand.is_nan() { String::new() } else { rand.to_string() }
+}
+
+
+// 管理図の監視統計量
+trait ControlChart {
+    // 監視統計量を初期状態に戻す
+    fn reset(&mut self);
+    // 1時点分のサンプルで監視統計量を更新し，管理外れ状態ならば判定した規則を返す
+    fn update(&mut self, rands: &[f64]) -> Option<SignalRule>;
+}
+
+
+// サンプル平均のEWMA管理図
+struct Ewma {
+    lambda: f64,
+    mu_0: f64,
+    sigma_xbar: f64,
+    z: f64,
+    t: i32,
+}
+
+impl Ewma {
+    fn new(scenario: &Scenario, lambda: f64) -> Result<Self, process_param::ScenarioError> {
+        if !(lambda > 0.0 && lambda <= 1.0) {
+            return Err(process_param::ScenarioError{
+                message: format!("Smoothing parameter lambda must satisfy 0 < lambda <= 1, but lambda = {lambda}.")
+            });
+        }
+        let (mu_0, sigma2_0) = scenario.param_in_control();
+        let n = scenario.n_as_usize()?;
+        Ok(Ewma{ lambda, mu_0, sigma_xbar: (sigma2_0 / n as f64).sqrt(), z: mu_0, t: 0 })
+    }
+
+    // 時点tにおける管理限界の半幅
+    fn half_width(&self, t: i32) -> f64 {
+        let l = self.lambda;
+        3.0 * self.sigma_xbar * (l / (2.0 - l) * (1.0 - (1.0 - l).powi(2 * t))).sqrt()
+    }
+
+    // サンプル平均でEWMAを更新
+    fn step(&mut self, xbar: f64) {
+        self.t += 1;
+        self.z = self.lambda * xbar + (1.0 - self.lambda) * self.z;
+    }
+}
+
+impl ControlChart for Ewma {
+    fn reset(&mut self) {
+        self.z = self.mu_0;
+        self.t = 0;
+    }
+
+    fn update(&mut self, rands: &[f64]) -> Option<SignalRule> {
+        self.step(sample_mean(rands));
+        ((self.z - self.mu_0).abs() > self.half_width(self.t)).then_some(SignalRule::Ewma)
+    }
+}
+
+
+// 管理限界の幅を指定したX̄-s管理図
+struct Shewhart {
+    limits: ControlLimits,
+}
+
+impl ControlChart for Shewhart {
+    fn reset(&mut self) {}
+
+    fn update(&mut self, rands: &[f64]) -> Option<SignalRule> {
+        let xbar = sample_mean(rands);
+        let s = sample_stddev(rands);
+        (xbar < self.limits.lcl_xbar || xbar > self.limits.ucl_xbar
+            || s < self.limits.lcl_s || s > self.limits.ucl_s).then_some(SignalRule::Limits)
+    }
+}
+
+
+// X-MR管理図の移動範囲の係数d2，D4（2点の移動範囲）
+const D2_MR: f64 = 1.128;
+const D4_MR: f64 = 3.267;
+
+// 個々の値と移動範囲によるX-MR管理図（サンプル・サイズn = 1の場合）
+// σの推定量は平均移動範囲を用いた$ \hat{\sigma} = \overline{MR} / d_2 $であり，
+// ここでは既知の管理状態の$ \sigma_0 $から$ \overline{MR} = d_2 \sigma_0 $として管理限界を定める
+// 管理限界の幅`sigma_mult`は個々の値のX管理図に適用し，MR管理図は3シグマのD4による上方管理限界とする
+struct Individuals {
+    mu_0: f64,
+    sigma_0: f64,
+    sigma_mult: f64,
+    prev: Option<f64>,
+}
+
+impl Individuals {
+    fn new(scenario: &Scenario) -> Self {
+        Self::with_sigma(scenario, 3.0)
+    }
+
+    fn with_sigma(scenario: &Scenario, sigma_mult: f64) -> Self {
+        let (mu_0, sigma2_0) = scenario.param_in_control();
+        Individuals{ mu_0, sigma_0: sigma2_0.sqrt(), sigma_mult, prev: None }
+    }
+}
+
+impl ControlChart for Individuals {
+    fn reset(&mut self) {
+        self.prev = None;
+    }
+
+    fn update(&mut self, rands: &[f64]) -> Option<SignalRule> {
+        let x = rands[0];
+        let mr = self.prev.map(|x_prev| (x - x_prev).abs());
+        self.prev = Some(x);
+        let beyond_x = (x - self.mu_0).abs() > self.sigma_mult * self.sigma_0;
+        let beyond_mr = mr.map_or(false, |mr| mr > D4_MR * D2_MR * self.sigma_0);
+        (beyond_x || beyond_mr).then_some(SignalRule::Limits)
+    }
+}
+
+
+// 管理限界の幅を指定した管理図
+// サンプル・サイズn = 1ではsを計算できない（c4が定義されない）ため，X̄-s管理図の代わりにX-MR管理図とする
+fn limits_chart(scenario: &Scenario, sigma_mult: f64) -> Result<Box<dyn ControlChart>, process_param::ScenarioError> {
+    if scenario.n_as_usize()? == 1 {
+        Ok(Box::new(Individuals::with_sigma(scenario, sigma_mult)))
+    } else {
+        Ok(Box::new(Shewhart{ limits: control_limits(scenario, sigma_mult)? }))
+    }
+}
+
+
+// Western Electricルールを追加したX̄-s管理図（n = 1ではX-MR管理図）
+struct WesternElectric {
+    rule_1: Box<dyn ControlChart>,
+    rules: RunsRules,
+    mu_0: f64,
+    sigma_xbar: f64,
+    // 直近8時点の標準化したサンプル平均（古い順）
+    history: VecDeque<f64>,
+}
+
+impl WesternElectric {
+    fn new(scenario: &Scenario, rules: RunsRules) -> Result<Self, process_param::ScenarioError> {
+        let (mu_0, sigma2_0) = scenario.param_in_control();
+        let n = scenario.n_as_usize()?;
+        Ok(WesternElectric{
+            rule_1: limits_chart(scenario, 3.0)?,
+            rules,
+            mu_0,
+            sigma_xbar: (sigma2_0 / n as f64).sqrt(),
+            history: VecDeque::with_capacity(8),
+        })
+    }
+
+    // 直近m点のうち，同じ側でk倍シグマを超える点がc点以上あるか
+    fn count_beyond(&self, m: usize, c: usize, k: f64) -> bool {
+        if self.history.len() < m {
+            return false;
+        }
+        let recent = self.history.iter().skip(self.history.len() - m);
+        let upper = recent.clone().filter(|z| **z > k).count();
+        let lower = recent.filter(|z| **z < -k).count();
+        upper >= c || lower >= c
+    }
+}
+
+impl ControlChart for WesternElectric {
+    fn reset(&mut self) {
+        self.rule_1.reset();
+        self.history.clear();
+    }
+
+    fn update(&mut self, rands: &[f64]) -> Option<SignalRule> {
+        if self.history.len() == 8 {
+            self.history.pop_front();
+        }
+        self.history.push_back((sample_mean(rands) - self.mu_0) / self.sigma_xbar);
+
+        if let Some(rule) = self.rule_1.update(rands) {
+            Some(rule)
+        } else if self.rules.two_of_three && self.count_beyond(3, 2, 2.0) {
+            Some(SignalRule::TwoOfThree)
+        } else if self.rules.four_of_five && self.count_beyond(5, 4, 1.0) {
+            Some(SignalRule::FourOfFive)
+        } else if self.rules.eight_in_row && self.count_beyond(8, 8, 0.0) {
+            Some(SignalRule::EightInRow)
+        } else {
+            None
+        }
+    }
+}
+
+
+// 標準化したサンプル平均のCUSUM管理図
+struct Cusum {
+    k: f64,
+    h: f64,
+    mu_0: f64,
+    sigma_xbar: f64,
+    c_upper: f64,
+    c_lower: f64,
+}
+
+impl Cusum {
+    fn new(scenario: &Scenario, k: f64, h: f64) -> Result<Self, process_param::ScenarioError> {
+        if !(k >= 0.0 && h > 0.0) {
+            return Err(process_param::ScenarioError{
+                message: format!("CUSUM parameters must satisfy k >= 0 and h > 0, but k = {k} and h = {h}.")
+            });
+        }
+        let (mu_0, sigma2_0) = scenario.param_in_control();
+        let n = scenario.n_as_usize()?;
+        Ok(Cusum{ k, h, mu_0, sigma_xbar: (sigma2_0 / n as f64).sqrt(), c_upper: 0.0, c_lower: 0.0 })
+    }
+
+    // サンプル平均で上側・下側のCUSUMを更新
+    fn step(&mut self, xbar: f64) {
+        let z = (xbar - self.mu_0) / self.sigma_xbar;
+        self.c_upper = (self.c_upper + z - self.k).max(0.0);
+        self.c_lower = (self.c_lower - z - self.k).max(0.0);
+    }
+}
+
+impl ControlChart for Cusum {
+    fn reset(&mut self) {
+        self.c_upper = 0.0;
+        self.c_lower = 0.0;
+    }
+
+    fn update(&mut self, rands: &[f64]) -> Option<SignalRule> {
+        self.step(sample_mean(rands));
+        (self.c_upper > self.h || self.c_lower > self.h).then_some(SignalRule::Cusum)
+    }
+}
+
+
+/// 管理図を併用した複数の乱数列を生成し，それぞれの検出の遅れを取得
+///
+/// [`RandomScenario::from_scenario_controlchart_multiple`]で生成した各乱数列について，
+/// [`RandomScenario::detection_delay`]を並べて返す．
+///
+/// # 引数
+/// * `scenario`- 乱数生成に用いるシナリオ
+/// * `num` - 生成する乱数列の個数
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::detection_delays;
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let delays = detection_delays(&scenario, 10).unwrap();
+/// assert_eq!(delays.len(), 10);
+/// ```
+pub fn detection_delays(scenario: &Scenario, num: usize) -> Result<Vec<Option<i64>>, process_param::ScenarioError> {
+    Ok(RandomScenario::from_scenario_controlchart_multiple(scenario, num)?
+                      .iter()
+                      .map(|r| r.detection_delay())
+                      .collect())
+}
+
+
+/// 平均連長（ARL）の推定結果
+///
+/// # 引数
+/// * `mean` - 連長の標本平均（ARLの推定値）
+/// * `std_error` - ARLの推定値の標準誤差
+/// * `run_lengths` - 各乱数列の連長
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArlStats {
+    pub mean: f64,
+    pub std_error: f64,
+    pub run_lengths: Vec<usize>,
+}
+
+
+/// 管理図を併用した乱数列から平均連長（ARL）を推定
+///
+/// [`RandomScenario::from_scenario_controlchart_multiple`]で`num`個の乱数列を生成し，
+/// 管理外れ状態を検出するまでの時点数（連長，乱数列の長さ）の標本平均と標準誤差を計算する．
+/// ファイルは出力しない．
+///
+/// # 注意
+/// 連長の分布は幾何分布に近く，標準偏差がARLと同程度になる．
+/// そのため標準誤差はおおよそ$ \mathrm{ARL} / \sqrt{num} $であり，
+/// 特に管理状態のARL（3シグマ管理図で370程度）の推定では分散が大きい．
+/// 相対誤差を1%程度にするには`num`を$ 10^4 $程度以上とすることを推奨する．
+///
+/// # 引数
+/// * `scenario`- 乱数生成に用いるシナリオ
+/// * `num` - 生成する乱数列の個数（2以上）
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::estimate_arl;
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let arl = estimate_arl(&scenario, 100).unwrap();
+/// assert_eq!(arl.run_lengths.len(), 100);
+/// assert!(arl.mean > 15.0);
+/// ```
+pub fn estimate_arl(scenario: &Scenario, num: usize) -> Result<ArlStats, ScenarioError> {
+    if num < 2 {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("At least 2 runs are required to estimate ARL, but num = {num}.")));
+    }
+    let run_lengths: Vec<usize> = RandomScenario::from_scenario_controlchart_multiple(scenario, num)?
+                                                 .iter()
+                                                 .map(|r| r.rand_vars().len())
+                                                 .collect();
+    let num_f = num as f64;
+    let mean = run_lengths.iter().sum::<usize>() as f64 / num_f;
+    let var = run_lengths.iter().map(|rl| (*rl as f64 - mean).powi(2)).sum::<f64>() / (num_f - 1.0);
+    Ok(ArlStats{ mean, std_error: (var / num_f).sqrt(), run_lengths })
+}
+
+
+/// [`RandomScenario::from_scenario_multiple`]で乱数列を生成した場合のメモリ使用量を見積もる
+///
+/// 乱数列の値の合計（`num * 時点数 * n * size_of::<f64>()`）に，乱数列ごとの構造体と各時点のベクタの大きさ，
+/// 全ての乱数列で共有するシナリオの複製の大きさを加えた値（バイト）を返す．
+///
+/// # 引数
+/// * `scenario`- 乱数生成に用いるシナリオ
+/// * `num` - 生成する乱数列の個数
+///
+/// # 注意
+/// アロケータによる余分な確保等は含まないため，実際の使用量はこれより多少大きくなります．
+/// 管理図を併用した乱数生成（[`RandomScenario::from_scenario_controlchart_multiple`]等）では
+/// 管理外れ状態を検出するまで時点数が定まらないため，メモリ使用量は予測できません．
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::estimate_memory;
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let bytes = estimate_memory(&scenario, 1000).unwrap();
+/// // 時点数40，サンプル・サイズ10
+/// assert!(bytes >= 1000 * 40 * 10 * std::mem::size_of::<f64>());
+/// assert!(bytes < 2 * estimate_memory(&scenario, 500).unwrap());
+/// ```
+pub fn estimate_memory(scenario: &Scenario, num: usize) -> Result<usize, ScenarioError> {
+    let n = scenario.n_as_usize()?;
+    let horizon = super::scenario_horizon(scenario)?;
+    let per_random = std::mem::size_of::<RandomScenario>()
+                     + horizon * std::mem::size_of::<Vec<<Parameter as Process>::Observation>>()
+                     + horizon * n * std::mem::size_of::<<Parameter as Process>::Observation>();
+    Ok(num * per_random + std::mem::size_of::<Scenario>())
+}
+
+
+/// 乱数列全体をメモリに保持せずに，Seedを指定したScenarioから生成した乱数列をcsvファイルに出力
+///
+/// 1時点分の乱数を生成するたびにファイルへ書き出して破棄するため，乱数列（時点数×サンプル・サイズの値）をメモリに保持しない．
+/// 出力は[`RandomScenario::from_scenario_seed`]で生成した乱数列を[`RandomScenario::to_csv`]で出力した場合と一致する．
+///
+/// # 引数
+/// * `scenario` - 乱数生成に用いるシナリオ
+/// * `seed` - 乱数生成に用いるseed値
+/// * `path` - 出力ファイルパス
+///
+/// # 注意
+/// 管理図が管理外れ状態を検出した時点で乱数生成を終了する機能（[`RandomScenario::from_scenario_seed_controlchart`]等）は利用できません．
+/// 各時点のパラメータ（`Scenario::decomplession`）は全時点分を展開して保持するため，時点数に比例したメモリ（1時点につき`Parameter`1つ分）を必要とします．
+/// サンプル・サイズ$ n = 1 $の場合，これは乱数列そのものより大きくなります．
+/// 削減できるのは時点数×サンプル・サイズの乱数列の分であり，サンプル・サイズが大きいシナリオほど効果があります．
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::{generate_csv_streaming, RandomScenario, Seed};
+/// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+/// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_streaming.csv");
+/// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+/// generate_csv_streaming(&scenario, Seed(42), &path_csv).unwrap();
+/// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+/// assert_eq!(std::fs::read_to_string(&path_csv).unwrap(), randoms.to_csv_string().unwrap());
+/// ```
+pub fn generate_csv_streaming<P: AsRef<Path>>(scenario: &Scenario, seed: Seed, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+    let dec_param = scenario.decomplession()?;
+    let mut wtr = csv::WriterBuilder::new()
+                      .flexible(true)
+                      .from_writer(BufWriter::new(fs::File::create(path)?));
+    for rnds in RowGenerator::new(scenario, &dec_param, Mt64::new(seed.0))? {
+        wtr.serialize(csv_record(&rnds))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+
+/// 管理状態のシナリオにおける誤警報率を推定
+///
+/// 管理状態のパラメータのみに従う乱数列を，管理外れ状態を検出しても再生成せずに`horizon`時点分生成し，
+/// 3シグマの$ \bar{X} - s $管理図（[`control_limits`]）が`horizon`時点以内に管理外れ状態を検出した乱数列の割合を返す．
+/// サンプル・サイズ$ n = 1 $の場合はsを計算できないため，X-MR管理図を用いる．
+/// これを`num`個の乱数列について行う．
+///
+/// # 注意
+/// [`RandomScenario::from_scenario_seed_controlchart`]等は，管理状態の区間で管理外れ状態を検出すると乱数列を再生成するため，
+/// 誤警報は結果に現れません．
+/// 誤警報率の推定には，この関数のように再生成を行わない乱数生成が必要です．
+/// シナリオに変化点がある場合も，管理状態（最初の区間）のパラメータのみを用います．
+///
+/// # 引数
+/// * `scenario`- 乱数生成に用いるシナリオ
+/// * `num` - 生成する乱数列の個数（1以上）
+/// * `horizon` - 各乱数列の時点数
+///
+/// # 使用例
+/// 3シグマ管理図の管理状態のARLは370程度であり，1時点あたりの誤警報率はおよそ0.0027である．
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::estimate_false_alarm_rate;
+/// let scenario = Scenario::parse_toml_str(r#"
+/// n = 5
+/// parameter = [{tau = 100, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}}]
+/// "#).unwrap();
+/// let rate = estimate_false_alarm_rate(&scenario, 1000, 100).unwrap();
+/// assert!(0.05 < rate && rate < 0.8);
+/// assert_eq!(estimate_false_alarm_rate(&scenario, 10, 0).unwrap(), 0.0);
+///
+/// // サンプル・サイズ1ではX-MR管理図を用いる
+/// let individual = Scenario::parse_toml_str(r#"
+/// n = 1
+/// parameter = [{tau = 100, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}}]
+/// "#).unwrap();
+/// let rate = estimate_false_alarm_rate(&individual, 1000, 100).unwrap();
+/// assert!(0.05 < rate && rate < 0.9);
+/// ```
+pub fn estimate_false_alarm_rate(scenario: &Scenario, num: usize, horizon: usize) -> Result<f64, ScenarioError> {
+    estimate_false_alarm_rate_with(scenario, num, horizon, &GenOptions::default())
+}
+
+
+/// 設定を指定して，再生成を行わない管理図から誤警報率を推定
+///
+/// [`estimate_false_alarm_rate`]と同様だが，`options.deterministic`が`true`の場合は
+/// 暗黙のエントロピーでseed値を生成せずにエラーを返す．
+///
+/// # 引数
+/// * `scenario`- 乱数生成に用いるシナリオ
+/// * `num` - 生成する乱数列の個数（1以上）
+/// * `horizon` - 各乱数列の時点数
+/// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::GenOptions;
+/// # use rand_scenario::norm::estimate_false_alarm_rate_with;
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+/// assert!(estimate_false_alarm_rate_with(&scenario, 10, 10, &options).is_err());
+/// ```
+pub fn estimate_false_alarm_rate_with(scenario: &Scenario, num: usize, horizon: usize, options: &GenOptions) -> Result<f64, ScenarioError> {
+    if num == 0 {
+        return Err(ScenarioError::new(
+            ScenarioErrorKind::OutOfRange,
+            "At least 1 run is required to estimate false alarm rate, but num = 0."
+        ));
+    }
+    let (mu_0, sigma2_0) = scenario.param_in_control();
+    let param_0 = Parameter::new(mu_0, sigma2_0)?;
+    let n = scenario.n_as_usize()?;
+    let seeds = super::entropy_seeds(num, options.deterministic)?;
+    let alarms = seeds.par_iter()
+                      .map(|seed| -> Result<bool, ScenarioError> {
+                          // 再生成せずに，horizon時点以内に管理外れ状態を検出するか
+                          let mut rng = Mt64::new(seed.0);
+                          let mut chart = limits_chart(scenario, 3.0)?;
+                          Ok((0..horizon).any(|_| chart.update(&rand_norm_n(&param_0, &mut rng, n)).is_some()))
+                      })
+                      .collect::<Result<Vec<bool>, ScenarioError>>()?;
+    let num_alarm = alarms.iter().filter(|alarm| **alarm).count();
+    Ok(num_alarm as f64 / num as f64)
+}
+
+
+extern crate rand;
+use rand::{RngCore, SeedableRng};
+extern crate rand_mt;
+use rand_mt::Mt64;
+use super::par::*;
+impl RandomScenario {
+    // 生成した乱数列からRandomScenarioを作成
+    // 管理外れ状態の検出時点等は，必要に応じて構造体更新構文で設定する
+    fn new(scenario: Arc<Scenario>, seed: Seed, random_variables: Vec<Vec<<Parameter as Process>::Observation>>) -> Self {
+        RandomScenario{ scenario, seed, random_variables, signal: None, contaminated: Vec::new(), derived: false }
+    }
+
+    /// 乱数列を取得
+    pub fn rand_vars(&self) -> &Vec<Vec<<Parameter as Process>::Observation>> {
+        &self.random_variables
+    }
+
+    /// seedを取得
+    pub fn get_seed(&self) -> Seed {
+        self.seed
+    }
+
+    /// 時点`t`のn個のサンプルを取得
+    ///
+    /// `t`が時点数以上の場合は`None`を返す．インデックス（`randoms[t]`）と異なりパニックしない．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.get(39), Some(&randoms[39]));
+    /// assert_eq!(randoms.get(40), None);
+    /// ```
+    pub fn get(&self, t: usize) -> Option<&Vec<<Parameter as Process>::Observation>> {
+        self.random_variables.get(t)
+    }
+
+    /// 時点`t`の`i`番目（0始まり）のサンプルを取得
+    ///
+    /// `t`が時点数以上の場合や，`i`がその時点のサンプル・サイズ以上の場合は`None`を返す．
+    /// 時点ごとにサンプル・サイズが異なる乱数列でも安全に利用できる．
+    ///
+    /// # 引数
+    /// * `t` - 時点
+    /// * `i` - サンプルの番号
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.get_sample(3, 9), Some(randoms.rand_vars()[3][9]));
+    /// assert_eq!(randoms.get_sample(3, 10), None);
+    /// assert_eq!(randoms.get_sample(40, 0), None);
+    /// ```
+    pub fn get_sample(&self, t: usize, i: usize) -> Option<f64> {
+        self.get(t)?.get(i).copied()
+    }
+
+    /// 時点`t`のサンプルを順に走査
+    ///
+    /// `t`が時点数以上の場合は何も返さない．
+    ///
+    /// # 引数
+    /// * `t` - 時点
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let samples: Vec<f64> = randoms.samples_at(3).collect();
+    /// assert_eq!(&samples, &randoms.rand_vars()[3]);
+    /// assert_eq!(randoms.samples_at(40).count(), 0);
+    /// ```
+    pub fn samples_at(&self, t: usize) -> impl Iterator<Item = f64> + '_ {
+        self.get(t).into_iter().flatten().copied()
+    }
+
+    /// 乱数列を1次元のベクトルに平坦化して取得
+    ///
+    /// 時点の昇順に，各時点のn個のサンプルを連結する（行優先）．
+    /// すなわち，時点tのi番目のサンプル`rand_vars()[t][i]`は，返り値の`t * n + i`番目の要素となる．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let flat = randoms.flatten();
+    /// let n = randoms.rand_vars()[0].len();
+    /// assert_eq!(flat.len(), randoms.rand_vars().len() * n);
+    /// assert_eq!(flat[n + 1], randoms.rand_vars()[1][1]);
+    /// ```
+    pub fn flatten(&self) -> Vec<f64> {
+        self.rand_vars().iter().flatten().cloned().collect()
+    }
+
+    /// 各時点のサンプル・サイズを取得
+    ///
+    /// 通常はすべての時点でシナリオのサンプル・サイズ$ n $となる．
+    /// [`RandomScenario::from_scenario_variable_n`]で生成した乱数列では，区間ごとに異なる値となる．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert!(randoms.sample_sizes().iter().all(|n| *n == 10));
+    /// ```
+    pub fn sample_sizes(&self) -> Vec<usize> {
+        self.rand_vars().iter().map(|rnds| rnds.len()).collect()
+    }
+
+    /// 平坦化した乱数列とその形状を取得
+    ///
+    /// [`RandomScenario::flatten`]の返り値と，形状`(時点数, サンプル・サイズn)`の組を返す．
+    /// 要素の並びは[`RandomScenario::flatten`]と同じ行優先である．
+    /// 時点ごとにサンプル・サイズが異なる場合，`n`は最初の時点のサンプル・サイズとなるため，
+    /// 形状の復元には[`RandomScenario::sample_sizes`]を用いること．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let (flat, num_timepoints, n) = randoms.flatten_with_shape();
+    /// assert_eq!((num_timepoints, n), (40, 10));
+    /// assert_eq!(flat.len(), num_timepoints * n);
+    /// ```
+    pub fn flatten_with_shape(&self) -> (Vec<f64>, usize, usize) {
+        let num_timepoints = self.rand_vars().len();
+        let n = self.rand_vars().first().map_or(0, |rnds| rnds.len());
+        (self.flatten(), num_timepoints, n)
+    }
+
+    /// 管理図が管理外れ状態を検出したシグナルを取得
+    ///
+    /// 管理図を併用せずに生成した乱数列や，TOMLファイルから読み込んだ乱数列では`None`となる．
+    pub fn signal(&self) -> Option<Signal> {
+        self.signal
+    }
+
+    /// 外れ値を混入させた観測値の位置を取得
+    ///
+    /// [`RandomScenario::from_scenario_seed_contaminated`]で外れ値とした観測値の位置`(時点, サンプル番号)`を時点の昇順に返す．
+    /// それ以外の方法で生成した乱数列では空となる．
+    pub fn contaminated(&self) -> &[(usize, usize)] {
+        &self.contaminated
+    }
+
+    /// シナリオとseed値を除いた乱数列を取得
+    ///
+    /// 数値のみを分析する場合に用いる（[`crate::data::RandomData`]を参照）．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let data = randoms.data();
+    /// assert_eq!((data.num_timepoints(), data.n()), (40, 10));
+    /// assert_eq!(data.flatten(), randoms.flatten());
+    /// assert_eq!(data.sample_means().len(), 40);
+    /// ```
+    pub fn data(&self) -> super::data::RandomData {
+        super::data::RandomData::from(self)
+    }
+
+    /// 乱数列の要約統計量を計算
+    ///
+    /// 全時点の観測値をまとめた平均，不偏分散，最小値，最大値と，時点数，サンプル・サイズ，
+    /// （管理図を併用した場合は）管理外れ状態を検出するまでの時点数（連長）を返す．
+    /// 欠測値（NaN）は除いて計算する．
+    /// 設定した分散から大きく外れていないか等，シナリオの誤りを簡単に確認するために用いる．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let summary = randoms.summary();
+    /// assert_eq!((summary.num_timepoints, summary.n, summary.run_length), (40, 10, None));
+    /// assert!(summary.min <= summary.mean && summary.mean <= summary.max);
+    /// println!("{}", summary);
+    ///
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.summary().run_length, Some(randoms.num_timepoints()));
+    /// ```
+    pub fn summary(&self) -> Summary {
+        let values: Vec<f64> = self.rand_vars().iter().flatten().cloned().filter(|x| !x.is_nan()).collect();
+        let count = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1.0);
+        Summary{
+            mean,
+            variance,
+            min: values.iter().cloned().fold(f64::INFINITY, f64::min),
+            max: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            num_timepoints: self.num_timepoints(),
+            n: self.rand_vars().first().map_or(0, |rands| rands.len()),
+            run_length: self.signal.map(|sig| sig.index + 1),
+        }
+    }
+
+    /// 全観測値のヒストグラムを作成
+    ///
+    /// 平坦化した全観測値（欠測値を除く）の最小値から最大値までを`bins`個の等幅の階級に分け，
+    /// 各階級の中央値と度数の組を返す．
+    /// Box-Muller法の出力が正規分布らしい形をしているかを簡単に確認するために用いる．
+    /// 文字列での表示には[`sparkline`]を用いる．
+    ///
+    /// # 引数
+    /// * `bins` - 階級の数
+    ///
+    /// # 注意
+    /// 全ての観測値が同じ値の場合は，その値を中央値とする1つの階級のみを返します．
+    /// 観測値がない場合や`bins`が0の場合は空のベクタを返します．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed, sparkline};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let hist = randoms.histogram(10);
+    /// assert_eq!(hist.len(), 10);
+    /// assert_eq!(hist.iter().map(|(_, c)| c).sum::<usize>(), 400);
+    /// println!("{}", sparkline(&hist));
+    /// ```
+    pub fn histogram(&self, bins: usize) -> Vec<(f64, usize)> {
+        let values: Vec<f64> = self.rand_vars().iter().flatten().cloned().filter(|x| !x.is_nan()).collect();
+        if values.is_empty() || bins == 0 {
+            return Vec::new();
+        }
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        if min == max {
+            return vec![(min, values.len())];
+        }
+        let width = (max - min) / bins as f64;
+        let mut counts = vec![0; bins];
+        for x in values.iter() {
+            // 最大値は最後の階級に含める
+            let i = (((x - min) / width) as usize).min(bins - 1);
+            counts[i] += 1;
+        }
+        counts.into_iter()
+              .enumerate()
+              .map(|(i, c)| (min + width * (i as f64 + 0.5), c))
+              .collect()
+    }
+
+    /// 生成した乱数列を加工して得た乱数列かどうか
+    ///
+    /// [`RandomScenario::downsample`]等で加工した場合は`true`となる．
+    /// このとき，seed値から同じ乱数列を再生成することはできない．
+    pub fn is_derived(&self) -> bool {
+        self.derived
+    }
+
+    /// 真の変化点を取得
+    ///
+    /// 乱数生成に用いたシナリオにおいてパラメータが変化する時点のインデックスを返す．
+    /// 詳しくは[`change_points`]を参照．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.change_points().unwrap(), vec![15, 30]);
+    /// assert_eq!(randoms.rand_vars().len(), 40);
+    /// ```
+    pub fn change_points(&self) -> Result<Vec<usize>, ScenarioError> {
+        Ok(change_points(&self.scenario)?)
+    }
+
+    /// 乱数列の時点数を取得
+    pub fn num_timepoints(&self) -> usize {
+        self.rand_vars().len()
+    }
+
+    /// 乱数生成に用いたシナリオの時点数を取得
+    ///
+    /// [`scenario_horizon`](crate::scenario_horizon)を参照．
+    /// 管理図を併用せずに生成した乱数列では[`RandomScenario::num_timepoints`]と一致する．
+    /// 管理図を併用して生成した乱数列の時点数は，管理外れ状態を検出した時点により異なる．
+    ///
+    /// # 注意
+    /// 呼び出すたびにシナリオのパラメータを展開します．
+    /// [`RandomScenario::parse_toml_str`]等で読み込んだ乱数列のシナリオは展開できるとは限らないため，
+    /// 展開に失敗した場合はエラーを返します．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.horizon().unwrap(), 40);
+    /// ```
+    pub fn horizon(&self) -> Result<usize, ScenarioError> {
+        super::scenario_horizon(&self.scenario)
+    }
+
+    /// 時点`t`で乱数列を2つに分割
+    ///
+    /// 時点`t`より前の行（`0..t`）と，時点`t`以降の行（`t..`）の組を返す．
+    /// 学習用・検証用のデータの作成等に用いる．
+    ///
+    /// # 引数
+    /// * `t` - 分割する時点．[`RandomScenario::num_timepoints`]以下とする．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let (pre, post) = randoms.split_at(10).unwrap();
+    /// assert_eq!((pre.len(), post.len()), (10, 30));
+    /// assert_eq!(post[0], randoms.rand_vars()[10]);
+    /// assert!(randoms.split_at(41).is_err());
+    /// ```
+    pub fn split_at(&self, t: usize) -> Result<(Vec<Vec<f64>>, Vec<Vec<f64>>), ScenarioError> {
+        if t > self.num_timepoints() {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Split point must be at most {}, but t = {}.", self.num_timepoints(), t)
+            ));
+        }
+        let (pre, post) = self.rand_vars().split_at(t);
+        Ok((pre.to_vec(), post.to_vec()))
+    }
+
+    /// `factor`時点ごとに間引いた乱数列を作成
+    ///
+    /// 時点`0, factor, 2 * factor, ...`の行のみからなる乱数列を返す．
+    /// 長い乱数列をグラフに描く際のデータ量の削減を目的としたものであり，統計的な処理ではない．
+    /// シナリオの各区間の終端`tau`は，間引いた後の行数に合わせて$ \lceil \tau / factor \rceil $に置き換える．
+    /// ただし，区間内で変化する平均・分散の傾き等のパラメータは変換しない．
+    /// 管理外れ状態を検出した時点と外れ値の位置の時点`t`は，その時点を含む間隔の先頭の行`t / factor`（切り捨て）に対応付ける．
+    /// seed値は元の乱数列のものを引き継ぎ，[`RandomScenario::is_derived`]は`true`となる．
+    ///
+    /// # 引数
+    /// * `factor` - 間引く間隔（1以上）
+    ///
+    /// # 注意
+    /// 間引いた結果，区間の長さが0となる場合はエラーを返します．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let thinned = randoms.downsample(4).unwrap();
+    /// assert_eq!(thinned.num_timepoints(), 10);
+    /// assert_eq!(thinned.rand_vars()[1], randoms.rand_vars()[4]);
+    /// assert_eq!(thinned.change_points().unwrap(), vec![4, 8]);
+    /// assert_eq!(thinned.get_seed(), randoms.get_seed());
+    /// assert!(thinned.is_derived() && !randoms.is_derived());
+    ///
+    /// // 検出した時点は間引いた後の行に対応付ける
+    /// let charted = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap();
+    /// let thinned = charted.downsample(4).unwrap();
+    /// assert_eq!(thinned.signal().unwrap().index, charted.signal().unwrap().index / 4);
+    /// ```
+    pub fn downsample(&self, factor: usize) -> Result<Self, ScenarioError> {
+        if factor == 0 {
+            return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Downsampling factor must be positive, but factor = 0."));
+        }
+        let f = factor as i64;
+        let scenario = map_scenario_taus(&self.scenario, |_, tau| (tau + f - 1) / f)?;
+        validate(&scenario)?;
+        let random_variables = self.rand_vars().iter().step_by(factor).cloned().collect();
+        let signal = self.signal.map(|sig| Signal{ index: sig.index / factor, rule: sig.rule });
+        let mut contaminated: Vec<(usize, usize)> = self.contaminated.iter()
+                                                                     .map(|(t, i)| (t / factor, *i))
+                                                                     .collect();
+        contaminated.sort_unstable();
+        contaminated.dedup();
+        Ok(RandomScenario{ signal, contaminated, derived: true, ..Self::new(Arc::new(scenario), self.seed, random_variables) })
+    }
+
+    /// 管理状態の行を復元抽出（ブートストラップ）した乱数列を作成
+    ///
+    /// 最初の変化点より前（管理状態）の行から，行単位で復元抽出して元の乱数列と同じ時点数の乱数列を作る．
+    /// 各行内のn個のサンプルの構造はそのまま保たれる．
+    /// 変化点のないシナリオでは全ての行から抽出する．
+    /// 抽出には`seed`で初期化したMersenne-Twister法の一様乱数を用いるため，同じ`seed`からは同じ乱数列が得られる．
+    ///
+    /// 返り値のシナリオは管理状態のパラメータのみからなる1区間のシナリオとなる．
+    /// seed値は元の乱数列のものを引き継ぎ，[`RandomScenario::is_derived`]は`true`となる．
+    ///
+    /// # 引数
+    /// * `seed` - 復元抽出に用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let resampled = randoms.bootstrap(Seed(7)).unwrap();
+    /// assert_eq!(resampled.num_timepoints(), randoms.num_timepoints());
+    /// assert!(resampled.change_points().unwrap().is_empty());
+    /// // 各行は管理状態の行のいずれかと一致
+    /// let in_control = &randoms.rand_vars()[..15];
+    /// assert!(resampled.rand_vars().iter().all(|row| in_control.contains(row)));
+    /// assert_eq!(resampled, randoms.bootstrap(Seed(7)).unwrap());
+    /// ```
+    pub fn bootstrap(&self, seed: Seed) -> Result<Self, ScenarioError> {
+        let len = self.num_timepoints();
+        let num_in_control = self.change_points()?.first().map_or(len, |cp| (*cp).min(len));
+        if num_in_control == 0 {
+            return Err(ScenarioError::new(ScenarioErrorKind::Other, "Random variables have no in-control rows to resample."));
+        }
+        let scenario = edit_scenario_parameter(&self.scenario, |parameter| {
+            parameter.truncate(1);
+            if let Some(cp) = parameter.first_mut() {
+                cp["tau"] = toml::Value::Integer(len as i64);
+            }
+        })?;
+        let mut rng = Mt64::new(seed.0);
+        let in_control = &self.rand_vars()[..num_in_control];
+        let random_variables = (0..len).map(|_| {
+                                           let i = ((super::rand_uniform(&mut rng) * num_in_control as f64) as usize).min(num_in_control - 1);
+                                           in_control[i].clone()
+                                       })
+                                       .collect();
+        Ok(RandomScenario{ derived: true, ..Self::new(Arc::new(scenario), self.seed, random_variables) })
+    }
+
+    /// 2つの乱数列を連結
+    ///
+    /// `self`の行の後に`other`の行を続けた乱数列を返す．
+    /// 単純なシナリオから生成した乱数列をつなぎ合わせて，変化点を複数持つデータセットを作る際に用いる．
+    /// シナリオは`self`のパラメータ区間の後に`other`のパラメータ区間を続けたものとし，
+    /// `self`の最後の区間の終端を`self`の時点数に，`other`の各区間の終端を`self`の時点数だけずらしたものとする．
+    /// したがって，`other`の先頭が新たな変化点となる．
+    ///
+    /// seed値は`self`のものを引き継ぎ，[`RandomScenario::is_derived`]は`true`となる．
+    /// 外れ値の位置は両者のものを引き継ぎ，管理外れ状態を検出したシグナルは引き継がない．
+    ///
+    /// # 引数
+    /// * `other` - 後ろに連結する乱数列
+    ///
+    /// # 注意
+    /// サンプル・サイズnが異なる場合はエラーを返します．
+    /// また，いずれかの乱数列の時点数がシナリオの時点数（最後の区間の終端）と異なる場合もエラーを返します．
+    /// 管理図を併用して管理外れ状態を検出した時点で打ち切った乱数列等は，シナリオの区間と行数が対応しないため連結できません．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let first = RandomScenario::from_scenario_seed(&scenario, Seed(1)).unwrap();
+    /// let second = RandomScenario::from_scenario_seed(&scenario, Seed(2)).unwrap();
+    /// let joined = first.concat(&second).unwrap();
+    /// assert_eq!(joined.num_timepoints(), 80);
+    /// assert_eq!(joined.change_points().unwrap(), vec![15, 30, 40, 55, 70]);
+    /// assert_eq!(joined.rand_vars()[40], second.rand_vars()[0]);
+    ///
+    /// // サンプル・サイズが異なる場合
+    /// let other_n = Scenario::parse_toml_str(r#"
+    /// n = 5
+    /// parameter = [{tau = 10, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}}]
+    /// "#).unwrap();
+    /// let third = RandomScenario::from_scenario_seed(&other_n, Seed(3)).unwrap();
+    /// assert!(first.concat(&third).is_err());
+    ///
+    /// // 時点数がシナリオの時点数（2）に満たない場合
+    /// let short = RandomScenario::parse_toml_str(r#"
+    /// seed = "4"
+    /// random_variables = [[0.5, -0.5, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8]]
+    ///
+    /// [scenario]
+    /// n = 10
+    /// parameter = [
+    ///     {tau = 2, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}},
+    /// ]
+    /// "#).unwrap();
+    /// assert!(short.concat(&first).is_err());
+    /// assert!(first.concat(&short).is_err());
+    /// ```
+    pub fn concat(&self, other: &RandomScenario) -> Result<Self, ScenarioError> {
+        let (layout_self, layout_other) = (ScenarioLayout::from_scenario(&self.scenario)?, ScenarioLayout::from_scenario(&other.scenario)?);
+        if layout_self.n != layout_other.n {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Sample sizes must be equal, but n = {} and {}.", layout_self.n, layout_other.n)
+            ));
+        }
+        // 区間の終端をずらして連結するため，両者とも行数がシナリオの時点数と一致する必要がある
+        for (randoms, layout) in [(self, &layout_self), (other, &layout_other)] {
+            let horizon = layout.parameter.last().map_or(0, |cp| cp.tau);
+            if randoms.num_timepoints() as i64 != horizon {
+                return Err(ScenarioError::new(
+                    ScenarioErrorKind::OutOfRange,
+                    format!("Only series spanning their scenario horizon can be concatenated, but {} rows for horizon {}.", randoms.num_timepoints(), horizon)
+                ));
+            }
+        }
+        let offset = self.num_timepoints();
+        let other_parameter: Vec<toml::Value> = match toml::from_str::<toml::Value>(&other.scenario.to_toml_string()) {
+            Ok(value) => value.get("parameter").and_then(|p| p.as_array()).cloned().unwrap_or_default(),
+            Err(e) => return Err(ScenarioError::new(ScenarioErrorKind::Parse, format!("Scenario layout cannot be read: {e}")).with_source(e)),
+        };
+        let scenario = edit_scenario_parameter(&self.scenario, |parameter| {
+            if let Some(cp) = parameter.last_mut() {
+                cp["tau"] = toml::Value::Integer(offset as i64);
+            }
+            for mut cp in other_parameter {
+                if let Some(tau) = cp.get("tau").and_then(|t| t.as_integer()) {
+                    cp["tau"] = toml::Value::Integer(tau + offset as i64);
+                }
+                parameter.push(cp);
+            }
+        })?;
+        let random_variables = self.rand_vars().iter().chain(other.rand_vars().iter()).cloned().collect();
+        let contaminated = self.contaminated.iter()
+                                            .cloned()
+                                            .chain(other.contaminated.iter().map(|(t, i)| (t + offset, *i)))
+                                            .collect();
+        Ok(RandomScenario{ contaminated, derived: true, ..Self::new(Arc::new(scenario), self.seed, random_variables) })
+    }
+
+    /// `k`番目（0始まり）の変化点で乱数列を2つに分割
+    ///
+    /// [`RandomScenario::change_points`]の`k`番目の時点で[`RandomScenario::split_at`]を行う．
+    /// 変化点前の行と変化点以降の行の組を返す．
+    ///
+    /// # 引数
+    /// * `k` - 分割に用いる変化点の番号
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let (pre, post) = randoms.split_at_change_point(1).unwrap();
+    /// assert_eq!((pre.len(), post.len()), (30, 10));
+    /// assert!(randoms.split_at_change_point(2).is_err());
+    /// ```
+    pub fn split_at_change_point(&self, k: usize) -> Result<(Vec<Vec<f64>>, Vec<Vec<f64>>), ScenarioError> {
+        let change_points = self.change_points()?;
+        match change_points.get(k) {
+            Some(t) => self.split_at(*t),
+            None => Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Scenario has {} change points, but k = {}.", change_points.len(), k)
+            )),
+        }
+    }
+
+    /// `k`番目（0始まり）のパラメータ区間の乱数列を取得
+    ///
+    /// [`RandomScenario::change_points`]を境界として，`k`番目の区間に属する行のみを返す．
+    /// 区間0は最初の変化点より前，区間`k`は`k - 1`番目の変化点から`k`番目の変化点の前まで，
+    /// 最後の区間は最後の変化点から乱数列の終端までとなる（[`RandomScenario::true_states`]の番号と同じ）．
+    ///
+    /// # 引数
+    /// * `k` - 区間の番号（区間の数は変化点の数 + 1）
+    ///
+    /// # 注意
+    /// 管理図を併用して生成した乱数列では，区間の途中で乱数列が終わる場合や，区間に属する行がない場合があります．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// // 変化点は15と30，時点数は40
+    /// assert_eq!(randoms.segment(0).unwrap().len(), 15);
+    /// assert_eq!(randoms.segment(1).unwrap().len(), 15);
+    /// assert_eq!(randoms.segment(2).unwrap().len(), 10);
+    /// assert_eq!(randoms.segment(1).unwrap()[0], randoms.rand_vars()[15]);
+    /// assert!(randoms.segment(3).is_err());
+    /// ```
+    pub fn segment(&self, k: usize) -> Result<Vec<Vec<f64>>, ScenarioError> {
+        let change_points = self.change_points()?;
+        if k > change_points.len() {
+            return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Scenario has {} segments, but k = {}.", change_points.len() + 1, k)));
+        }
+        let len = self.num_timepoints();
+        let start = if k == 0 { 0 } else { change_points[k - 1].min(len) };
+        let end = change_points.get(k).map_or(len, |t| (*t).min(len));
+        Ok(self.random_variables[start..end].to_vec())
+    }
+
+    /// 最後の変化点から管理図が管理外れ状態を検出するまでの遅れを取得
+    ///
+    /// シグナルの時点から最後の変化点を引いた値$ t_{signal} - \tau_{last} $を返す．
+    /// 最後の変化点の時点で検出した場合は0となる．
+    /// 変化点より前の誤警報は負の値となる（通常は再生成により起こらないが，念のため区別できるようにしている）．
+    /// 管理図を併用していない乱数列や変化点のないシナリオでは`None`を返す．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap();
+    /// let delay = randoms.detection_delay().unwrap();
+    /// assert_eq!(delay, randoms.rand_vars().len() as i64 - 1 - 30);
+    /// // 管理図を併用していない場合
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.detection_delay(), None);
+    /// ```
+    pub fn detection_delay(&self) -> Option<i64> {
+        let signal = self.signal?;
+        let last_cp = *self.change_points().ok()?.last()?;
+        Some(signal.index as i64 - last_cp as i64)
+    }
+
+    /// 各時点で有効なパラメータ区間の番号を取得
+    ///
+    /// 各時点のインデックスを，その時点で有効なパラメータ区間の番号（0が管理状態，以降は変化点を経るごとに1増える）に対応させる．
+    /// 区間は[`RandomScenario::change_points`]から求める．
+    /// 管理図を併用してシナリオの終端より長く生成した乱数列では，終端以降の時点は最後の区間とする．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let states = randoms.true_states().unwrap();
+    /// assert_eq!(states.len(), randoms.rand_vars().len());
+    /// assert_eq!(states[0], 0);
+    /// // 区間の番号が変わる時点は変化点と一致する
+    /// let transitions: Vec<usize> = (1..states.len()).filter(|t| states[*t] != states[t - 1]).collect();
+    /// assert_eq!(transitions, randoms.change_points().unwrap());
+    /// ```
+    pub fn true_states(&self) -> Result<Vec<usize>, ScenarioError> {
+        let cps = self.change_points()?;
+        Ok((0..self.random_variables.len()).map(|t| cps.iter().filter(|cp| **cp <= t).count())
+                                           .collect())
+    }
+
+    /// 各時点で真に有効なパラメータ$ (\mu_t, \sigma_t^2) $を取得
+    ///
+    /// 乱数生成に用いたパラメータの系列を返す．
+    /// 生成時と同じく`Scenario::decomp_exclude_last`から乱数列の時点数まで展開した系列であり，
+    /// 管理図を併用せずに生成した乱数列では`Scenario::decomplession`の系列と一致する．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let params = randoms.true_params().unwrap();
+    /// assert_eq!(params.len(), randoms.rand_vars().len());
+    /// assert_eq!(params[0], (0.0, 1.0));
+    /// assert_eq!(params[15].1, 2.0);
+    ///
+    /// // 時点数を固定して管理外れ状態を検出しなかった場合も，乱数列と同じ時点数となる
+    /// let fixed = RandomScenario::from_scenario_seed_controlchart_fixed(&scenario, Seed(42), 10).unwrap();
+    /// assert_eq!(fixed.signal(), None);
+    /// assert_eq!(fixed.true_params().unwrap(), params[..10].to_vec());
+    /// assert_eq!(fixed.loglik_series().unwrap().len(), 10);
+    /// ```
+    pub fn true_params(&self) -> Result<Vec<(f64, f64)>, ScenarioError> {
+        Ok(self.true_parameters()?.iter().map(param_tuple).collect())
+    }
+
+    /// 各時点の対数尤度の系列を取得
+    ///
+    /// 各時点のn個のサンプルについて，その時点で真に有効なパラメータ（[`RandomScenario::true_params`]）の下での
+    /// 正規分布の対数尤度
+    /// $ \ell_t = -\frac{n}{2} \log (2 \pi \sigma_t^2) - \sum_{i=1}^n \frac{(x_{t,i} - \mu_t)^2}{2 \sigma_t^2} $
+    /// を計算する．
+    /// 対数尤度が極端に小さい時点は，生成された乱数列が真のパラメータに対して非典型的であることを示す．
+    ///
+    /// # 注意
+    /// 分散が正でない時点では密度が定義されないため，エラーを返す．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let loglik = randoms.loglik_series().unwrap();
+    /// assert_eq!(loglik.len(), randoms.rand_vars().len());
+    /// assert!(loglik.iter().all(|l| l.is_finite()));
+    /// ```
+    pub fn loglik_series(&self) -> Result<Vec<f64>, ScenarioError> {
+        let params = self.true_params()?;
+        self.random_variables.iter()
+                             .zip(params.iter())
+                             .enumerate()
+                             .map(|(t, (rands, (mu, sigma2)))| {
+                                 if sigma2.is_nan() || *sigma2 <= 0.0 {
+                                     return Err(ScenarioError::new(
+                                         ScenarioErrorKind::OutOfRange,
+                                         format!("Density is not defined for sigma2 = {sigma2} at t = {t}.")
+                                     ));
+                                 }
+                                 let n = rands.len() as f64;
+                                 let ss: f64 = rands.iter().map(|x| (x - mu).powi(2)).sum();
+                                 Ok(-0.5 * n * (2.0 * std::f64::consts::PI * sigma2).ln() - ss / (2.0 * sigma2))
+                             })
+                             .collect()
+    }
+
+    /// 標準化したサンプル平均の上側・下側CUSUMの系列を取得
+    ///
+    /// 各時点のサンプル平均$ \bar{X}_t $（[`RandomData::sample_means`](crate::data::RandomData::sample_means)）を
+    /// シナリオの管理状態のパラメータ$ (\mu_0, \sigma_0^2) $で$ z_t = (\bar{X}_t - \mu_0) / (\sigma_0 / \sqrt{n}) $と標準化し，
+    /// $ C_t^+ = \max(0, C_{t-1}^+ + z_t - k) $，$ C_t^- = \max(0, C_{t-1}^- - z_t - k) $を計算する．
+    /// 初期値は$ C_0^+ = C_0^- = 0 $であり，和が負になる時点では0に戻す（リセットする）．
+    /// 返り値は上側$ C_t^+ $と下側$ C_t^- $の系列の組であり，
+    /// [`RandomScenario::from_scenario_seed_controlchart_cusum`]の管理図と同じ統計量となる．
+    ///
+    /// # 引数
+    /// * `k` - 参照値（$ k \geq 0 $，標準化した単位）．通常は検出したい平均のずれの大きさの半分とする．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart_cusum(&scenario, Seed(42), 0.5, 4.0).unwrap();
+    /// let (upper, lower) = randoms.cusum_series(0.5).unwrap();
+    /// assert_eq!(upper.len(), randoms.num_timepoints());
+    /// assert!(upper.iter().chain(lower.iter()).all(|c| *c >= 0.0));
+    /// // 決定区間h = 4.0を初めて超えた時点で乱数生成を終了している
+    /// let last = randoms.num_timepoints() - 1;
+    /// assert!(upper[last] > 4.0 || lower[last] > 4.0);
+    /// assert!((0..last).all(|t| upper[t] <= 4.0 && lower[t] <= 4.0));
+    ///
+    /// assert!(randoms.cusum_series(-1.0).is_err());
+    /// ```
+    pub fn cusum_series(&self, k: f64) -> Result<(Vec<f64>, Vec<f64>), ScenarioError> {
+        let mut cusum = Cusum::new(&self.scenario, k, f64::INFINITY)?;
+        let (upper, lower) = self.data()
+                                 .sample_means()
+                                 .into_iter()
+                                 .map(|xbar| {
+                                     cusum.step(xbar);
+                                     (cusum.c_upper, cusum.c_lower)
+                                 })
+                                 .unzip();
+        Ok((upper, lower))
+    }
+
+    /// サンプル平均のEWMAの系列を取得
+    ///
+    /// 各時点のサンプル平均$ \bar{X}_t $（[`RandomData::sample_means`](crate::data::RandomData::sample_means)）から
+    /// $ Z_t = \lambda \bar{X}_t + (1 - \lambda) Z_{t-1} $を計算する．
+    /// 初期値$ Z_0 $はシナリオの管理状態の平均$ \mu_0 $とする．
+    /// [`RandomScenario::from_scenario_seed_controlchart_ewma`]の管理図と同じ統計量であり，
+    /// 管理限界は[`RandomScenario::ewma_limits`]で取得できる．
+    ///
+    /// # 引数
+    /// * `lambda` - 平滑化定数（$ 0 < \lambda \leq 1 $）
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart_ewma(&scenario, Seed(42), 0.2).unwrap();
+    /// let ewma = randoms.ewma_series(0.2).unwrap();
+    /// let limits = randoms.ewma_limits(0.2).unwrap();
+    /// assert_eq!(ewma.len(), randoms.num_timepoints());
+    /// // 管理限界を初めて外れた時点で乱数生成を終了している
+    /// let last = randoms.num_timepoints() - 1;
+    /// assert!(ewma[last] < limits[last].0 || ewma[last] > limits[last].1);
+    /// assert!((0..last).all(|t| limits[t].0 <= ewma[t] && ewma[t] <= limits[t].1));
+    ///
+    /// // lambda = 1ではサンプル平均そのもの
+    /// assert_eq!(randoms.ewma_series(1.0).unwrap(), randoms.data().sample_means());
+    /// assert!(randoms.ewma_series(0.0).is_err());
+    /// assert!(randoms.ewma_series(1.5).is_err());
+    /// ```
+    pub fn ewma_series(&self, lambda: f64) -> Result<Vec<f64>, ScenarioError> {
+        let mut ewma = Ewma::new(&self.scenario, lambda)?;
+        Ok(self.data()
+               .sample_means()
+               .into_iter()
+               .map(|xbar| {
+                   ewma.step(xbar);
+                   ewma.z
+               })
+               .collect())
+    }
+
+    /// EWMA管理図の各時点の管理限界を取得
+    ///
+    /// [`RandomScenario::ewma_series`]の各時点$ t = 1, 2, \ldots $に対する3シグマの管理限界
+    /// $ \mu_0 \pm 3 \frac{\sigma_0}{\sqrt{n}} \sqrt{\frac{\lambda}{2 - \lambda} \left( 1 - (1 - \lambda)^{2t} \right)} $
+    /// を，（下方管理限界，上方管理限界）の組として返す．
+    /// 管理限界の幅は時点とともに広がり，漸近的に一定となる．
+    ///
+    /// # 引数
+    /// * `lambda` - 平滑化定数（$ 0 < \lambda \leq 1 $）
+    ///
+    /// # 使用例
+    /// 使用例は[`RandomScenario::ewma_series`]を参照．
+    pub fn ewma_limits(&self, lambda: f64) -> Result<Vec<(f64, f64)>, ScenarioError> {
+        let ewma = Ewma::new(&self.scenario, lambda)?;
+        Ok((1..=self.num_timepoints())
+               .map(|t| {
+                   let half_width = ewma.half_width(t as i32);
+                   (ewma.mu_0 - half_width, ewma.mu_0 + half_width)
+               })
+               .collect())
+    }
+
+    /// 乱数列がシナリオと統計的に整合するかを検証
+    ///
+    /// [`RandomScenario::verify_against_scenario_with`]で統計量に[`VerifyStatistic::Mean`]を用いる．
+    /// すなわち，パラメータの区間ごとに標本平均が真の平均から標準誤差の`tol`倍以内にあることを確認する．
+    /// TOMLファイルから読み込んだ乱数列の破損や，シナリオとの取り違えの検出に用いる．
+    ///
+    /// # 引数
+    /// * `tol` - 許容する統計量の絶対値の上限（標準誤差の何倍までのずれを許容するか）
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let read = RandomScenario::parse_toml_str(&randoms.to_toml_string()).unwrap();
+    /// assert!(read.verify_against_scenario(5.0).is_ok());
+    ///
+    /// // 平均0のシナリオに対して値が大きくずれている
+    /// let mismatched = RandomScenario::parse_toml_str(r#"
+    /// seed = "42"
+    /// random_variables = [[10.0, 10.0], [10.0, 10.0]]
+    ///
+    /// [scenario]
+    /// n = 2
+    /// parameter = [
+    ///     {tau = 2, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}},
+    /// ]
+    /// "#).unwrap();
+    /// assert!(mismatched.verify_against_scenario(5.0).is_err());
+    /// ```
+    pub fn verify_against_scenario(&self, tol: f64) -> Result<(), ScenarioError> {
+        self.verify_against_scenario_with(tol, VerifyStatistic::Mean)
+    }
+
+    /// 統計量を指定して，乱数列がシナリオと統計的に整合するかを検証
+    ///
+    /// パラメータの区間（[`RandomScenario::true_states`]）ごとに`statistic`で指定した統計量を計算し，
+    /// その絶対値が`tol`を超える区間があればエラーを返す．
+    /// 統計量はシナリオと整合する場合に近似的に標準正規分布に従うため，例えば`tol = 4.0`ならば
+    /// 整合する乱数列を誤ってエラーとする確率は区間あたり0.01%程度となる．
+    /// 時点数とシナリオの時点数が一致しない場合もエラーを返す．
+    ///
+    /// # 引数
+    /// * `tol` - 許容する統計量の絶対値の上限（正の値）
+    /// * `statistic` - 用いる統計量
+    ///
+    /// # 注意
+    /// 外れ値を混入させた乱数列（[`RandomScenario::from_scenario_seed_contaminated`]）等，
+    /// シナリオとは異なる分布から生成した乱数列はエラーとなることがあります．
+    /// 欠測値（NaN）は除いて計算します．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed, VerifyStatistic};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert!(randoms.verify_against_scenario_with(5.0, VerifyStatistic::MeanAndVariance).is_ok());
+    ///
+    /// // 平均は一致するが分散が小さすぎる
+    /// let too_narrow = RandomScenario::parse_toml_str(r#"
+    /// seed = "42"
+    /// random_variables = [[0.01, -0.01], [0.01, -0.01], [0.01, -0.01], [0.01, -0.01]]
+    ///
+    /// [scenario]
+    /// n = 2
+    /// parameter = [
+    ///     {tau = 4, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 100.0}},
+    /// ]
+    /// "#).unwrap();
+    /// assert!(too_narrow.verify_against_scenario_with(1.5, VerifyStatistic::Mean).is_ok());
+    /// assert!(too_narrow.verify_against_scenario_with(1.5, VerifyStatistic::Variance).is_err());
+    /// assert!(too_narrow.verify_against_scenario_with(0.0, VerifyStatistic::Mean).is_err());
+    /// ```
+    pub fn verify_against_scenario_with(&self, tol: f64, statistic: VerifyStatistic) -> Result<(), ScenarioError> {
+        if tol.is_nan() || tol <= 0.0 {
+            return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Tolerance must be positive, but tol = {tol}.")));
+        }
+        let params = self.true_params()?;
+        if params.len() != self.num_timepoints() {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Random variables have {} timepoints, but the scenario has {}.", self.num_timepoints(), params.len())
+            ));
+        }
+        // 区間ごとの標準化した観測値の和，2乗和，個数
+        let states = self.true_states()?;
+        let num_segments = states.last().map_or(0, |k| k + 1);
+        let mut sums = vec![(0.0, 0.0, 0usize); num_segments];
+        for ((rands, (mu, sigma2)), k) in self.random_variables.iter().zip(params.iter()).zip(states.iter()) {
+            if sigma2.is_nan() || *sigma2 <= 0.0 {
+                return Err(ScenarioError::new(
+                    ScenarioErrorKind::OutOfRange,
+                    format!("Standardization is not defined for sigma2 = {sigma2} in segment {k}.")
+                ));
+            }
+            for x in rands.iter().filter(|x| !x.is_nan()) {
+                let z = (x - mu) / sigma2.sqrt();
+                sums[*k].0 += z;
+                sums[*k].1 += z * z;
+                sums[*k].2 += 1;
+            }
+        }
+        for (k, (sum, sum_sq, count)) in sums.into_iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let m = count as f64;
+            let mut stats = Vec::with_capacity(2);
+            if statistic != VerifyStatistic::Variance {
+                stats.push(("mean", sum / m.sqrt()));
+            }
+            if statistic != VerifyStatistic::Mean {
+                stats.push(("variance", (sum_sq / m - 1.0) / (2.0 / m).sqrt()));
+            }
+            if let Some((name, value)) = stats.into_iter().find(|(_, value)| value.is_nan() || value.abs() > tol) {
+                return Err(ScenarioError::new(
+                    ScenarioErrorKind::OutOfRange,
+                    format!("Segment {k} is inconsistent with the scenario: {name} statistic {value} exceeds tolerance {tol}.")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // 各時点で乱数生成に用いたパラメータ
+    // 管理外れ状態の検出の有無によらず，生成時と同じ手順で乱数列の時点数まで展開する
+    fn true_parameters(&self) -> Result<Vec<Parameter>, process_param::ScenarioError> {
+        let len = self.random_variables.len();
+        let (inctrl_param ,dec_param, last_cp) = self.scenario.decomp_exclude_last()?;
+        let mut params = inctrl_param;
+        params.extend(dec_param);
+        let mut ind_outctrl = 0;
+        while params.len() < len {
+            ind_outctrl = ind_outctrl + 1;
+            match last_cp.get_param(ind_outctrl) {
+                Ok(p) => params.push(p),
+                Err(e) => return Err(process_param::ScenarioError{
+                    message: format!("Parameters are out of range: {e}")
+                }),
+            };
+        }
+        params.truncate(len);
+        Ok(params)
+    }
+
+    /// 最初のパラメータを取得
+    ///
+    /// サンプル自体が従うパラメータを取得する．
+    ///
+    /// # 返り値
+    /// * `param_0` - 最初の状態における正規分布のパラメータ
+    pub fn get_init_param(&self) -> Parameter {
+        let (mu, sigma2) = self.scenario.param_in_control();
+        Parameter::new(mu, sigma2).unwrap()
+    }
+
+    /// サンプル平均の従う最初のパラメータを取得
+    ///
+    /// 正規分布には再生性があり，サンプルの平均値も正規分布に従う．
+    /// 乱数生成の最初の状態において，サンプル平均が従う正規分布のパラメータを取得する．
+    ///
+    /// # 返り値
+    /// * `param_barx0` - 最初の状態でサンプル平均が従う正規分布のパラメータ
+    pub fn get_sm_init_param(&self) -> Parameter {
+        let (mu, sigma2) = self.scenario.param_samplemean();
+        Parameter::new(mu, sigma2).unwrap()
+    }
+
+
+    /// Scenarioから乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// 
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario);
+    /// println!("{:?}", randoms);
+    /// ```
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, process_param::ScenarioError> {
+        Self::from_scenario_with(scenario, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario`]と同様だが，`options.deterministic`が`true`の場合は
+    /// 暗黙のエントロピーでseed値を生成せずにエラーを返す．
+    /// テストやCIで再現できない乱数生成が紛れ込むことを防ぐために利用する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::GenOptions;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_multiple_with(&scenario, 4, &options).is_err());
+    /// // seed値を指定すれば生成できる
+    /// assert!(RandomScenario::from_scenario_seed(&scenario, Seed(42)).is_ok());
+    /// // 決定的モードは呼び出しごとの設定のため，他の呼び出しには影響しない
+    /// assert!(RandomScenario::from_scenario(&scenario).is_ok());
+    /// ```
+    pub fn from_scenario_with(scenario: &Scenario, options: &GenOptions) -> Result<Self, process_param::ScenarioError> {
+        let seed = Seed(super::entropy_rng(options.deterministic)?.next_u64());
+        Self::from_scenario_seed(scenario, seed)
+    }
+
+    /// Seedを指定してScenarioから乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// 
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// println!("{:?}", randoms);
+    /// ```
+    pub fn from_scenario_seed(scenario: &Scenario, seed: Seed) -> Result<Self, process_param::ScenarioError> {
+        Self::from_shared_scenario_seed(&Arc::new(scenario.clone()), seed)
+    }
+
+    // 共有するシナリオから乱数列を生成
+    pub(crate) fn from_shared_scenario_seed(scenario: &Arc<Scenario>, seed: Seed) -> Result<Self, process_param::ScenarioError> {
+        let random_variables = Self::gen_random(&scenario, seed)?;
+        Ok(Self::new(Arc::clone(scenario), seed, random_variables))
+    }
+
+    /// 乱数生成器の型を指定してScenarioから乱数列を生成
+    ///
+    /// 既定のMersenne-Twister法（[`Mt64`]）の代わりに，任意の乱数生成器`R`で一様乱数を生成する．
+    /// 乱数生成器は`R::seed_from_u64(seed.0)`で初期化する．
+    /// そのため，`R = Mt64`としても[`RandomScenario::from_scenario_seed`]（`Mt64::new(seed.0)`で初期化）とは異なる乱数列となる．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// use rand::rngs::StdRng;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_with_rng::<StdRng>(&scenario, Seed(42)).unwrap();
+    /// let randoms_again = RandomScenario::from_scenario_seed_with_rng::<StdRng>(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms, randoms_again);
+    /// ```
+    pub fn from_scenario_seed_with_rng<R: RngCore + SeedableRng>(scenario: &Scenario, seed: Seed) -> Result<Self, process_param::ScenarioError> {
+        let random_variables = Self::gen_random_with_rng(&scenario, &mut R::seed_from_u64(seed.0))?;
+        Ok(Self::new(Arc::new(scenario.clone()), seed, random_variables))
+    }
+
+    /// Seedを指定して自己相関を持つ乱数列をScenarioから生成
+    ///
+    /// 各サンプルの系列にAR(1)フィルタ
+    /// $ x_t = \mu_t + \phi (x_{t-1} - \mu_{t-1}) + \varepsilon_t $
+    /// を適用した乱数列を生成する．
+    /// ここで$ \varepsilon_t \sim N(0, \sigma_t^2) $は[`RandomScenario::from_scenario_seed`]と同じ手順で生成した誤差である．
+    /// `phi = 0.0`のとき[`RandomScenario::from_scenario_seed`]と同じ乱数列となる．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `phi` - AR(1)の係数$ \phi $．定常性のため$ |\phi| < 1 $であること．
+    ///
+    /// # 注意
+    /// 定常状態での分散は$ \sigma^2 / (1 - \phi^2) $となり，シナリオの分散より大きくなります．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_ar1(&scenario, Seed(42), 0.5).unwrap();
+    /// assert_eq!(randoms.rand_vars().len(), 40);
+    /// let iid = RandomScenario::from_scenario_seed_ar1(&scenario, Seed(42), 0.0).unwrap();
+    /// assert_eq!(iid, RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap());
+    /// // 定常でない係数はエラー
+    /// let err = RandomScenario::from_scenario_seed_ar1(&scenario, Seed(42), 1.0).unwrap_err();
+    /// assert_eq!(err.kind, rand_scenario::ScenarioErrorKind::OutOfRange);
+    /// ```
+    pub fn from_scenario_seed_ar1(scenario: &Scenario, seed: Seed, phi: f64) -> Result<Self, ScenarioError> {
+        if phi.is_nan() || phi.abs() >= 1.0 {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("AR(1) coefficient must satisfy |phi| < 1, but phi = {phi}.")
+            ));
+        }
+        let mut random_variables = Self::gen_random(&scenario, seed)?;
+        let mus: Vec<f64> = scenario.decomplession()?.iter().map(|p| param_tuple(p).0).collect();
+        for t in 1..random_variables.len() {
+            let (prev, curr) = random_variables.split_at_mut(t);
+            for (x, x_prev) in curr[0].iter_mut().zip(prev[t - 1].iter()) {
+                *x += phi * (x_prev - mus[t - 1]);
+            }
+        }
+        Ok(Self::new(Arc::new(scenario.clone()), seed, random_variables))
+    }
+
+    /// Seedを指定して平均に季節変動を重ねた乱数列をScenarioから生成
+    ///
+    /// [`RandomScenario::from_scenario_seed`]と同じ乱数列を生成した後，
+    /// 時点$ t $の全ての観測値に決定的な季節変動[`Seasonal::value`]を加える．
+    /// すなわち，平均は$ \mu_t + A \sin(2 \pi t / T + \phi) $となる．
+    /// 季節変動を真の変化と誤認しないかの検証等に用いる．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `seasonal` - 季節変動
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed, Seasonal};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let seasonal = Seasonal{ amplitude: 2.0, period: 7.0, phase: 0.0 };
+    /// let randoms = RandomScenario::from_scenario_seed_seasonal(&scenario, Seed(42), seasonal).unwrap();
+    /// let base = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// for (t, (rands, rands_base)) in randoms.rand_vars().iter().zip(base.rand_vars()).enumerate() {
+    ///     for (x, x_base) in rands.iter().zip(rands_base) {
+    ///         assert!((x - x_base - seasonal.value(t)).abs() < 1e-12);
+    ///     }
+    /// }
+    /// ```
+    pub fn from_scenario_seed_seasonal(scenario: &Scenario, seed: Seed, seasonal: Seasonal) -> Result<Self, ScenarioError> {
+        if seasonal.period.is_nan() || seasonal.period <= 0.0 {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Seasonal period must be positive, but period = {}.", seasonal.period)
+            ));
+        }
+        let mut random_variables = Self::gen_random(&scenario, seed)?;
+        for (t, rands) in random_variables.iter_mut().enumerate() {
+            let s_t = seasonal.value(t);
+            rands.iter_mut().for_each(|x| *x += s_t);
+        }
+        Ok(Self::new(Arc::new(scenario.clone()), seed, random_variables))
+    }
+
+    /// Seedを指定して2成分の混合正規分布に従う乱数列をScenarioから生成
+    ///
+    /// 二峰性の工程や，異なる状態が混在する工程を想定する．
+    /// シナリオの変化点で区切った各区間に対して，`segments`で2つの成分と混合比を与える．
+    /// 成分の選択には一様乱数を，各成分の乱数生成にはBox-Muller法を用い，いずれも同じ乱数生成器から生成する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ．区間（変化点）とサンプル・サイズのみを用いる．
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `segments` - 各区間の混合分布のパラメータ．区間の数と同じ長さとする．
+    ///
+    /// # 注意
+    /// シナリオの平均・分散は乱数生成には用いません．
+    /// 一方で，[`control_limits`]等の管理限界はシナリオの管理状態のパラメータから計算するため，
+    /// 最初の区間の[`MixtureSegment::effective_param`]をシナリオの管理状態の平均・分散としておくと，
+    /// 混合分布の平均・分散に基づく管理限界となります．
+    /// ただし，混合分布は正規分布ではないため，管理限界を外れる確率は正規分布の場合と一致しません．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::{Scenario, Parameter};
+    /// # use rand_scenario::norm::{RandomScenario, Seed, MixtureSegment};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// // 変化点ごとに2番目の成分の混合比が増える
+    /// let segments: Vec<MixtureSegment> = [0.0, 0.2, 0.5].iter()
+    ///     .map(|w| MixtureSegment{
+    ///         first: Parameter::new(0.0, 1.0).unwrap(),
+    ///         second: Parameter::new(5.0, 1.0).unwrap(),
+    ///         weight: *w,
+    ///     })
+    ///     .collect();
+    /// let randoms = RandomScenario::from_scenario_seed_mixture(&scenario, Seed(42), &segments).unwrap();
+    /// assert_eq!(randoms.rand_vars().len(), 40);
+    /// assert_eq!(randoms, RandomScenario::from_scenario_seed_mixture(&scenario, Seed(42), &segments).unwrap());
+    /// // 区間の数と一致しない場合はエラー
+    /// assert!(RandomScenario::from_scenario_seed_mixture(&scenario, Seed(42), &segments[..2]).is_err());
+    /// ```
+    pub fn from_scenario_seed_mixture(scenario: &Scenario, seed: Seed, segments: &[MixtureSegment]) -> Result<Self, ScenarioError> {
+        let change_points = change_points(scenario)?;
+        if segments.len() != change_points.len() + 1 {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Number of mixture segments must be {}, but {} are given.", change_points.len() + 1, segments.len())
+            ));
+        }
+        if let Some(seg) = segments.iter().find(|seg| !(0.0..=1.0).contains(&seg.weight)) {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Mixture weight must be in [0, 1], but weight = {}.", seg.weight)
+            ));
+        }
+        let horizon = super::scenario_horizon(scenario)?;
+        let n = scenario.n_as_usize()?;
+        let mut rng = Mt64::new(seed.0);
+        let starts = std::iter::once(0).chain(change_points.iter().cloned());
+        let ends = change_points.iter().cloned().chain(std::iter::once(horizon));
+        let mut random_variables = Vec::with_capacity(horizon);
+        for ((start, end), seg) in starts.zip(ends).zip(segments.iter()) {
+            for _t in start..end {
+                let rands = (0..n).map(|_| {
+                                      // 成分を選択してから，その成分の乱数を生成
+                                      let component = if super::rand_uniform(&mut rng) < seg.weight { &seg.second } else { &seg.first };
+                                      rand_norm_n(component, &mut rng, 1)[0]
+                                  })
+                                  .collect();
+                random_variables.push(rands);
+            }
+        }
+        Ok(Self::new(Arc::new(scenario.clone()), seed, random_variables))
+    }
+
+    /// Seedを指定して外れ値を混入させた乱数列をScenarioから生成
+    ///
+    /// [`RandomScenario::from_scenario_seed`]と同様に乱数列を生成した後，
+    /// 各観測値を確率`frac`で外れ値とし，その時点の真の標準偏差$ \sigma_t $の`shift`倍を加える．
+    /// 外れ値とするかの判定には乱数列の生成に続けて同じ乱数生成器で生成した一様乱数を用いるため，
+    /// 外れ値の位置はseed値から再現できる．
+    /// 外れ値とした位置は[`RandomScenario::contaminated`]で取得できる．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `frac` - 外れ値とする確率（0以上1以下）
+    /// * `shift` - 外れ値に加えるずれの大きさ（標準偏差の倍数）
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_contaminated(&scenario, Seed(42), 0.05, 5.0).unwrap();
+    /// let again = RandomScenario::from_scenario_seed_contaminated(&scenario, Seed(42), 0.05, 5.0).unwrap();
+    /// assert_eq!(randoms.contaminated(), again.contaminated());
+    /// // 外れ値を含まない場合は通常の乱数列と一致
+    /// let clean = RandomScenario::from_scenario_seed_contaminated(&scenario, Seed(42), 0.0, 5.0).unwrap();
+    /// assert!(clean.contaminated().is_empty());
+    /// assert_eq!(clean.rand_vars(), RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap().rand_vars());
+    /// ```
+    pub fn from_scenario_seed_contaminated(scenario: &Scenario, seed: Seed, frac: f64, shift: f64) -> Result<Self, ScenarioError> {
+        if !(0.0..=1.0).contains(&frac) {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Contamination fraction must be in [0, 1], but frac = {frac}.")
+            ));
+        }
+        let mut rng = Mt64::new(seed.0);
+        let mut random_variables = Self::gen_random_with_rng(&scenario, &mut rng)?;
+        let params = scenario.decomplession()?;
+        let mut contaminated = Vec::new();
+        for (t, (rands, parameter)) in random_variables.iter_mut().zip(params.iter()).enumerate() {
+            let sigma = param_tuple(parameter).1.sqrt();
+            for (i, x) in rands.iter_mut().enumerate() {
+                if super::rand_uniform(&mut rng) < frac {
+                    *x += shift * sigma;
+                    contaminated.push((t, i));
+                }
+            }
+        }
+        Ok(RandomScenario{ contaminated, ..Self::new(Arc::new(scenario.clone()), seed, random_variables) })
+    }
+
+    /// Seedを指定して欠測値を含む乱数列をScenarioから生成
+    ///
+    /// [`RandomScenario::from_scenario_seed`]と同様に乱数列を生成した後，
+    /// 各観測値を確率`miss_prob`で欠測値とし，`f64::NAN`に置き換える．
+    /// 欠測とするかの判定には乱数列の生成に続けて同じ乱数生成器で生成した一様乱数を用いるため，
+    /// 欠測の位置はseed値から再現できる．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `miss_prob` - 欠測値とする確率（0以上1以下）
+    ///
+    /// # 注意
+    /// CSVとして出力すると，欠測値は空欄となります．
+    /// 各時点の標本平均や標本標準偏差は，[`RandomData::sample_means`](crate::data::RandomData::sample_means)等により欠測値を除外して計算できます．
+    /// 管理図の監視統計量も同様に欠測値を除外し，欠測していない個数をサンプル・サイズとして計算します．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// # use rand_scenario::data::RandomData;
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_missing(&scenario, Seed(42), 0.1).unwrap();
+    /// let again = RandomScenario::from_scenario_seed_missing(&scenario, Seed(42), 0.1).unwrap();
+    /// let missing: Vec<bool> = randoms.flatten().iter().map(|x| x.is_nan()).collect();
+    /// assert_eq!(missing, again.flatten().iter().map(|x| x.is_nan()).collect::<Vec<bool>>());
+    /// // 欠測値を除いた標本平均
+    /// let means = RandomData::from(&randoms).sample_means();
+    /// assert_eq!(means.len(), 40);
+    /// assert!(means.iter().all(|m| !m.is_nan()));
+    /// randoms.to_csv(&std::path::Path::new("test/randoms_from_test_scenario_missing.csv")).unwrap();
+    /// ```
+    pub fn from_scenario_seed_missing(scenario: &Scenario, seed: Seed, miss_prob: f64) -> Result<Self, ScenarioError> {
+        if !(0.0..=1.0).contains(&miss_prob) {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Missing probability must be in [0, 1], but miss_prob = {miss_prob}.")
+            ));
+        }
+        let mut rng = Mt64::new(seed.0);
+        let mut random_variables = Self::gen_random_with_rng(&scenario, &mut rng)?;
+        for x in random_variables.iter_mut().flatten() {
+            if super::rand_uniform(&mut rng) < miss_prob {
+                *x = f64::NAN;
+            }
+        }
+        Ok(Self::new(Arc::new(scenario.clone()), seed, random_variables))
+    }
+
+    /// Seedを指定して測定分解能で丸めた乱数列をScenarioから生成
+    ///
+    /// [`RandomScenario::from_scenario_seed`]で生成した各観測値を，`resolution`の最も近い倍数に丸める．
+    /// 丸めは乱数生成の後に行うため，連続値の乱数列はseed値が同じであれば[`RandomScenario::from_scenario_seed`]と同じである．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `resolution` - 測定分解能（正の値）
+    ///
+    /// # 注意
+    /// 分解能$ h $による丸めは観測値に分散およそ$ h^2 / 12 $の誤差を加えます（Sheppardの補正）．
+    /// `resolution`が標準偏差$ \sigma $に対して大きい場合，各時点の標本標準偏差は偏り，
+    /// 同じ値ばかりとなる時点では0となることがあるため，s管理図の判定に影響します．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_quantized(&scenario, Seed(42), 0.1).unwrap();
+    /// let original = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// for (x, y) in randoms.flatten().iter().zip(original.flatten()) {
+    ///     assert!((x - y).abs() <= 0.05 + 1e-12);
+    ///     assert!(((x / 0.1).round() * 0.1 - x).abs() < 1e-9);
+    /// }
+    /// assert!(RandomScenario::from_scenario_seed_quantized(&scenario, Seed(42), 0.0).is_err());
+    /// ```
+    pub fn from_scenario_seed_quantized(scenario: &Scenario, seed: Seed, resolution: f64) -> Result<Self, ScenarioError> {
+        if resolution.is_nan() || resolution <= 0.0 {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Resolution must be positive, but resolution = {resolution}.")
+            ));
+        }
+        let mut random_variables = Self::gen_random(&scenario, seed)?;
+        for x in random_variables.iter_mut().flatten() {
+            *x = (*x / resolution).round() * resolution;
+        }
+        Ok(Self::new(Arc::new(scenario.clone()), seed, random_variables))
+    }
+
+    /// 区間ごとにサンプル・サイズを指定してScenarioから乱数列を生成
+    ///
+    /// 変化点で区切られた区間ごとに，シナリオのサンプル・サイズ$ n $の代わりに`ns`の値を用いる．
+    /// そのため，乱数列は時点ごとに長さの異なる行からなる．
+    /// 各時点のサンプル・サイズは[`RandomScenario::sample_sizes`]で取得できる．
+    /// CSVやTOMLとして出力すると，各行の項目数は時点ごとのサンプル・サイズとなる．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `ns` - 各区間のサンプル・サイズ．区間の個数（変化点の個数+1）と同じ長さであること．
+    ///
+    /// # 注意
+    /// 管理限界はサンプル・サイズに依存するため，区間$ k $では$ n_k $を用いて計算し直す必要があります．
+    /// $ \bar{X} $管理図では$ \mu_0 \pm 3 \sigma_0 / \sqrt{n_k} $，
+    /// s管理図では中心線$ c_4(n_k) \sigma_0 $，管理限界$ c_4(n_k) \sigma_0 \pm 3 \sigma_0 \sqrt{1 - c_4(n_k)^2} $となります．
+    /// [`control_limits`]はシナリオの$ n $に対する管理限界のみを返します．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_variable_n(&scenario, Seed(42), &[5, 8, 3]).unwrap();
+    /// let sizes = randoms.sample_sizes();
+    /// assert_eq!((sizes[0], sizes[15], sizes[39]), (5, 8, 3));
+    /// randoms.to_csv(&std::path::Path::new("test/randoms_from_test_scenario_variable_n.csv")).unwrap();
+    /// // 区間の個数と一致しない場合はエラー
+    /// assert!(RandomScenario::from_scenario_variable_n(&scenario, Seed(42), &[5, 8]).is_err());
+    /// ```
+    pub fn from_scenario_variable_n(scenario: &Scenario, seed: Seed, ns: &[usize]) -> Result<Self, ScenarioError> {
+        let cps = change_points(scenario)?;
+        if ns.len() != cps.len() + 1 {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Number of sample sizes must be {}, but {} are given.", cps.len() + 1, ns.len())
+            ));
+        }
+        let mut rng = Mt64::new(seed.0);
+        let random_variables = scenario.decomplession()?
+                                       .iter()
+                                       .enumerate()
+                                       .map(|(t, parameter)| {
+                                           let segment = cps.iter().filter(|cp| **cp <= t).count();
+                                           rand_norm_n(parameter, &mut rng, ns[segment])
+                                       })
+                                       .collect();
+        Ok(Self::new(Arc::new(scenario.clone()), seed, random_variables))
+    }
+
+    /// Sobol列による準乱数を用いてScenarioから乱数列を生成
+    ///
+    /// Box-Muller法に用いる一様乱数として，Mersenne-Twister法の代わりに低食い違い量列であるSobol列を用いる．
+    /// Box-Muller法が用いる2個1組の一様乱数には，Sobol列の第$ 2d + 1 $次元と第$ 2d + 2 $次元
+    /// （$ d $は`dimension_offset`）の点を先頭から順に用いる（ただし原点である最初の点は除く）．
+    /// 感度分析等で，擬似乱数より速い収束が必要な場合を想定している．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `dimension_offset` - 利用するSobol列の次元の組の番号
+    ///
+    /// # 注意
+    /// Sobol列は決定的な列のため，seed値はありません．
+    /// 同じ`dimension_offset`からは常に同じ乱数列が得られ，異なる乱数列が必要な場合は`dimension_offset`を変えてください．
+    /// 記録されるseed値（[`RandomScenario::get_seed`]）は`dimension_offset`となります．
+    /// また，`sobol` featureを有効にする必要があります．
+    ///
+    /// # 使用例
+    /// ```
+    /// # #[cfg(feature = "sobol")] {
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_sobol(&scenario, 0).unwrap();
+    /// assert_eq!(randoms, RandomScenario::from_scenario_sobol(&scenario, 0).unwrap());
+    /// assert_ne!(randoms, RandomScenario::from_scenario_sobol(&scenario, 1).unwrap());
+    /// # }
+    /// ```
+    #[cfg(feature = "sobol")]
+    pub fn from_scenario_sobol(scenario: &Scenario, dimension_offset: usize) -> Result<Self, process_param::ScenarioError> {
+        let random_variables = Self::gen_random_with_rng(&scenario, &mut SobolRng::new(dimension_offset))?;
+        Ok(Self::new(Arc::new(scenario.clone()), Seed(dimension_offset as u64), random_variables))
+    }
+
+    // 乱数生成コア
+    fn gen_random(scenario: &Scenario, seed: Seed) -> Result<Vec<Vec<<Parameter as Process>::Observation>>, process_param::ScenarioError> {
+        Self::gen_random_with_rng(scenario, &mut Mt64::new(seed.0))
+    }
+
+    /// 呼び出し元の乱数生成器を進めてScenarioから乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_seed`]のように乱数生成器をseed値から初期化し直さず，与えた`rng`の状態から乱数を生成する．
+    /// 1つの乱数生成器を使い回すことで，共通乱数法（CRN）による比較や，互いに異なり再現可能な多数の乱数列の生成に利用できる．
+    /// 初期化直後の`Mt64::new(seed)`を与えた場合，乱数列は`from_scenario_seed`で`seed`を指定した場合と一致する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `rng` - 乱数生成器．生成した乱数の分だけ状態が進む．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// extern crate rand_mt;
+    /// use process_param::norm::Scenario;
+    /// use rand_mt::Mt64;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let mut rng = Mt64::new(42);
+    /// let first = RandomScenario::gen_random_with_existing_rng(&scenario, &mut rng).unwrap();
+    /// let second = RandomScenario::gen_random_with_existing_rng(&scenario, &mut rng).unwrap();
+    /// assert_eq!(&first, RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap().rand_vars());
+    /// assert_ne!(first, second);
+    ///
+    /// // 同じseed値で初期化すれば，同じ乱数列の系列を再現できる
+    /// let mut rng_again = Mt64::new(42);
+    /// assert_eq!(first, RandomScenario::gen_random_with_existing_rng(&scenario, &mut rng_again).unwrap());
+    /// assert_eq!(second, RandomScenario::gen_random_with_existing_rng(&scenario, &mut rng_again).unwrap());
+    /// ```
+    pub fn gen_random_with_existing_rng(scenario: &Scenario, rng: &mut Mt64) -> Result<Vec<Vec<<Parameter as Process>::Observation>>, process_param::ScenarioError> {
+        Self::gen_random_with_rng(scenario, rng)
+    }
+
+    // 乱数生成器を指定した乱数生成コア
+    fn gen_random_with_rng<R: RngCore>(scenario: &Scenario, rng: &mut R) -> Result<Vec<Vec<<Parameter as Process>::Observation>>, process_param::ScenarioError> {
+        let dec_param = scenario.decomplession()?;
+        Self::gen_random_params(scenario, &dec_param, rng)
+    }
+
+    // 各時点のパラメータを与えた乱数生成コア
+    fn gen_random_params<R: RngCore>(scenario: &Scenario, dec_param: &[Parameter], rng: &mut R) -> Result<Vec<Vec<<Parameter as Process>::Observation>>, process_param::ScenarioError> {
+        Ok(RowGenerator::new(scenario, dec_param, rng)?.collect())
+    }
+
+    /// Seedを指定して，変化点間で平均が線形に推移する乱数列をScenarioから生成
+    ///
+    /// 展開したパラメータ系列（`Scenario::decomplession`）の平均$ \mu_t $を，
+    /// 各区間の先頭での値から次の区間の先頭での値まで線形に推移するよう補間してから乱数を生成する．
+    /// そのため，平均は変化点でステップ状に変化するのではなく，区間内で徐々に変化する．
+    /// 最後の区間の平均はシナリオのままとなる．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 注意
+    /// 分散$ \sigma_t^2 $は補間せず，シナリオの値のままとなります．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario_small_shift.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_drift(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.rand_vars().len(), 40);
+    /// ```
+    pub fn from_scenario_seed_drift(scenario: &Scenario, seed: Seed) -> Result<Self, process_param::ScenarioError> {
+        let dec_param = drift_params(scenario)?;
+        let random_variables = Self::gen_random_params(scenario, &dec_param, &mut Mt64::new(seed.0))?;
+        Ok(Self::new(Arc::new(scenario.clone()), seed, random_variables))
+    }
+
+    /// Scenarioから複数の乱数列を生成
+    /// 
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// 
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_multiple(&scenario, 4).unwrap();
+    /// println!("{:?}", randoms);
+    /// // 並列（`parallel` feature）・逐次のどちらでも，各乱数列はそのseed値から再現できる
+    /// assert_eq!(randoms.len(), 4);
+    /// for r in randoms.iter() {
+    ///     let rep = RandomScenario::from_scenario_seed(&scenario, r.get_seed()).unwrap();
+    ///     assert_eq!(r.rand_vars(), rep.rand_vars());
+    /// }
+    ///
+    /// // 分散が負のシナリオでは，並列に生成するワーカースレッドで発生したエラーがパニックせずに返される
+    /// let negative_var = Scenario::parse_toml_str(r#"
+    /// n = 5
+    /// parameter = [{tau = 10, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = -1.0}}]
+    /// "#).unwrap();
+    /// assert!(RandomScenario::from_scenario_multiple(&negative_var, 4).is_err());
+    /// ```
+    pub fn from_scenario_multiple(scenario: &Scenario, num: usize) -> Result<Vec<Self>, process_param::ScenarioError> {
+        Self::from_scenario_multiple_with(scenario, num, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_multiple`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    /// 詳しくは[`RandomScenario::from_scenario_with`]を参照．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    pub fn from_scenario_multiple_with(scenario: &Scenario, num: usize, options: &GenOptions) -> Result<Vec<Self>, process_param::ScenarioError> {
+        let mut seeds = Vec::with_capacity(num);
+        let mut rng_for_seed = super::entropy_rng(options.deterministic)?; 
+        for _i in 0..num {
+            seeds.push(Seed(rng_for_seed.next_u64()));
+        }
+        // 全ての乱数列で1つのシナリオを共有する
+        let shared = Arc::new(scenario.clone());
+        seeds.par_iter()
+             .map(|seed| Self::from_shared_scenario_seed(&shared, *seed))
+             .collect()
+    }
+
+    /// 進捗を通知しながらScenarioから複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_multiple`]と同様に乱数列を生成し，
+    /// 乱数列を1つ生成するたびに，それまでに生成を終えた乱数列の個数を引数として`on_progress`を呼び出す．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `on_progress` - 進捗を受け取る関数
+    ///
+    /// # 注意
+    /// 乱数列は並列に生成されるため，`on_progress`は複数のスレッドから同時に呼び出されることがあります．
+    /// 呼び出しの順序は保証されず，引数の値が前後することもあります（ただし，各値`1..=num`はちょうど1回ずつ渡されます）．
+    /// 進捗表示では，受け取った値の最大値を用いてください．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let max_done = AtomicUsize::new(0);
+    /// let randoms = RandomScenario::from_scenario_multiple_with_progress(&scenario, 8, |done| {
+    ///     max_done.fetch_max(done, Ordering::Relaxed);
+    /// }).unwrap();
+    /// assert_eq!(randoms.len(), 8);
+    /// assert_eq!(max_done.into_inner(), 8);
+    /// ```
+    pub fn from_scenario_multiple_with_progress<F>(scenario: &Scenario, num: usize, on_progress: F) -> Result<Vec<Self>, process_param::ScenarioError>
+    where
+        F: Fn(usize) + Sync
+    {
+        Self::from_scenario_multiple_with_progress_with(scenario, num, &GenOptions::default(), on_progress)
+    }
+
+    /// 設定を指定して，進捗を通知しながらScenarioから複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_multiple_with_progress`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    /// * `on_progress` - 進捗を受け取る関数
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::GenOptions;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+    /// assert!(RandomScenario::from_scenario_multiple_with_progress_with(&scenario, 4, &options, |_| {}).is_err());
+    /// ```
+    pub fn from_scenario_multiple_with_progress_with<F>(scenario: &Scenario, num: usize, options: &GenOptions, on_progress: F) -> Result<Vec<Self>, process_param::ScenarioError>
+    where
+        F: Fn(usize) + Sync
+    {
+        let seeds = super::entropy_seeds(num, options.deterministic)?;
+        let shared = Arc::new(scenario.clone());
+        let done = AtomicUsize::new(0);
+        seeds.par_iter()
+             .map(|seed| {
+                 let randoms = Self::from_shared_scenario_seed(&shared, *seed)?;
+                 on_progress(done.fetch_add(1, Ordering::Relaxed) + 1);
+                 Ok(randoms)
+             })
+             .collect()
+    }
+
+    /// 共通乱数法により複数のScenarioから乱数列を生成
+    ///
+    /// 各シナリオについて同じseed値で初期化したMersenne-Twisterを用いて乱数列を生成する．
+    /// すべてのシナリオで同じ一様乱数の系列を共有するため，乱数列の差異はシナリオの差異のみを反映する．
+    /// シミュレーションによるシナリオ間の比較における分散減少法として利用できる．
+    ///
+    /// # 引数
+    /// * `scenarios` - 乱数生成に用いるシナリオ
+    /// * `seed` - 全シナリオで共通して用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenarios_crn(&[scenario.clone(), scenario], Seed(42)).unwrap();
+    /// assert_eq!(randoms.len(), 2);
+    /// assert_eq!(randoms[0].rand_vars(), randoms[1].rand_vars());
+    /// ```
+    pub fn from_scenarios_crn(scenarios: &[Scenario], seed: Seed) -> Result<Vec<Self>, ScenarioError> {
+        scenarios.par_iter()
+                 .map(|scenario| Ok(Self::from_scenario_seed(scenario, seed)?))
+                 .collect()
+    }
+
+
+    /// TOMLファイルからRandomScenarioを作成
+    /// 
+    /// RandomScenario::to_tomlにより生成されたTOMLファイルを読み込む．
+    /// 
+    /// # 引数
+    /// * `path` - 読み込むTOMLファイルのパス
+    /// 
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_toml = std::path::Path::new("test/randoms_from_test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// // TOMLファイルに保存
+    /// randoms.to_toml(&path_toml).unwrap();
+    /// // TOMLファイルから読み出し
+    /// let rs_read = RandomScenario::from_toml(&path_toml).unwrap();
+    /// assert_eq!(rs_read, randoms);
+    /// ```
+    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+
+    /// Scenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// 管理図には$ \bar{X} $管理図とs管理図の併用を想定．
+    /// 最初の変化点以前で管理外れ状態を検出した場合には乱数列を再生成する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// 
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_controlchart(&scenario);
+    /// println!("{:?}", randoms);
+    /// ```
+    pub fn from_scenario_controlchart(scenario: &Scenario) -> Result<Self, process_param::ScenarioError> {
+        Self::from_scenario_controlchart_with(scenario, &GenOptions::default())
+    }
+
+
+    /// 設定を指定して，Scenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// [`RandomScenario::from_scenario_controlchart`]と同様だが，`options.deterministic`が`true`の場合は
+    /// 暗黙のエントロピーでseed値を生成せずにエラーを返す．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// use std::time::Duration;
+    /// # use rand_scenario::GenOptions;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+    /// assert!(RandomScenario::from_scenario_controlchart_with(&scenario, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_controlchart_multiple_with(&scenario, 4, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_controlchart_multiple_sigma_with(&scenario, 4, 2.5, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_controlchart_multiple_timeout_with(&scenario, 4, Some(Duration::from_secs(60)), &options).is_err());
+    /// assert!(RandomScenario::from_scenario_controlchart_with(&scenario, &GenOptions::default()).is_ok());
+    /// ```
+    pub fn from_scenario_controlchart_with(scenario: &Scenario, options: &GenOptions) -> Result<Self, process_param::ScenarioError> {
+        let seed = Seed(super::entropy_rng(options.deterministic)?.next_u64());
+        Self::from_scenario_seed_controlchart(scenario, seed)
+    }
+
+
+    /// Seedを指定してScenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// 管理図には$ \bar{X} $管理図とs管理図の併用を想定．
+    /// 最初の変化点以前で管理外れ状態を検出した場合には乱数列を再生成する．
+    ///
+    /// サンプル・サイズ$ n = 1 $の場合はsを計算できないため，自動的に3シグマのX-MR管理図（個々の値と移動範囲の管理図）を用いる．
+    /// X管理図の管理限界は$ \mu_0 \pm 3 \sigma $，MR管理図の上方管理限界は$ D_4 \overline{MR} $（$ D_4 = 3.267 $）である．
+    /// 実際の工程では$ \sigma $を平均移動範囲から$ \hat{\sigma} = \overline{MR} / d_2 $（$ d_2 = 1.128 $）と推定するが，
+    /// ここではシナリオの管理状態の$ \sigma_0 $を用いて$ \overline{MR} = d_2 \sigma_0 $とする．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 注意
+    /// $ n \geq 2 $の場合の管理外れ状態の判定は，各時点の最尤推定値に対する`Scenario::out_of_control`（シナリオの3シグマの規則）であり，
+    /// 管理限界の幅を指定する機能を追加する前と同じseed値から同じ乱数列を生成する．
+    /// 管理限界の幅（`sigma_mult`）はこの関数には適用されない．
+    /// 幅を指定する場合は[`RandomScenario::from_scenario_seed_controlchart_sigma`]を用いる．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap();
+    /// println!("{:?}", randoms);
+    ///
+    /// // サンプル・サイズ1の場合はX-MR管理図を用いる
+    /// let individuals = Scenario::parse_toml_str(r#"
+    /// n = 1
+    /// parameter = [
+    ///     {tau = 20, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}},
+    ///     {tau = 30, mu = {type = "Step", level = 3.0}, sigma2 = {type = "Step", level = 1.0}}
+    /// ]
+    /// "#).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart(&individuals, Seed(42)).unwrap();
+    /// assert!(randoms.rand_vars().iter().all(|rands| rands.len() == 1));
+    /// assert!(randoms.num_timepoints() > 20);
+    /// ```
+    pub fn from_scenario_seed_controlchart(scenario: &Scenario, seed: Seed) -> Result<Self, process_param::ScenarioError> {
+        Self::from_shared_scenario_seed_controlchart(&Arc::new(scenario.clone()), seed)
+    }
+
+    // 共有するシナリオから管理図が管理外れ状態を検出するまで乱数を生成
+    fn from_shared_scenario_seed_controlchart(scenario: &Arc<Scenario>, seed: Seed) -> Result<Self, process_param::ScenarioError> {
+        let random_variables = Self::gen_random_controlchart(&scenario, seed)?;
+        let signal = Some(Signal{ index: random_variables.len() - 1, rule: SignalRule::Limits });
+        Ok(RandomScenario{ signal, ..Self::new(Arc::clone(scenario), seed, random_variables) })
+    }
+ 
+ 
+    /// 乱数生成器の型を指定してScenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// 乱数生成器の初期化については[`RandomScenario::from_scenario_seed_with_rng`]を参照．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// use rand::rngs::StdRng;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart_with_rng::<StdRng>(&scenario, Seed(42)).unwrap();
+    /// println!("{:?}", randoms);
+    /// ```
+    pub fn from_scenario_seed_controlchart_with_rng<R: RngCore + SeedableRng>(scenario: &Scenario, seed: Seed) -> Result<Self, process_param::ScenarioError> {
+        let random_variables = Self::gen_random_controlchart_with_rng(&scenario, &mut R::seed_from_u64(seed.0), None, None)?;
+        let signal = Some(Signal{ index: random_variables.len() - 1, rule: SignalRule::Limits });
+        Ok(RandomScenario{ signal, ..Self::new(Arc::new(scenario.clone()), seed, random_variables) })
+    }
+
+
+    /// 時点数の上限を指定して，Seedを指定したScenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// 最後の変化点以降のパラメータの変化が小さい場合，管理外れ状態を検出するまでの時点数（連長）は非常に長くなり得る．
+    /// 大規模なARLの評価等で乱数列が際限なく長くなることを避けるために用いる．
+    /// 乱数列は[`RandomScenario::from_scenario_seed_controlchart`]と同じであり，上限に達した場合は
+    /// 種類が[`ScenarioErrorKind::RetryExhausted`](crate::ScenarioErrorKind::RetryExhausted)のエラーを返す．
+    /// 上限は最後の変化点以降の乱数生成にのみ適用する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `max_len` - 乱数列の時点数の上限
+    ///
+    /// # 使用例
+    /// 変化点が1つのシナリオでは，管理状態の区間で検出した乱数列は再生成するため，検出は必ず変化点（t = 20）以降となる．
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario_small_shift.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap();
+    /// let len = randoms.rand_vars().len();
+    /// assert!(len > 20);
+    /// let capped = RandomScenario::from_scenario_seed_controlchart_capped(&scenario, Seed(42), len).unwrap();
+    /// assert_eq!(capped.rand_vars(), randoms.rand_vars());
+    /// // 1時点短い上限ではエラーとなる
+    /// let err = RandomScenario::from_scenario_seed_controlchart_capped(&scenario, Seed(42), len - 1).unwrap_err();
+    /// assert_eq!(err.kind, rand_scenario::ScenarioErrorKind::RetryExhausted);
+    ///
+    /// // サンプル・サイズ1（X-MR管理図）でも上限で打ち切る
+    /// let individual = Scenario::parse_toml_str(r#"
+    /// n = 1
+    /// parameter = [
+    ///     {tau = 20, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}},
+    ///     {tau = 40, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}},
+    /// ]
+    /// "#).unwrap();
+    /// let err = RandomScenario::from_scenario_seed_controlchart_capped(&individual, Seed(42), 20).unwrap_err();
+    /// assert_eq!(err.kind, rand_scenario::ScenarioErrorKind::RetryExhausted);
+    /// ```
+    pub fn from_scenario_seed_controlchart_capped(scenario: &Scenario, seed: Seed, max_len: usize) -> Result<Self, ScenarioError> {
+        let random_variables = Self::gen_random_controlchart_with_rng(scenario, &mut Mt64::new(seed.0), Some(max_len), None)?;
+        let signal = Some(Signal{ index: random_variables.len() - 1, rule: SignalRule::Limits });
+        Ok(RandomScenario{ signal, ..Self::new(Arc::new(scenario.clone()), seed, random_variables) })
+    }
+
+
+    /// 時点数を固定して，Seedを指定したScenarioから管理図を併用した乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_seed_controlchart`]と同様に管理図で管理外れ状態を判定するが，
+    /// 検出した時点で打ち切らず，常にちょうど`horizon`時点の乱数列を返す．
+    /// 検出した時点は[`RandomScenario::signal`]に記録する（`horizon`時点までに検出しなかった場合は`None`）．
+    /// 検出した時点以降も，シナリオのパラメータ（最後の変化点以降は最後の変化点のパラメータ）に従って乱数を生成する．
+    /// 全ての乱数列の時点数が揃うため，複数の乱数列を1つの配列として扱える．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `horizon` - 乱数列の時点数
+    ///
+    /// # 注意
+    /// 検出した時点までの乱数列は，同じseed値で`from_scenario_seed_controlchart`により生成した乱数列と一致します．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let stopped = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap();
+    /// // 検出した時点より長い時点数を指定する
+    /// let horizon = stopped.num_timepoints() + 20;
+    /// let fixed = RandomScenario::from_scenario_seed_controlchart_fixed(&scenario, Seed(42), horizon).unwrap();
+    /// assert_eq!(fixed.num_timepoints(), horizon);
+    /// assert_eq!(fixed.signal(), stopped.signal());
+    /// assert_eq!(&fixed.rand_vars()[..stopped.num_timepoints()], &stopped.rand_vars()[..]);
+    ///
+    /// // 最初の変化点（t = 15）より前では検出しない
+    /// let short = RandomScenario::from_scenario_seed_controlchart_fixed(&scenario, Seed(42), 10).unwrap();
+    /// assert_eq!(short.num_timepoints(), 10);
+    /// assert_eq!(short.signal(), None);
+    /// ```
+    pub fn from_scenario_seed_controlchart_fixed(scenario: &Scenario, seed: Seed, horizon: usize) -> Result<Self, ScenarioError> {
+        let mut rng = Mt64::new(seed.0);
+        let (mut random_variables, rule) = Self::gen_random_controlchart_until(scenario, &mut rng, Some(horizon), None)?;
+        let mut signal = rule.map(|rule| Signal{ index: random_variables.len() - 1, rule });
+        if random_variables.len() > horizon {
+            // 最後の変化点より前の，horizon以降の時点で検出した場合
+            random_variables.truncate(horizon);
+            signal = None;
+        }
+
+        // 検出した時点以降もシナリオのパラメータに従って乱数を生成
+        let (inctrl_param, dec_param, last_cp) = scenario.decomp_exclude_last()?;
+        let n = scenario.n_as_usize()?;
+        let known_len = inctrl_param.len() + dec_param.len();
+        let start = random_variables.len();
+        for parameter in inctrl_param.iter().chain(dec_param.iter()).take(horizon).skip(start) {
+            random_variables.push(rand_norm_n(parameter, &mut rng, n));
+        }
+        // 最後の変化点以降（生成時と同様に，最後の変化点からの時点数でパラメータを求める）
+        for ind_outctrl in (1..).take(horizon.saturating_sub(known_len)).skip(start.saturating_sub(known_len)) {
+            let param_ind = match last_cp.get_param(ind_outctrl) {
+                Ok(p) => p,
+                Err(e) => return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Parameters are out of range: {e}")).with_source(e)),
+            };
+            random_variables.push(rand_norm_n(&param_ind, &mut rng, n));
+        }
+        Ok(RandomScenario{ signal, ..Self::new(Arc::new(scenario.clone()), seed, random_variables) })
+    }
+
+
+    // 管理図が管理外れ状態を検出するまで乱数を生成
+    fn gen_random_controlchart(scenario: &Scenario, seed: Seed) -> Result<Vec<Vec<<Parameter as Process>::Observation>>, ScenarioError> {
+        Self::gen_random_controlchart_with_rng(scenario, &mut Mt64::new(seed.0), None, None)
+    }
+
+
+    // 乱数生成器を指定して，管理図が管理外れ状態を検出するまで乱数を生成
+    // 最後の変化点前までの長さは既知なので予め確保する
+    // `max_len`を指定した場合，その時点数までに管理外れ状態を検出しなければエラーとする
+    // `deadline`を指定した場合，その時刻を過ぎても管理外れ状態を検出しなければエラーとする
+    fn gen_random_controlchart_with_rng<R: RngCore>(scenario: &Scenario, rng: &mut R, max_len: Option<usize>, deadline: Option<Instant>) -> Result<Vec<Vec<<Parameter as Process>::Observation>>, ScenarioError> {
+        match Self::gen_random_controlchart_until(scenario, rng, max_len, deadline)? {
+            (randoms, Some(_)) => Ok(randoms),
+            (randoms, None) => Err(ScenarioError::new(
+                ScenarioErrorKind::RetryExhausted,
+                format!("Control chart does not alart within {} timepoints.", randoms.len())
+            )),
+        }
+    }
+
+    // 管理図が管理外れ状態を検出するか，時点数が`max_len`に達するまで乱数を生成
+    // 生成した乱数列と，管理外れ状態を検出した規則（検出しなかった場合は`None`）の組を返す
+    fn gen_random_controlchart_until<R: RngCore>(scenario: &Scenario, rng: &mut R, max_len: Option<usize>, deadline: Option<Instant>) -> Result<(Vec<Vec<<Parameter as Process>::Observation>>, Option<SignalRule>), ScenarioError> {
+        let (inctrl_param ,dec_param, last_cp) = scenario.decomp_exclude_last()?;
+        let n = scenario.n_as_usize()?;
+        if n == 1 {
+            // サンプル・サイズ1ではsを計算できないため，X-MR管理図を用いる
+            let (randoms, signal) = Self::gen_random_chart(scenario, rng, &mut Individuals::new(scenario), true, max_len, deadline)?;
+            return Ok((randoms, signal.map(|signal| signal.rule)))
+        }
+        let known_len = inctrl_param.len() + dec_param.len();
+        let mut randoms: Vec<Vec<<Parameter as Process>::Observation>>;
+ 
+        // 管理状態の乱数列
+        loop {
+            check_deadline(deadline)?;
+            randoms = Vec::with_capacity(known_len + CONTROLCHART_TAIL_CAPACITY);
+            randoms.extend(inctrl_param.iter()
+                                       .map(|parameter| rand_norm_n(parameter, &mut *rng, n)));
+            let params_dec_inctrl = match <Parameter as process_param::Mle>::mle_all(&randoms) {
+                Err(e) => return Err(ScenarioError::new(ScenarioErrorKind::RngFailure, format!("Random number generation fails: {e}")).with_source(e)),
+                Ok(pd) => pd,
+            };
+            if scenario.in_control_all(&params_dec_inctrl) {
+                // 管理状態ならば現在のrandomsを利用
+                break;
+            }
+        }
+
+        // 最後の変化点前までの乱数生成
+        let mut randoms_dec = dec_param.iter()
+                                       .map(|parameter| rand_norm_n(parameter, &mut *rng, n))
+                                       .collect::<Vec<Vec<<Parameter as Process>::Observation>>>();
+        let params_dec = match <Parameter as process_param::Mle>::mle_all(&randoms_dec) {
+            Err(e) => return Err(ScenarioError::new(ScenarioErrorKind::RngFailure, format!("Random number generation fails: {e}")).with_source(e)),
+            Ok(pd) => pd,
+        };
+        match scenario.index_out_of_control(&params_dec) {
+            None => randoms.append(&mut randoms_dec),
+            Some(i) =>  {
+                    // 管理外れ状態を検出した時点までの乱数を返す
+                    randoms.append(&mut randoms_dec[..=i].to_vec());
+                    return Ok((randoms, Some(SignalRule::Limits)))
+                },
+        };
+
+        // 最後の変化点の情報に基づいて，管理外れ状態を検出するまで乱数を生成
+        let mut ind_outctrl = 0;
+        loop {
+            ind_outctrl = ind_outctrl + 1;
+            let param_ind = match last_cp.get_param(ind_outctrl) {
+                Ok(p) => p,
+                Err(e) => return Err(ScenarioError::new(
+                    ScenarioErrorKind::OutOfRange,
+                    format!("Parameters are out of range before control chart alart.: {e}")
+                ).with_source(e)),
+            };
+            if let Some(max_len) = max_len {
+                if randoms.len() >= max_len {
+                    return Ok((randoms, None));
+                }
+            }
+            check_deadline(deadline)?;
+            let rand_ind = rand_norm_n(&param_ind, &mut *rng, n);
+            let mle_ind = match <Parameter as process_param::Mle>::mle(&rand_ind) {
+                Err(e) => return Err(ScenarioError::new(ScenarioErrorKind::RngFailure, format!("Random number generation fails: {e}")).with_source(e)),
+                Ok(pd) => pd,
+            };
+            randoms.push(rand_ind);
+            if scenario.out_of_control(&mle_ind) {
+                // 管理外れ状態
+                break;
+            }
+        }
+        
+        Ok((randoms, Some(SignalRule::Limits)))
+    }
+
+
+    /// 管理限界の幅を指定して，Seedを指定したScenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// 管理図には$ \bar{X} $管理図とs管理図の併用を想定し，管理限界は[`control_limits`]で計算する．
+    /// サンプル平均$ \bar{X}_t $または不偏標準偏差$ s_t $が管理限界を外れた時点で乱数生成を終了する．
+    /// 最初の変化点以前で管理外れ状態を検出した場合には乱数列を再生成する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `sigma_mult` - 管理限界の幅（シグマの倍数）．通常の管理図では3.0．
+    ///
+    /// # 注意
+    /// s管理図には不偏標準偏差$ s_t $を用いるため，`sigma_mult = 3.0`であっても
+    /// 最尤推定値を`Scenario::out_of_control`で判定する[`RandomScenario::from_scenario_seed_controlchart`]とは停止の規則が一致しない．
+    /// そのため，同じseed値でも生成する乱数列の長さは異なりうる．
+    /// サンプル・サイズ$ n = 1 $の場合はsを計算できないため，X-MR管理図を用いる．
+    /// このとき`sigma_mult`は個々の値の管理限界に適用し，移動範囲の管理限界は3シグマのままとする．
+    ///
+    /// # 使用例
+    /// 管理限界を狭くすると，管理外れ状態を検出するまでの時点数は短くなる．
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario_small_shift.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let (mut rl_narrow, mut rl_wide) = (0, 0);
+    /// for seed in 0..200 {
+    ///     rl_narrow += RandomScenario::from_scenario_seed_controlchart_sigma(&scenario, Seed(seed), 2.5).unwrap().rand_vars().len();
+    ///     rl_wide += RandomScenario::from_scenario_seed_controlchart_sigma(&scenario, Seed(seed), 3.5).unwrap().rand_vars().len();
+    /// }
+    /// assert!(rl_narrow < rl_wide);
+    /// ```
+    pub fn from_scenario_seed_controlchart_sigma(scenario: &Scenario, seed: Seed, sigma_mult: f64) -> Result<Self, process_param::ScenarioError> {
+        Self::from_shared_scenario_seed_controlchart_sigma(&Arc::new(scenario.clone()), seed, sigma_mult)
+    }
+
+    // 共有するシナリオから，管理限界の幅を指定して管理図が管理外れ状態を検出するまで乱数を生成
+    fn from_shared_scenario_seed_controlchart_sigma(scenario: &Arc<Scenario>, seed: Seed, sigma_mult: f64) -> Result<Self, process_param::ScenarioError> {
+        let mut chart = limits_chart(scenario, sigma_mult)?;
+        let (random_variables, signal) = Self::gen_random_chart(&scenario, &mut Mt64::new(seed.0), &mut *chart, true, None, None)?;
+        Ok(RandomScenario{ signal, ..Self::new(Arc::clone(scenario), seed, random_variables) })
+    }
+
+
+    /// 誤警報を残したまま，Seedを指定したScenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// [`RandomScenario::from_scenario_seed_controlchart_sigma`]で`sigma_mult = 3.0`とした場合と同じ3シグマの$ \bar{X} - s $管理図を用いるが，
+    /// 最初の変化点以前で管理外れ状態を検出しても乱数列を再生成せず，その時点で乱数生成を終了する．
+    /// シグナルの時点は変化点前の誤警報であっても記録する．
+    /// 再生成による偏りがないため，管理状態のARLや誤警報率の評価に用いる．
+    /// 真の変化の検出を条件とした評価には，再生成を行う[`RandomScenario::from_scenario_seed_controlchart`]等を用いる．
+    /// サンプル・サイズ$ n = 1 $の場合は，$ \bar{X} - s $管理図の代わりに3シグマのX-MR管理図を用いる．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let mut num_false_alarm = 0;
+    /// for seed in 0..200 {
+    ///     let randoms = RandomScenario::from_scenario_seed_controlchart_raw(&scenario, Seed(seed)).unwrap();
+    ///     let signal = randoms.signal().unwrap();
+    ///     assert_eq!(signal.index, randoms.num_timepoints() - 1);
+    ///     if signal.index < 15 {
+    ///         num_false_alarm += 1;
+    ///     }
+    /// }
+    /// // 再生成しないため，最初の変化点前の誤警報が残る
+    /// assert!(num_false_alarm > 0);
+    /// ```
+    pub fn from_scenario_seed_controlchart_raw(scenario: &Scenario, seed: Seed) -> Result<Self, process_param::ScenarioError> {
+        let mut chart = limits_chart(scenario, 3.0)?;
+        let (random_variables, signal) = Self::gen_random_chart(&scenario, &mut Mt64::new(seed.0), &mut *chart, false, None, None)?;
+        Ok(RandomScenario{ signal, ..Self::new(Arc::new(scenario.clone()), seed, random_variables) })
+    }
+
+
+    /// 管理限界の幅を指定して，Scenarioから管理図を併用した場合の複数の乱数列を生成
+    ///
+    /// 各乱数列は[`RandomScenario::from_scenario_seed_controlchart_sigma`]で生成する．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `sigma_mult` - 管理限界の幅（シグマの倍数）
+    pub fn from_scenario_controlchart_multiple_sigma(scenario: &Scenario, num: usize, sigma_mult: f64) -> Result<Vec<Self>, process_param::ScenarioError> {
+        Self::from_scenario_controlchart_multiple_sigma_with(scenario, num, sigma_mult, &GenOptions::default())
+    }
+
+
+    /// 設定と管理限界の幅を指定して，Scenarioから管理図を併用した場合の複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_controlchart_multiple_sigma`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    /// 使用例は[`RandomScenario::from_scenario_controlchart_with`]を参照．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `sigma_mult` - 管理限界の幅（シグマの倍数）
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    pub fn from_scenario_controlchart_multiple_sigma_with(scenario: &Scenario, num: usize, sigma_mult: f64, options: &GenOptions) -> Result<Vec<Self>, process_param::ScenarioError> {
+        let seeds = super::entropy_seeds(num, options.deterministic)?;
+        // 全ての乱数列で1つのシナリオを共有する
+        let shared = Arc::new(scenario.clone());
+        seeds.par_iter()
+             .map(|seed| Self::from_shared_scenario_seed_controlchart_sigma(&shared, *seed, sigma_mult))
+             .collect()
+    }
+
+
+    /// Western Electricルールを適用して，Seedを指定したScenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// 3シグマの$ \bar{X} - s $管理図（[`control_limits`]）に加えて，`rules`で有効にした規則を
+    /// 標準化したサンプル平均$ (\bar{X}_t - \mu_0) / (\sigma_0 / \sqrt{n}) $の系列に適用する．
+    /// いずれかの規則で管理外れ状態を検出した時点で乱数生成を終了し，判定した規則を[`RandomScenario::signal`]に記録する．
+    /// 最初の変化点以前で管理外れ状態を検出した場合には乱数列を再生成する．
+    /// サンプル・サイズ$ n = 1 $の場合，ルール1には$ \bar{X} - s $管理図の代わりに3シグマのX-MR管理図を用いる．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `rules` - 適用するWestern Electricルール
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, RunsRules, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart_rules(&scenario, Seed(42), RunsRules::all()).unwrap();
+    /// let signal = randoms.signal().unwrap();
+    /// assert_eq!(signal.index, randoms.rand_vars().len() - 1);
+    /// println!("{:?}", signal.rule);
+    ///
+    /// // 既定の規則（ルール1のみ）は3シグマの管理図と同じ乱数列となる
+    /// for seed in 0..20 {
+    ///     let rule_1 = RandomScenario::from_scenario_seed_controlchart_rules(&scenario, Seed(seed), RunsRules::default()).unwrap();
+    ///     let shewhart = RandomScenario::from_scenario_seed_controlchart_sigma(&scenario, Seed(seed), 3.0).unwrap();
+    ///     assert_eq!(rule_1.rand_vars(), shewhart.rand_vars());
+    ///     assert_eq!(rule_1.signal(), shewhart.signal());
+    /// }
+    /// ```
+    pub fn from_scenario_seed_controlchart_rules(scenario: &Scenario, seed: Seed, rules: RunsRules) -> Result<Self, process_param::ScenarioError> {
+        let mut chart = WesternElectric::new(scenario, rules)?;
+        let (random_variables, signal) = Self::gen_random_chart(&scenario, &mut Mt64::new(seed.0), &mut chart, true, None, None)?;
+        Ok(RandomScenario{ signal, ..Self::new(Arc::new(scenario.clone()), seed, random_variables) })
+    }
+
+
+    /// Seedを指定してScenarioからEWMA管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// サンプル平均$ \bar{X}_t $のEWMA統計量
+    /// $ Z_t = \lambda \bar{X}_t + (1 - \lambda) Z_{t-1}, \ Z_0 = \mu_0 $
+    /// が管理限界
+    /// $ \mu_0 \pm 3 \frac{\sigma_0}{\sqrt{n}} \sqrt{\frac{\lambda}{2 - \lambda} \left\{ 1 - (1 - \lambda)^{2t} \right\}} $
+    /// を外れた時点で乱数生成を終了する．
+    /// 管理限界はシナリオの管理状態のパラメータ$ (\mu_0, \sigma_0^2) $から計算する．
+    /// 最初の変化点以前で管理外れ状態を検出した場合には乱数列を再生成する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `lambda` - 平滑化定数$ \lambda $（$ 0 < \lambda \leq 1 $）
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart_ewma(&scenario, Seed(42), 0.2).unwrap();
+    /// assert!(randoms.rand_vars().len() > randoms.change_points().unwrap()[0]);
+    /// // 平滑化定数が範囲外
+    /// assert!(RandomScenario::from_scenario_seed_controlchart_ewma(&scenario, Seed(42), 0.0).is_err());
+    /// ```
+    pub fn from_scenario_seed_controlchart_ewma(scenario: &Scenario, seed: Seed, lambda: f64) -> Result<Self, process_param::ScenarioError> {
+        let mut chart = Ewma::new(scenario, lambda)?;
+        let (random_variables, signal) = Self::gen_random_chart(&scenario, &mut Mt64::new(seed.0), &mut chart, true, None, None)?;
+        Ok(RandomScenario{ signal, ..Self::new(Arc::new(scenario.clone()), seed, random_variables) })
+    }
+
+
+    /// Seedを指定してScenarioからCUSUM管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// 標準化したサンプル平均$ z_t = (\bar{X}_t - \mu_0) / (\sigma_0 / \sqrt{n}) $から
+    /// 上側・下側の累積和
+    /// $ C_t^+ = \max(0, C_{t-1}^+ + z_t - k), \ C_t^- = \max(0, C_{t-1}^- - z_t - k), \ C_0^+ = C_0^- = 0 $
+    /// を計算し，いずれかが決定区間$ h $を超えた時点で乱数生成を終了する．
+    /// 管理外れ状態を検出した時点は，既存の管理図と同様に乱数列の最後の時点となる．
+    /// 最初の変化点以前で管理外れ状態を検出した場合には乱数列を再生成する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `k` - 参照値$ k $（標準化した単位）
+    /// * `h` - 決定区間$ h $（標準化した単位）
+    ///
+    /// # 使用例
+    /// 小さな平均の変化に対しては，$ \bar{X} - s $管理図よりも早く検出する．
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario_small_shift.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let tau = 20;
+    /// let num = 200;
+    /// let (mut rl_shewhart, mut rl_cusum) = (0, 0);
+    /// for seed in 0..num {
+    ///     let shewhart = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(seed)).unwrap();
+    ///     let cusum = RandomScenario::from_scenario_seed_controlchart_cusum(&scenario, Seed(seed), 0.5, 5.0).unwrap();
+    ///     rl_shewhart += shewhart.rand_vars().len().saturating_sub(tau);
+    ///     rl_cusum += cusum.rand_vars().len().saturating_sub(tau);
+    /// }
+    /// assert!(rl_cusum < rl_shewhart);
+    /// ```
+    pub fn from_scenario_seed_controlchart_cusum(scenario: &Scenario, seed: Seed, k: f64, h: f64) -> Result<Self, process_param::ScenarioError> {
+        let mut chart = Cusum::new(scenario, k, h)?;
+        let (random_variables, signal) = Self::gen_random_chart(&scenario, &mut Mt64::new(seed.0), &mut chart, true, None, None)?;
+        Ok(RandomScenario{ signal, ..Self::new(Arc::new(scenario.clone()), seed, random_variables) })
+    }
+
+
+    // 任意の管理図が管理外れ状態を検出するまで乱数を生成
+    // 手順はgen_random_controlchart_with_rngと同様
+    // `regenerate`が`false`の場合は，管理状態の区間で管理外れ状態を検出しても再生成せず，その時点までの乱数を返す
+    // `max_len`を指定した場合，最後の変化点以降でその時点数に達すると検出せずに打ち切り，シグナルは`None`とする
+    fn gen_random_chart<R: RngCore, C: ControlChart + ?Sized>(scenario: &Scenario, rng: &mut R, chart: &mut C, regenerate: bool, max_len: Option<usize>, deadline: Option<Instant>) -> Result<(Vec<Vec<<Parameter as Process>::Observation>>, Option<Signal>), ScenarioError> {
+        let (inctrl_param ,dec_param, last_cp) = scenario.decomp_exclude_last()?;
+        let n = scenario.n_as_usize()?;
+        let mut randoms: Vec<Vec<<Parameter as Process>::Observation>>;
+
+        // 管理状態の乱数列
+        loop {
+            check_deadline(deadline)?;
+            chart.reset();
+            randoms = inctrl_param.iter()
+                                  .map(|parameter| rand_norm_n(parameter, &mut *rng, n))
+                                  .collect::<Vec<Vec<<Parameter as Process>::Observation>>>();
+            let false_alarm = randoms.iter()
+                                     .enumerate()
+                                     .find_map(|(t, rands)| chart.update(rands).map(|rule| Signal{ index: t, rule }));
+            match false_alarm {
+                // 管理状態ならば現在のrandomsを利用
+                None => break,
+                // 再生成しない場合は誤警報の時点までの乱数を返す
+                Some(signal) if !regenerate => {
+                    randoms.truncate(signal.index + 1);
+                    return Ok((randoms, Some(signal)))
+                },
+                Some(_) => (),
+            }
+        }
+
+        // 最後の変化点前までの乱数生成
+        let randoms_dec = dec_param.iter()
+                                   .map(|parameter| rand_norm_n(parameter, &mut *rng, n))
+                                   .collect::<Vec<Vec<<Parameter as Process>::Observation>>>();
+        for rands in randoms_dec {
+            let alarm = chart.update(&rands);
+            randoms.push(rands);
+            if let Some(rule) = alarm {
+                // 管理外れ状態を検出した時点までの乱数を返す
+                let signal = Signal{ index: randoms.len() - 1, rule };
+                return Ok((randoms, Some(signal)))
+            }
+        }
+
+        // 最後の変化点の情報に基づいて，管理外れ状態を検出するまで乱数を生成
+        let mut ind_outctrl = 0;
+        loop {
+            ind_outctrl = ind_outctrl + 1;
+            let param_ind = match last_cp.get_param(ind_outctrl) {
+                Ok(p) => p,
+                Err(e) => return Err(ScenarioError::new(
+                    ScenarioErrorKind::OutOfRange,
+                    format!("Parameters are out of range before control chart alart.: {e}")
+                ).with_source(e)),
+            };
+            if let Some(max_len) = max_len {
+                if randoms.len() >= max_len {
+                    return Ok((randoms, None));
+                }
+            }
+            check_deadline(deadline)?;
+            let rand_ind = rand_norm_n(&param_ind, &mut *rng, n);
+            let alarm = chart.update(&rand_ind);
+            randoms.push(rand_ind);
+            if let Some(rule) = alarm {
+                // 管理外れ状態
+                let signal = Signal{ index: randoms.len() - 1, rule };
+                return Ok((randoms, Some(signal)))
+            }
+        }
+    }
+
+
+    /// Scenarioから管理図を併用した場合の複数の乱数列を生成
+    /// 
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
     /// 
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_controlchart_multiple(&scenario, 4).unwrap();
+    /// println!("{:?}", randoms);
+    /// assert_eq!(randoms.len(), 4);
+    /// for r in randoms.iter() {
+    ///     let rep = RandomScenario::from_scenario_seed_controlchart(&scenario, r.get_seed()).unwrap();
+    ///     assert_eq!(r.rand_vars(), rep.rand_vars());
+    /// }
+    ///
+    /// // 分散が負のシナリオでは，並列に生成するワーカースレッドで発生したエラーがパニックせずに返される
+    /// let negative_var = Scenario::parse_toml_str(r#"
+    /// n = 5
+    /// parameter = [{tau = 10, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = -1.0}}]
+    /// "#).unwrap();
+    /// assert!(RandomScenario::from_scenario_controlchart_multiple(&negative_var, 4).is_err());
+    /// ```
+    pub fn from_scenario_controlchart_multiple(scenario: &Scenario, num: usize) -> Result<Vec<Self>, process_param::ScenarioError> {
+        Self::from_scenario_controlchart_multiple_with(scenario, num, &GenOptions::default())
+    }
+
+
+    /// 設定を指定して，Scenarioから管理図を併用した場合の複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_controlchart_multiple`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    /// 使用例は[`RandomScenario::from_scenario_controlchart_with`]を参照．
+    ///
     /// # 引数
     /// * `scenario`- 乱数生成に用いるシナリオ
     /// * `num` - 生成する乱数列の個数
-    /// 
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    pub fn from_scenario_controlchart_multiple_with(scenario: &Scenario, num: usize, options: &GenOptions) -> Result<Vec<Self>, process_param::ScenarioError> {
+        let seeds = super::entropy_seeds(num, options.deterministic)?;
+        // 全ての乱数列で1つのシナリオを共有する
+        let shared = Arc::new(scenario.clone());
+        seeds.par_iter()
+             .map(|seed| Self::from_shared_scenario_seed_controlchart(&shared, *seed))
+             .collect()
+    }
+
+
+    /// 制限時間を指定して，Seedを指定したScenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    ///
+    /// 乱数列は[`RandomScenario::from_scenario_seed_controlchart`]と同じであり，`timeout`が経過するまでに
+    /// 管理外れ状態を検出しなかった場合は種類が[`ScenarioErrorKind::Timeout`](crate::ScenarioErrorKind::Timeout)のエラーを返す．
+    /// [`RandomScenario::from_scenario_seed_controlchart_capped`]と異なり，最初の変化点以前での乱数列の再生成にも適用する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    /// * `timeout` - 制限時間（実時間）
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// use std::time::Duration;
+    /// # use rand_scenario::ScenarioErrorKind;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed_controlchart_timeout(&scenario, Seed(42), Duration::from_secs(60)).unwrap();
+    /// let expected = RandomScenario::from_scenario_seed_controlchart(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms.rand_vars(), expected.rand_vars());
+    ///
+    /// let err = RandomScenario::from_scenario_seed_controlchart_timeout(&scenario, Seed(42), Duration::ZERO).unwrap_err();
+    /// assert_eq!(err.kind, ScenarioErrorKind::Timeout);
+    /// ```
+    pub fn from_scenario_seed_controlchart_timeout(scenario: &Scenario, seed: Seed, timeout: Duration) -> Result<Self, ScenarioError> {
+        Self::from_shared_scenario_seed_controlchart_deadline(&Arc::new(scenario.clone()), seed, Some(Instant::now() + timeout))
+    }
+
+    // 共有するシナリオから，制限時刻までに管理図が管理外れ状態を検出するまで乱数を生成
+    fn from_shared_scenario_seed_controlchart_deadline(scenario: &Arc<Scenario>, seed: Seed, deadline: Option<Instant>) -> Result<Self, ScenarioError> {
+        let random_variables = Self::gen_random_controlchart_with_rng(scenario, &mut Mt64::new(seed.0), None, deadline)?;
+        let signal = Some(Signal{ index: random_variables.len() - 1, rule: SignalRule::Limits });
+        Ok(RandomScenario{ signal, ..Self::new(Arc::clone(scenario), seed, random_variables) })
+    }
+
+
+    /// 乱数列ごとの制限時間を指定して，Scenarioから管理図を併用した場合の複数の乱数列を生成
+    ///
+    /// 各乱数列は[`RandomScenario::from_scenario_seed_controlchart_timeout`]と同様に生成する．
+    /// 制限時間を超えた乱数列があっても残りの乱数列の生成は続け，そのseed値を[`ControlChartBatch::timed_out`]に記録する．
+    /// 制限時間の超過以外のエラーが発生した場合はエラーを返す．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `timeout` - 乱数列1つあたりの制限時間（実時間）．`None`の場合は制限しない．
+    ///
+    /// # 注意
+    /// 制限時間は各乱数列の生成を開始した時点から計ります．
+    /// 実時間による制限のため，制限時間を超えるかどうかは実行環境の負荷により変わり得ます．
+    ///
     /// # 使用例
     /// ```
     /// extern crate process_param;
     /// use process_param::norm::Scenario;
+    /// use std::time::Duration;
     /// # use rand_scenario::norm::RandomScenario;
     /// let path = std::path::Path::new("test/test_scenario.toml");
     /// let scenario = Scenario::from_toml(&path).unwrap();
-    /// let randoms = RandomScenario::from_scenario_multiple(&scenario, 4).unwrap();
+    /// let batch = RandomScenario::from_scenario_controlchart_multiple_timeout(&scenario, 4, Some(Duration::from_secs(60))).unwrap();
+    /// assert_eq!(batch.randoms.len(), 4);
+    /// assert!(batch.timed_out.is_empty());
+    ///
+    /// // 全ての乱数列が制限時間を超えた場合
+    /// let batch = RandomScenario::from_scenario_controlchart_multiple_timeout(&scenario, 4, Some(Duration::ZERO)).unwrap();
+    /// assert!(batch.randoms.is_empty());
+    /// assert_eq!(batch.timed_out.len(), 4);
+    /// ```
+    pub fn from_scenario_controlchart_multiple_timeout(scenario: &Scenario, num: usize, timeout: Option<Duration>) -> Result<ControlChartBatch, ScenarioError> {
+        Self::from_scenario_controlchart_multiple_timeout_with(scenario, num, timeout, &GenOptions::default())
+    }
+
+
+    /// 設定と乱数列ごとの制限時間を指定して，Scenarioから管理図を併用した場合の複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_controlchart_multiple_timeout`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    /// 使用例は[`RandomScenario::from_scenario_controlchart_with`]を参照．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `timeout` - 乱数列1つあたりの制限時間（実時間）．`None`の場合は制限しない．
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    pub fn from_scenario_controlchart_multiple_timeout_with(scenario: &Scenario, num: usize, timeout: Option<Duration>, options: &GenOptions) -> Result<ControlChartBatch, ScenarioError> {
+        let seeds = super::entropy_seeds(num, options.deterministic)?;
+        let shared = Arc::new(scenario.clone());
+        let results = seeds.par_iter()
+                           .map(|seed| {
+                               let deadline = timeout.map(|timeout| Instant::now() + timeout);
+                               (*seed, Self::from_shared_scenario_seed_controlchart_deadline(&shared, *seed, deadline))
+                           })
+                           .collect::<Vec<(Seed, Result<Self, ScenarioError>)>>();
+        let mut batch = ControlChartBatch{ randoms: Vec::with_capacity(num), timed_out: Vec::new() };
+        for (seed, result) in results {
+            match result {
+                Ok(randoms) => batch.randoms.push(randoms),
+                Err(e) if e.kind == ScenarioErrorKind::Timeout => batch.timed_out.push(seed),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(batch)
+    }
+
+
+    /// TOMLファイルから管理図を併用した場合のRandomScenarioを作成
+    /// 
+    /// RandomScenario::to_tomlにより生成されたTOMLファイルを読み込む．
+    /// 
+    /// # 引数
+    /// * `path` - 読み込むTOMLファイルのパス
+    /// 
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_controlchart.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario_controlchart(&scenario).unwrap();
+    /// // TOMLファイルに保存
+    /// randoms.to_csv(&path_csv).unwrap();
+    /// ```
+    pub fn from_toml_controlchart<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+
+    /// TOML形式の文字列からRandScenario読み取り
+    ///
+    /// `seed`，`random_variables`，`[scenario]`以外のフィールドやセクション（`[meta]`等）は無視する．
+    /// したがって，異なるバージョンで出力したTOMLファイルも読み込める．
+    ///
+    /// # 使用例
+    /// ```
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let toml_str = r#"
+    /// seed = "42"
+    /// random_variables = [[0.5, -0.5], [1.0, -1.0]]
+    /// comment = "unknown top-level field"
+    ///
+    /// [meta]
+    /// version = "9.9.9"
+    /// created_at = "2030-01-01T00:00:00Z"
+    /// rng = "Mt64"
+    /// unknown_key = 1
+    ///
+    /// [scenario]
+    /// n = 2
+    /// parameter = [
+    ///     {tau = 2, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}},
+    /// ]
+    /// "#;
+    /// let randoms = RandomScenario::parse_toml_str(toml_str).unwrap();
+    /// assert_eq!(randoms.get_seed(), Seed(42));
+    /// assert_eq!(randoms.rand_vars(), &vec![vec![0.5, -0.5], vec![1.0, -1.0]]);
+    /// ```
+    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_toml: RandomScenarioToml = toml::from_str(&toml_str)?;
+        let seed = Seed::from_str(&file_toml.seed)?;
+        let scenario_toml = toml::to_string(&file_toml.scenario)?;
+        let scenario = Scenario::parse_toml_str(&scenario_toml)?;
+
+        Ok(RandomScenario{ derived: file_toml.derived, ..Self::new(Arc::new(scenario), seed, file_toml.random_variables) })
+    }
+
+
+    /// 乱数列をCSVとして出力
+    /// 
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    /// 
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
     /// println!("{:?}", randoms);
+    /// randoms.to_csv(&path_csv).unwrap();
+    /// ```
+    /// 
+    /// # 注意: 出力されるCSVファイルの見方
+    ///
+    /// 行方向（横）に同一時点でのn個のサンプルが並ぶ．
+    /// 列方向（縦）は，時系列の昇順に並んでいる．
+    pub fn to_csv<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        self.to_csv_delimited(path, b',')
+    }
+
+
+    /// 区切り文字を指定して乱数列を出力
+    ///
+    /// 例えば`delimiter`に`b'\t'`を指定するとTSVとして出力する．
+    /// 行と列の見方は[`RandomScenario::to_csv`]と同じ．
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    /// * `delimiter` - 区切り文字
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_tsv = std::path::Path::new("test/randoms_from_test_scenario.tsv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv_delimited(&path_tsv, b'\t').unwrap();
+    /// // 同じ区切り文字で読み出し
+    /// let mut rdr = csv::ReaderBuilder::new()
+    ///                   .delimiter(b'\t')
+    ///                   .has_headers(false)
+    ///                   .from_path(&path_tsv)
+    ///                   .unwrap();
+    /// let rows: Vec<Vec<f64>> = rdr.deserialize().map(|r| r.unwrap()).collect();
+    /// assert_eq!(&rows, randoms.rand_vars());
+    /// ```
+    pub fn to_csv_delimited<P: AsRef<Path>>(&self, path: &P, delimiter: u8) -> Result<(), Box<dyn std::error::Error>> {
+        self.to_csv_with(path, delimiter, |r| r)
+    }
+
+    // 区切り文字と各値の変換を指定して乱数列をファイルに出力
+    fn to_csv_with<P, T, F>(&self, path: &P, delimiter: u8, format: F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        P: AsRef<Path>,
+        T: Serialize,
+        F: Fn(f64) -> T,
+    {
+        self.write_delimited_with(BufWriter::new(fs::File::create(path)?), delimiter, format)
+    }
+
+    // 区切り文字を指定して任意の出力先に乱数列を書き込む
+    fn write_delimited<W: Write>(&self, writer: W, delimiter: u8) -> Result<(), Box<dyn std::error::Error>> {
+        self.write_delimited_with(writer, delimiter, |r| r)
+    }
+
+    // 区切り文字と各値の変換を指定して任意の出力先に乱数列を書き込む
+    // 欠測値（NaN）の空欄化と列数の異なる行の扱いはすべてのCSV出力でここに揃える
+    fn write_delimited_with<W, T, F>(&self, writer: W, delimiter: u8, format: F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        W: Write,
+        T: Serialize,
+        F: Fn(f64) -> T,
+    {
+        let mut wtr = csv::WriterBuilder::new()
+                          .delimiter(delimiter)
+                          .flexible(true)
+                          .from_writer(writer);
+        for rnds in self.rand_vars() {
+            wtr.serialize(csv_record_with(rnds, &format))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+
+    /// 値の精度を指定して乱数列をCSVとして出力
+    ///
+    /// 各値を型`T`（[`Precision`]を実装した`f64`または`f32`）に変換してから出力する．
+    /// 行と列の見方は[`RandomScenario::to_csv`]と同じであり，`T = f64`の場合は`to_csv`と一致する．
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 注意
+    /// 精度の低い型を指定した場合の注意は[`RandomScenario::to_csv_f32`]を参照してください．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_as_f64.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// randoms.to_csv_as::<f64, _>(&path_csv).unwrap();
+    /// assert_eq!(std::fs::read_to_string(&path_csv).unwrap(), randoms.to_csv_string().unwrap());
+    /// ```
+    pub fn to_csv_as<T: Precision, P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        self.to_csv_with(path, b',', T::from_f64)
+    }
+
+
+    /// 乱数列を単精度（f32）に変換してCSVとして出力
+    ///
+    /// 有効数字が7桁程度になるため，[`RandomScenario::to_csv`]と比べてファイルの大きさはおおよそ半分になる．
+    /// 行と列の見方は`to_csv`と同じ．
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 注意
+    /// 値は単精度に丸められるため，読み込んだ値は元の乱数列と一致しません（相対誤差は$ 10^{-7} $程度）．
+    /// 分散が非常に小さいシナリオでは平均からの偏差が丸め誤差に埋もれるため，この形式は適しません．
+    /// また，単精度の範囲（絶対値が約$ 3.4 \times 10^{38} $）を超える値は無限大となります．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_f32.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// randoms.to_csv_f32(&path_csv).unwrap();
+    /// let csv_f32 = std::fs::read_to_string(&path_csv).unwrap();
+    /// assert!(csv_f32.len() < randoms.to_csv_string().unwrap().len());
+    ///
+    /// let mut rdr = csv::ReaderBuilder::new().has_headers(false).from_path(&path_csv).unwrap();
+    /// let rows: Vec<Vec<f64>> = rdr.deserialize().map(|r| r.unwrap()).collect();
+    /// for (row, rnds) in rows.iter().zip(randoms.rand_vars()) {
+    ///     for (x, r) in row.iter().zip(rnds) {
+    ///         assert!((x - r).abs() <= 1e-6 * r.abs().max(1.0));
+    ///     }
+    /// }
+    /// ```
+    pub fn to_csv_f32<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        self.to_csv_as::<f32, P>(path)
+    }
+
+
+    /// 乱数列をCSV形式の文字列に変換
+    ///
+    /// ファイルを介さずに[`RandomScenario::to_csv`]と同じ内容を文字列として返す．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_string.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv(&path_csv).unwrap();
+    /// assert_eq!(randoms.to_csv_string().unwrap(), std::fs::read_to_string(&path_csv).unwrap());
+    /// ```
+    pub fn to_csv_string(&self) -> Result<String, ScenarioError> {
+        let mut buf = Vec::new();
+        self.write_delimited(&mut buf, b',').map_err(|e| ScenarioError::new(ScenarioErrorKind::Other, format!("Failed to write CSV: {e}")).with_source(super::into_scenario_error(e)))?;
+        String::from_utf8(buf).map_err(|e| ScenarioError::new(ScenarioErrorKind::Other, format!("CSV is not valid UTF-8: {e}")).with_source(e))
+    }
+
+
+    /// 各時点のパラメータ区間の番号を付けて乱数列をCSVとして出力
+    ///
+    /// 各行の最後の列に[`RandomScenario::true_states`]による区間の番号を追加する．
+    /// すなわち，1行は$ n $個のサンプルと区間の番号の$ n + 1 $列からなる．
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_labeled.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv_labeled(&path_csv).unwrap();
+    /// ```
+    pub fn to_csv_labeled<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(BufWriter::new(fs::File::create(path)?));
+        for (rnds, state) in self.rand_vars().iter().zip(self.true_states()?) {
+            let mut record: Vec<String> = rnds.iter().map(csv_field).collect();
+            record.push(state.to_string());
+            wtr.write_record(&record)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+
+    /// 各時点の真のパラメータを付けて乱数列をCSVとして出力
+    ///
+    /// 各行のサンプルの後に，[`RandomScenario::true_params`]による平均`mu`と分散`sigma2`の2列を追加する．
+    /// すなわち，1行は$ n $個のサンプルと$ \mu_t, \sigma_t^2 $の$ n + 2 $列からなる．
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_with_params.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv_with_params(&path_csv).unwrap();
+    /// ```
+    pub fn to_csv_with_params<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(BufWriter::new(fs::File::create(path)?));
+        for (rnds, (mu, sigma2)) in self.rand_vars().iter().zip(self.true_params()?) {
+            let mut record: Vec<String> = rnds.iter().map(csv_field).collect();
+            record.push(mu.to_string());
+            record.push(sigma2.to_string());
+            wtr.write_record(&record)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+
+    /// 各時点のサンプル平均と標準偏差をCSVとして出力
+    ///
+    /// 管理図の作成に用いる，時点`t`（0始まり），サンプル平均`xbar`，不偏標準偏差`s`の3列を出力する．
+    /// 1行目は列名のヘッダとなる．
+    /// 値は[`RandomData::sample_means`](crate::data::RandomData::sample_means)と
+    /// [`RandomData::sample_stddevs`](crate::data::RandomData::sample_stddevs)で計算する．
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 注意
+    /// サンプル・サイズ$ n = 1 $の時点（欠測値を除いたサンプルが1個以下の時点）では`s`を計算できないため，空欄となります．
+    /// 全てのサンプルが欠測の時点では`xbar`も空欄となります．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_chart_stats.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// randoms.to_csv_chart_stats(&path_csv).unwrap();
+    /// let csv = std::fs::read_to_string(&path_csv).unwrap();
+    /// let lines: Vec<&str> = csv.lines().collect();
+    /// assert_eq!(lines[0], "t,xbar,s");
+    /// assert_eq!(lines.len(), 1 + 40);
+    /// assert!(lines[1].starts_with("0,"));
+    ///
+    /// // n = 1ではsは空欄
+    /// let individuals = Scenario::parse_toml_str(r#"
+    /// n = 1
+    /// parameter = [{tau = 5, mu = {type = "Step", level = 0.0}, sigma2 = {type = "Step", level = 1.0}}]
+    /// "#).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&individuals, Seed(42)).unwrap();
+    /// randoms.to_csv_chart_stats(&path_csv).unwrap();
+    /// let csv = std::fs::read_to_string(&path_csv).unwrap();
+    /// assert!(csv.lines().skip(1).all(|line| line.ends_with(',')));
     /// ```
-    pub fn from_scenario_multiple(scenario: &Scenario, num: usize) -> Result<Vec<Self>, process_param::ScenarioError> {
-        let mut seeds = Vec::with_capacity(num);
-        let mut rng_for_seed = rand::thread_rng(); 
-        for _i in 0..num {
-            seeds.push(rng_for_seed.next_u64());
+    pub fn to_csv_chart_stats<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        let data = self.data();
+        let mut wtr = csv::Writer::from_writer(BufWriter::new(fs::File::create(path)?));
+        wtr.write_record(["t", "xbar", "s"])?;
+        for (t, (xbar, s)) in data.sample_means().iter().zip(data.sample_stddevs().iter()).enumerate() {
+            wtr.write_record(&[t.to_string(), csv_field(xbar), csv_field(s)])?;
         }
-        seeds.par_iter()
-             .map(|seed| Self::from_scenario_seed(scenario, *seed))
-             .collect()
+        wtr.flush()?;
+        Ok(())
     }
 
 
-    /// TOMLファイルからRandomScenarioを作成
-    /// 
-    /// RandomScenario::to_tomlにより生成されたTOMLファイルを読み込む．
-    /// 
+    /// 乱数列をgzipで圧縮したCSVとして出力
+    ///
+    /// 出力内容は[`RandomScenario::to_csv`]と同じで，gzip形式で圧縮する．
+    /// 拡張子は`*.csv.gz`とすることを想定している．
+    /// `gzip` featureが有効な場合のみ利用できる．
+    ///
     /// # 引数
-    /// * `path` - 読み込むTOMLファイルのパス
-    /// 
+    /// * `path` - 出力ファイルパス
+    ///
     /// # 使用例
     /// ```
     /// extern crate process_param;
     /// use process_param::norm::Scenario;
     /// # use rand_scenario::norm::RandomScenario;
     /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
-    /// let path_toml = std::path::Path::new("test/randoms_from_test_scenario.toml");
+    /// let path_gz = std::path::Path::new("test/randoms_from_test_scenario.csv.gz");
     /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
     /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
-    /// // TOMLファイルに保存
-    /// randoms.to_toml(&path_toml).unwrap();
-    /// // TOMLファイルから読み出し
-    /// let rs_read = RandomScenario::from_toml(&path_toml).unwrap();
-    /// assert_eq!(rs_read, randoms);
+    /// randoms.to_csv_gz(&path_gz).unwrap();
+    /// // 展開して行数と列数を確認
+    /// let gz = flate2::read::GzDecoder::new(std::fs::File::open(&path_gz).unwrap());
+    /// let mut rdr = csv::ReaderBuilder::new().has_headers(false).from_reader(gz);
+    /// let rows: Vec<Vec<f64>> = rdr.deserialize().map(|r| r.unwrap()).collect();
+    /// assert_eq!(rows.len(), randoms.rand_vars().len());
+    /// assert!(rows.iter().zip(randoms.rand_vars()).all(|(r, s)| r.len() == s.len()));
     /// ```
-    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
-        let file_str = fs::read_to_string(path)?;
-        Self::parse_toml_str(&file_str)
+    #[cfg(feature = "gzip")]
+    pub fn to_csv_gz<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        let gz = GzEncoder::new(BufWriter::new(fs::File::create(path)?), Compression::default());
+        let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(gz);
+        for rnds in self.rand_vars() {
+            wtr.serialize(csv_record(rnds))?;
+        }
+        wtr.flush()?;
+        wtr.into_inner()?.finish()?.flush()?;
+        Ok(())
     }
 
 
-    /// Scenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    /// 小数点以下の桁数を指定して乱数列をCSVとして出力
     ///
-    /// 管理図には$ \bar{X} $管理図とs管理図の併用を想定．
-    /// 最初の変化点以前で管理外れ状態を検出した場合には乱数列を再生成する．
+    /// 各値を小数点以下`decimals`桁に丸めて書き出す．
+    /// 例えば`decimals = 4`のとき，`1.2345678`は`1.2346`となる．
+    /// 行と列の見方は[`RandomScenario::to_csv`]と同じ．
     ///
     /// # 引数
-    /// * `scenario` - 乱数生成に用いるシナリオ
-    /// 
+    /// * `path` - 出力ファイルパス
+    /// * `decimals` - 小数点以下の桁数
+    ///
+    /// # 注意
+    /// 丸めはファイルサイズを抑えるためのものであり，乱数列の統計的性質を変えることを意図したものではない．
+    /// 既定の[`RandomScenario::to_csv`]は丸めずに全精度で出力する．
+    ///
     /// # 使用例
     /// ```
     /// extern crate process_param;
     /// use process_param::norm::Scenario;
     /// # use rand_scenario::norm::RandomScenario;
-    /// let path = std::path::Path::new("test/test_scenario.toml");
-    /// let scenario = Scenario::from_toml(&path).unwrap();
-    /// let randoms = RandomScenario::from_scenario_controlchart(&scenario);
-    /// println!("{:?}", randoms);
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_precision.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv_precision(&path_csv, 4).unwrap();
+    /// let csv_str = std::fs::read_to_string(&path_csv).unwrap();
+    /// for field in csv_str.lines().flat_map(|l| l.split(',')) {
+    ///     assert_eq!(field.split('.').nth(1).unwrap().len(), 4);
+    /// }
     /// ```
-    pub fn from_scenario_controlchart(scenario: &Scenario) -> Result<Self, process_param::ScenarioError> {
-        let seed = rand::thread_rng().next_u64();
-        Self::from_scenario_seed_controlchart(scenario, seed)
+    pub fn to_csv_precision<P: AsRef<Path>>(&self, path: &P, decimals: usize) -> Result<(), Box<dyn std::error::Error>> {
+        self.to_csv_with(path, b',', |r| format!("{:.*}", decimals, r))
     }
 
 
-    /// Seedを指定してScenarioから管理図が管理外れ状態を検出するまで乱数を生成
+    /// 乱数列をNumPyの`.npy`形式で出力
     ///
-    /// 管理図には$ \bar{X} $管理図とs管理図の併用を想定．
-    /// 最初の変化点以前で管理外れ状態を検出した場合には乱数列を再生成する．
+    /// 形状`(時点数, n)`，データ型float64（リトルエンディアン），C順序（行優先）の配列として出力する．
+    /// Pythonからは`numpy.load`で直接読み込める．
     ///
     /// # 引数
-    /// * `scenario` - 乱数生成に用いるシナリオ
-    /// * `seed` - 乱数生成に用いるseed値
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 注意
+    /// `npy` featureを有効にする必要があります．
+    /// 時点ごとにサンプル・サイズが異なる乱数列（[`RandomScenario::from_scenario_variable_n`]）は
+    /// 2次元配列とならないため，エラーとなります．
     ///
     /// # 使用例
     /// ```
+    /// # #[cfg(feature = "npy")] {
     /// extern crate process_param;
     /// use process_param::norm::Scenario;
     /// # use rand_scenario::norm::RandomScenario;
-    /// let path = std::path::Path::new("test/test_scenario.toml");
-    /// let scenario = Scenario::from_toml(&path).unwrap();
-    /// let randoms = RandomScenario::from_scenario_seed_controlchart(&scenario, 42).unwrap();
-    /// println!("{:?}", randoms);
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_npy = std::path::Path::new("test/randoms_from_test_scenario.npy");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_npy(&path_npy).unwrap();
+    ///
+    /// let bytes = std::fs::read(&path_npy).unwrap();
+    /// assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    /// let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    /// assert_eq!((10 + header_len) % 64, 0);
+    /// let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+    /// assert!(header.contains("'descr': '<f8'"));
+    /// assert!(header.contains("'shape': (40, 10)"));
+    /// assert_eq!(bytes.len(), 10 + header_len + 40 * 10 * 8);
+    /// # }
     /// ```
-    pub fn from_scenario_seed_controlchart(scenario: &Scenario, seed: Seed) -> Result<Self, process_param::ScenarioError> {
-        let random_variables = Self::gen_random_controlchart(&scenario, seed)?;
-        Ok(RandomScenario{ scenario: scenario.clone(), seed, random_variables })
-    }
- 
- 
-    // 管理図が管理外れ状態を検出するまで乱数を生成
-    fn gen_random_controlchart(scenario: &Scenario, seed: Seed) -> Result<Vec<Vec<<Parameter as Process>::Observation>>, process_param::ScenarioError> {
-        let mut rng = Mt64::new(seed);
-        let (inctrl_param ,dec_param, last_cp) = scenario.decomp_exclude_last()?;
-        let n = scenario.n_as_usize()?;
-        let mut randoms: Vec<Vec<<Parameter as Process>::Observation>>;
- 
-        // 管理状態の乱数列
-        loop {
-            randoms = inctrl_param.iter()
-                                  .map(|parameter| Parameter::rand_with_n(parameter, &mut rng, n))
-                                  .collect::<Vec<Vec<<Parameter as Process>::Observation>>>();
-            let params_dec_inctrl = match <Parameter as process_param::Mle>::mle_all(&randoms) {
-                Err(e) => return Err(process_param::ScenarioError{
-                    message: format!("Random number generation fails: {e}")
-                }),
-                Ok(pd) => pd,
-            };
-            if scenario.in_control_all(&params_dec_inctrl) {
-                // 管理状態ならば現在のrandomsを利用
-                break;
-            }
+    #[cfg(feature = "npy")]
+    pub fn to_npy<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        let (data, num_timepoints, n) = self.flatten_with_shape();
+        if self.sample_sizes().iter().any(|size| *size != n) {
+            return Err(Box::new(ScenarioError::new(
+                ScenarioErrorKind::Other,
+                "Random variables with variable sample sizes cannot be written as a 2-dimensional array."
+            )));
         }
+        // ヘッダ：マジックナンバー，バージョン，ヘッダ長を含めて64バイトの倍数となるよう空白で埋める
+        let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}", num_timepoints, n);
+        let padding = 64 - (10 + header.len() + 1) % 64;
+        header.push_str(&" ".repeat(padding % 64));
+        header.push('\n');
 
-        // 最後の変化点前までの乱数生成
-        let mut randoms_dec = dec_param.iter()
-                                       .map(|parameter| Parameter::rand_with_n(parameter, &mut rng, n))
-                                       .collect::<Vec<Vec<<Parameter as Process>::Observation>>>();
-        let params_dec = match <Parameter as process_param::Mle>::mle_all(&randoms_dec) {
-            Err(e) => return Err(process_param::ScenarioError{
-                message: format!("Random number generation fails: {e}")
-            }),
-            Ok(pd) => pd,
-        };
-        match scenario.index_out_of_control(&params_dec) {
-            None => randoms.append(&mut randoms_dec),
-            Some(i) =>  {
-                    // 管理外れ状態を検出した時点までの乱数を返す
-                    randoms.append(&mut randoms_dec[..=i].to_vec());
-                    return Ok(randoms)
-                },
-        };
-
-        // 最後の変化点の情報に基づいて，管理外れ状態を検出するまで乱数を生成
-        let mut ind_outctrl = 0;
-        loop {
-            ind_outctrl = ind_outctrl + 1;
-            let param_ind = match last_cp.get_param(ind_outctrl) {
-                Ok(p) => p,
-                Err(e) => return Err(process_param::ScenarioError{
-                    message: format!("Parameters are out of range before control chart alart.: {e}")
-                }),
-            };
-            let rand_ind = param_ind.rand_with_n(&mut rng, n);
-            let mle_ind = match <Parameter as process_param::Mle>::mle(&rand_ind) {
-                Err(e) => return Err(process_param::ScenarioError{
-                    message: format!("Random number generation fails: {e}")
-                }),
-                Ok(pd) => pd,
-            };
-            randoms.push(rand_ind);
-            if scenario.out_of_control(&mle_ind) {
-                // 管理外れ状態
-                break;
-            }
+        let mut wtr = BufWriter::new(fs::File::create(path)?);
+        wtr.write_all(b"\x93NUMPY\x01\x00")?;
+        wtr.write_all(&(header.len() as u16).to_le_bytes())?;
+        wtr.write_all(header.as_bytes())?;
+        for x in data {
+            wtr.write_all(&x.to_le_bytes())?;
         }
-        
-        Ok(randoms)
+        wtr.flush()?;
+        Ok(())
     }
 
 
-    /// Scenarioから管理図を併用した場合の複数の乱数列を生成
-    /// 
+    /// 乱数列を生のバイナリ形式（`.bin`）で出力
+    ///
+    /// テキストの解析を省き，最も速く読み込める形式で出力する．
+    /// 先頭16バイトのヘッダに時点数とサンプル・サイズ$ n $を符号なし64ビット整数で記録し，
+    /// その後に乱数列を時点の昇順に行優先で並べたf64の値が続く．
+    /// 読み込みには[`RandomData::from_bin`](crate::data::RandomData::from_bin)を用いる．
+    ///
     /// # 引数
-    /// * `scenario`- 乱数生成に用いるシナリオ
-    /// * `num` - 生成する乱数列の個数
-    /// 
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 注意
+    /// 実行環境によらず読み込めるよう，ヘッダ・値ともに常にリトルエンディアンで出力します．
+    /// シナリオとseed値は出力しません．
+    /// 時点ごとにサンプル・サイズが異なる乱数列はエラーとなります．
+    ///
     /// # 使用例
     /// ```
     /// extern crate process_param;
     /// use process_param::norm::Scenario;
-    /// # use rand_scenario::norm::RandomScenario;
-    /// let path = std::path::Path::new("test/test_scenario.toml");
-    /// let scenario = Scenario::from_toml(&path).unwrap();
-    /// let randoms = RandomScenario::from_scenario_controlchart_multiple(&scenario, 4).unwrap();
-    /// println!("{:?}", randoms);
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let path_bin = std::path::Path::new("test/randoms_from_test_scenario.bin");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// randoms.to_bin(&path_bin).unwrap();
+    ///
+    /// let bytes = std::fs::read(&path_bin).unwrap();
+    /// assert_eq!(u64::from_le_bytes(bytes[..8].try_into().unwrap()), 40);
+    /// assert_eq!(u64::from_le_bytes(bytes[8..16].try_into().unwrap()), 10);
+    /// assert_eq!(bytes.len(), 16 + 40 * 10 * 8);
     /// ```
-    pub fn from_scenario_controlchart_multiple(scenario: &Scenario, num: usize) -> Result<Vec<Self>, process_param::ScenarioError> {
-        let mut seeds = Vec::with_capacity(num);
-        let mut rng_for_seed = rand::thread_rng(); 
-        for _i in 0..num {
-            seeds.push(rng_for_seed.next_u64());
+    pub fn to_bin<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        let (data, num_timepoints, n) = self.flatten_with_shape();
+        if self.sample_sizes().iter().any(|size| *size != n) {
+            return Err(Box::new(ScenarioError::new(
+                ScenarioErrorKind::Other,
+                "Random variables with variable sample sizes cannot be written as a binary matrix."
+            )));
         }
-        seeds.par_iter()
-             .map(|seed| Self::from_scenario_seed_controlchart(scenario, *seed))
-             .collect()
+        let mut wtr = BufWriter::new(fs::File::create(path)?);
+        wtr.write_all(&(num_timepoints as u64).to_le_bytes())?;
+        wtr.write_all(&(n as u64).to_le_bytes())?;
+        for x in data {
+            wtr.write_all(&x.to_le_bytes())?;
+        }
+        wtr.flush()?;
+        Ok(())
     }
 
 
-    /// TOMLファイルから管理図を併用した場合のRandomScenarioを作成
-    /// 
-    /// RandomScenario::to_tomlにより生成されたTOMLファイルを読み込む．
-    /// 
+    /// 乱数列をParquet形式で出力
+    ///
+    /// 時点の列`t`（0始まり，UInt64）と，各サンプルの列`s1`, ..., `sn`（Float64）からなる
+    /// Apache Arrowの`RecordBatch`を作成し，Parquetファイルとして出力する．
+    /// 欠測値（NaN）はnullとして出力する．
+    ///
     /// # 引数
-    /// * `path` - 読み込むTOMLファイルのパス
-    /// 
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 注意
+    /// `parquet` featureを有効にする必要があります．
+    /// 時点ごとにサンプル・サイズが異なる乱数列はエラーとなります．
+    ///
     /// # 使用例
     /// ```
+    /// # #[cfg(feature = "parquet")] {
     /// extern crate process_param;
     /// use process_param::norm::Scenario;
+    /// use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
     /// # use rand_scenario::norm::RandomScenario;
     /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
-    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_controlchart.csv");
+    /// let path_parquet = std::path::Path::new("test/randoms_from_test_scenario.parquet");
     /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
-    /// let randoms = RandomScenario::from_scenario_controlchart(&scenario).unwrap();
-    /// // TOMLファイルに保存
-    /// randoms.to_csv(&path_csv).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_parquet(&path_parquet).unwrap();
+    ///
+    /// let builder = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path_parquet).unwrap()).unwrap();
+    /// let schema = builder.schema().clone();
+    /// assert_eq!(schema.fields().len(), 11);
+    /// assert_eq!(schema.field(0).name(), "t");
+    /// assert_eq!(schema.field(10).name(), "s10");
+    /// let num_rows: usize = builder.build().unwrap().map(|batch| batch.unwrap().num_rows()).sum();
+    /// assert_eq!(num_rows, 40);
+    /// # }
     /// ```
-    pub fn from_toml_controlchart<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
-        let file_str = fs::read_to_string(path)?;
-        Self::parse_toml_str(&file_str)
-    }
-
-
-    /// TOML形式の文字列からRandScenario読み取り
-    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let file_toml: RandomScenarioToml = toml::from_str(&toml_str)?;
-        println!("{:?}", file_toml);
-        let seed = Seed::from_str(&file_toml.seed)?;
-        let scenario_toml = toml::to_string(&file_toml.scenario)?;
-        let scenario = Scenario::parse_toml_str(&scenario_toml)?;
+    #[cfg(feature = "parquet")]
+    pub fn to_parquet<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        let n = self.rand_vars().first().map_or(0, |rnds| rnds.len());
+        if self.sample_sizes().iter().any(|size| *size != n) {
+            return Err(Box::new(ScenarioError::new(
+                ScenarioErrorKind::Other,
+                "Random variables with variable sample sizes cannot be written as a table."
+            )));
+        }
+        let mut fields = vec![Field::new("t", DataType::UInt64, false)];
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(UInt64Array::from_iter_values(0..self.rand_vars().len() as u64))];
+        for i in 0..n {
+            fields.push(Field::new(format!("s{}", i + 1), DataType::Float64, true));
+            columns.push(Arc::new(self.rand_vars()
+                                      .iter()
+                                      .map(|rnds| if rnds[i].is_nan() { None } else { Some(rnds[i]) })
+                                      .collect::<Float64Array>()));
+        }
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
 
-        Ok(RandomScenario {scenario, seed, random_variables: file_toml.random_variables})
+        let mut wtr = ArrowWriter::try_new(fs::File::create(path)?, schema, None)?;
+        wtr.write(&batch)?;
+        wtr.close()?;
+        Ok(())
     }
 
 
-    /// 乱数列をCSVとして出力
-    /// 
+    /// 乱数列をMATLABの`.mat`（Level 5 MAT-file）形式で出力
+    ///
+    /// 乱数列を`var_name`という名前の1つの倍精度行列変数として出力する．
+    /// 行列の行は時点，列はサンプルに対応し，MATLABでは`X(t, i)`が時点tのi番目のサンプルとなる（CSVと同じ向き）．
+    /// MATLABは列優先で値を格納するため，ファイル中では各サンプルの列が時系列順に並ぶ．
+    ///
     /// # 引数
     /// * `path` - 出力ファイルパス
-    /// 
+    /// * `var_name` - MATLABでの変数名
+    ///
+    /// # 注意
+    /// `mat` featureを有効にする必要があります．
+    /// 時点ごとにサンプル・サイズが異なる乱数列はエラーとなります．
+    /// `var_name`はMATLABの変数名の規則（英字で始まり，英数字と`_`のみからなる63文字以内）に従う必要があります．
+    /// 出力したファイルをscipyで読み込めることは`python test/test_mat_scipy.py`で確認できます．
+    ///
     /// # 使用例
     /// ```
-    /// extern crate process_param;
-    /// use process_param::norm::Scenario;
+    /// # #[cfg(feature = "mat")] {
     /// # use rand_scenario::norm::RandomScenario;
-    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
-    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario.csv");
-    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
-    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
-    /// println!("{:?}", randoms);
-    /// randoms.to_csv(&path_csv).unwrap();
-    /// ```
-    /// 
-    /// # 注意: 出力されるCSVファイルの見方
+    /// let path_toml = std::path::Path::new("test/test_mat_fixture.toml");
+    /// let path_mat = std::path::Path::new("test/randoms_from_test_mat_fixture.mat");
+    /// let randoms = RandomScenario::from_toml(&path_toml).unwrap();
+    /// randoms.to_mat(&path_mat, "X").unwrap();
     ///
-    /// 行方向（横）に同一時点でのn個のサンプルが並ぶ．
-    /// 列方向（縦）は，時系列の昇順に並んでいる．
-    pub fn to_csv<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
-        let mut wtr = csv::Writer::from_path(path)?;
-        for rnds in self.rand_vars() {
-            wtr.serialize(rnds)?;
+    /// // Level 5 MAT-fileの仕様に従って読み取る
+    /// let bytes = std::fs::read(&path_mat).unwrap();
+    /// let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+    /// assert_eq!(&bytes[126..128], b"IM"); // リトルエンディアン
+    /// assert_eq!(u16::from_le_bytes([bytes[124], bytes[125]]), 0x0100);
+    /// assert_eq!(u32_at(128), 14); // miMATRIX
+    /// assert_eq!(u32_at(128 + 4) as usize, bytes.len() - 136);
+    /// assert_eq!(u32_at(144) & 0xff, 6); // mxDOUBLE_CLASS
+    /// assert_eq!((u32_at(160), u32_at(164)), (3, 2)); // 3時点 × 2サンプル
+    /// assert_eq!((u32_at(168), u32_at(172)), (1, 1)); // 変数名（miINT8，1文字）
+    /// assert_eq!(bytes[176], b'X');
+    /// assert_eq!((u32_at(184), u32_at(188)), (9, 48)); // miDOUBLE，6要素
+    /// let values: Vec<f64> = bytes[192..240].chunks(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect();
+    /// // 列優先
+    /// assert_eq!(values, vec![1.0, 3.0, 5.0, 2.0, 4.0, 6.5]);
+    ///
+    /// // MATLABの変数名として無効な名前
+    /// assert!(randoms.to_mat(&path_mat, "1X").is_err());
+    /// assert!(randoms.to_mat(&path_mat, "my-var").is_err());
+    /// # }
+    /// ```
+    #[cfg(feature = "mat")]
+    pub fn to_mat<P: AsRef<Path>>(&self, path: &P, var_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let num_timepoints = self.rand_vars().len();
+        let n = self.rand_vars().first().map_or(0, |rnds| rnds.len());
+        if self.sample_sizes().iter().any(|size| *size != n) {
+            return Err(Box::new(ScenarioError::new(
+                ScenarioErrorKind::Other,
+                "Random variables with variable sample sizes cannot be written as a matrix."
+            )));
+        }
+        if !is_matlab_identifier(var_name) {
+            return Err(Box::new(ScenarioError::new(ScenarioErrorKind::Other, format!("Invalid MATLAB variable name: {var_name:?}"))));
+        }
+
+        // 行列の各要素（配列フラグ，次元，変数名，実部）
+        let mut flags = Vec::new();
+        flags.extend_from_slice(&6u32.to_le_bytes()); // mxDOUBLE_CLASS
+        flags.extend_from_slice(&0u32.to_le_bytes());
+        let mut dims = Vec::new();
+        dims.extend_from_slice(&(num_timepoints as i32).to_le_bytes());
+        dims.extend_from_slice(&(n as i32).to_le_bytes());
+        let mut real = Vec::with_capacity(num_timepoints * n * 8);
+        for i in 0..n {
+            for rnds in self.rand_vars() {
+                real.extend_from_slice(&rnds[i].to_le_bytes());
+            }
         }
+        let mut matrix = Vec::new();
+        mat_element(&mut matrix, 6, &flags); // miUINT32
+        mat_element(&mut matrix, 5, &dims); // miINT32
+        mat_element(&mut matrix, 1, var_name.as_bytes()); // miINT8
+        mat_element(&mut matrix, 9, &real); // miDOUBLE
+
+        let mut wtr = BufWriter::new(fs::File::create(path)?);
+        // ヘッダ：説明文116バイト，サブシステム・オフセット8バイト，バージョン，エンディアン
+        wtr.write_all(format!("{:<116}", "MATLAB 5.0 MAT-file, Created by: rand_scenario").as_bytes())?;
+        wtr.write_all(&[0u8; 8])?;
+        wtr.write_all(&0x0100u16.to_le_bytes())?;
+        wtr.write_all(b"IM")?;
+        let mut element = Vec::new();
+        mat_element(&mut element, 14, &matrix); // miMATRIX
+        wtr.write_all(&element)?;
         wtr.flush()?;
         Ok(())
     }
@@ -423,11 +4852,101 @@ impl RandomScenario {
     }
 
 
+    /// サンプル平均の推移をPNG画像で出力
+    ///
+    /// 各時点のサンプル平均$ \bar{X}_t $を折れ線で描き，$ \bar{X} $管理図の管理限界（赤）と真の変化点（黒の縦線）を重ねる．
+    /// 生成した乱数列を目視で簡単に確認するためのものである．
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    /// * `opts` - 画像の大きさと管理限界の幅
+    ///
+    /// # 注意
+    /// `plot` featureを有効にする必要があります．
+    ///
+    /// # 使用例
+    /// ```
+    /// # #[cfg(feature = "plot")] {
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed, PlotOptions};
+    /// let path = std::path::Path::new("test/test_scenario.toml");
+    /// let path_png = std::path::Path::new("test/randoms_from_test_scenario.png");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// randoms.plot_png(&path_png, &PlotOptions::default()).unwrap();
+    /// assert!(path_png.exists());
+    /// # }
+    /// ```
+    #[cfg(feature = "plot")]
+    pub fn plot_png<P: AsRef<Path>>(&self, path: &P, opts: &PlotOptions) -> Result<(), Box<dyn std::error::Error>> {
+        use plotters::prelude::*;
+        let means: Vec<f64> = self.rand_vars().iter().map(|rands| sample_mean(rands)).collect();
+        let limits = control_limits(&self.scenario, opts.sigma_mult)?;
+        let values = means.iter().cloned().chain([limits.lcl_xbar, limits.ucl_xbar]).filter(|v| v.is_finite());
+        let (y_min, y_max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        let margin = ((y_max - y_min) * 0.05).max(1e-9);
+        let (y_lo, y_hi) = (y_min - margin, y_max + margin);
+        let x_max = means.len().max(1) as f64;
+
+        let root = BitMapBackend::new(path.as_ref(), (opts.width, opts.height)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+                            .margin(10)
+                            .x_label_area_size(30)
+                            .y_label_area_size(50)
+                            .build_cartesian_2d(0.0..x_max, y_lo..y_hi)?;
+        chart.configure_mesh().x_desc("t").y_desc("sample mean").draw()?;
+        // 管理限界
+        for cl in [limits.lcl_xbar, limits.ucl_xbar] {
+            chart.draw_series(LineSeries::new(vec![(0.0, cl), (x_max, cl)], &RED))?;
+        }
+        // 真の変化点
+        for cp in self.change_points()? {
+            chart.draw_series(LineSeries::new(vec![(cp as f64, y_lo), (cp as f64, y_hi)], &BLACK))?;
+        }
+        // サンプル平均
+        chart.draw_series(LineSeries::new(means.iter().enumerate().map(|(t, m)| (t as f64, *m)), &BLUE))?;
+        root.present()?;
+        Ok(())
+    }
+
+
     /// TOML形式の文字列に変換
+    ///
+    /// seed値，乱数列，シナリオに加えて，来歴情報として`[meta]`セクションを出力する．
+    /// `[meta]`セクションにはクレートのバージョン（`version`），出力日時（`created_at`，UTC），
+    /// 乱数生成器の名前（`rng`），シナリオのハッシュ値（`scenario_hash`）を記録する．
+    /// `[meta]`セクションは読み込み時には無視されるため，これを持たない古いファイルも読み込める．
+    /// 加工して得た乱数列（[`RandomScenario::is_derived`]が`true`）の場合は`derived = true`も出力し，読み込み時に引き継ぐ．
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::norm::Scenario;
+    /// # use rand_scenario::norm::{RandomScenario, Seed};
+    /// let path_scenario = std::path::Path::new("test/test_scenario.toml");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let toml_str = randoms.to_toml_string();
+    /// let value: toml::Value = toml::from_str(&toml_str).unwrap();
+    /// assert_eq!(value["meta"]["rng"].as_str(), Some("Mt64"));
+    /// assert_eq!(value["meta"]["version"].as_str(), Some(env!("CARGO_PKG_VERSION")));
+    /// // 読み込むと元の乱数列に戻る
+    /// let loaded = RandomScenario::parse_toml_str(&toml_str).unwrap();
+    /// assert_eq!(loaded.rand_vars(), randoms.rand_vars());
+    /// assert!(!loaded.is_derived());
+    /// // 加工して得た乱数列であることも引き継ぐ
+    /// let thinned = randoms.downsample(4).unwrap();
+    /// let loaded = RandomScenario::parse_toml_str(&thinned.to_toml_string()).unwrap();
+    /// assert!(loaded.is_derived());
+    /// ```
     pub fn to_toml_string(&self) -> String {
         let scenario = self.scenario.to_toml_string();
         let rands = self.rands_to_toml_string();
-        format!("seed = \"{}\"\n{}\n\n[scenario]\n{}", self.get_seed(), rands, scenario)
+        let meta = toml::to_string(&MetaToml::new(&self.scenario)).unwrap();
+        let derived = if self.derived { "derived = true\n" } else { "" };
+        format!("seed = \"{}\"\n{}{}\n\n[meta]\n{}\n[scenario]\n{}", self.get_seed(), derived, rands, meta, scenario)
     }
 
 
@@ -449,10 +4968,78 @@ impl RandomScenario {
     /// randoms.to_toml(&path_toml).unwrap();
     /// ```
     pub fn to_toml<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
-        let mut wtr = fs::File::create(path)?;
+        let mut wtr = BufWriter::new(fs::File::create(path)?);
         let str_self = self.to_toml_string();
         write!(wtr, "{}", str_self)?;
         wtr.flush()?;
         Ok(())
     }
 }
+
+
+/// 時点ごとに乱数列を走査
+///
+/// 各要素は1時点分のn個のサンプルである．
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::{RandomScenario, Seed};
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+/// let mut sums = Vec::new();
+/// for row in &randoms {
+///     sums.push(row.iter().sum::<f64>());
+/// }
+/// assert_eq!(sums.len(), randoms.rand_vars().len());
+/// ```
+impl<'a> IntoIterator for &'a RandomScenario {
+    type Item = &'a Vec<<Parameter as Process>::Observation>;
+    type IntoIter = std::slice::Iter<'a, Vec<<Parameter as Process>::Observation>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.random_variables.iter()
+    }
+}
+
+
+/// 時点`t`のn個のサンプルを取得
+///
+/// `randoms[t]`は`randoms.rand_vars()[t]`と同じ．
+///
+/// # 注意
+/// `t`が時点数以上の場合はパニックします．パニックさせたくない場合は[`RandomScenario::get`]を用いてください．
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::{RandomScenario, Seed};
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+/// assert_eq!(&randoms[15], &randoms.rand_vars()[15]);
+/// assert_eq!(randoms[0].len(), 10);
+/// ```
+///
+/// ```should_panic
+/// extern crate process_param;
+/// use process_param::norm::Scenario;
+/// # use rand_scenario::norm::{RandomScenario, Seed};
+/// let path = std::path::Path::new("test/test_scenario.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+/// let _ = &randoms[40];
+/// ```
+impl std::ops::Index<usize> for RandomScenario {
+    type Output = Vec<<Parameter as Process>::Observation>;
+
+    fn index(&self, t: usize) -> &Self::Output {
+        match self.random_variables.get(t) {
+            Some(rands) => rands,
+            None => panic!("Timepoint index {} is out of range for random variables of length {}.", t, self.random_variables.len()),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! 変化点で区切った区間ごとにパラメータを持つシナリオの共通処理
+//!
+//! 指数分布，二項分布，ガンマ分布，多変量正規分布のシナリオは，いずれも区間の終端となる変化点`tau`と
+//! その区間のパラメータを並べた形式をとる．
+//! 変化点の展開，複数の乱数列の生成，CSV・TOMLの入出力をここにまとめ，
+//! 分布固有のパラメータの検査と乱数生成は各モジュールに置く．
+
+use super::{ScenarioError, ScenarioErrorKind};
+use super::norm::Seed;
+use super::par::*;
+
+extern crate serde;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use std::path::Path;
+use std::fs;
+use std::io::{BufWriter, Write};
+extern crate toml;
+
+
+// サンプル・サイズが正であり，パラメータ区間があることを検証
+pub(crate) fn validate_size(n: usize, num_segments: usize) -> Result<(), ScenarioError> {
+    if n == 0 {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Sample size n must be positive, but n = 0."));
+    }
+    if num_segments == 0 {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Scenario has no parameter."));
+    }
+    Ok(())
+}
+
+
+// 各区間の(区間長, パラメータ)を計算
+// `tau`は区間の終端となる時点を，`param`は区間のパラメータを検査した上で返す
+// 変化点が狭義単調増加でない場合はエラー
+pub(crate) fn segments<C, T, F, G>(parameter: &[C], tau: F, mut param: G) -> Result<Vec<(usize, T)>, ScenarioError>
+where
+    F: Fn(&C) -> usize,
+    G: FnMut(&C) -> Result<T, ScenarioError>,
+{
+    let mut segments = Vec::with_capacity(parameter.len());
+    let mut tau_prev = 0;
+    for cp in parameter.iter() {
+        let value = param(cp)?;
+        let tau = tau(cp);
+        if tau <= tau_prev {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Change points must be strictly increasing, but tau = {} follows {}.", tau, tau_prev)
+            ));
+        }
+        segments.push((tau - tau_prev, value));
+        tau_prev = tau;
+    }
+    Ok(segments)
+}
+
+
+// 各時点のパラメータを展開
+// 区間の扱いは`segments`と同じ
+pub(crate) fn expand<C, T, F, G>(parameter: &[C], tau: F, param: G) -> Result<Vec<T>, ScenarioError>
+where
+    T: Clone,
+    F: Fn(&C) -> usize,
+    G: FnMut(&C) -> Result<T, ScenarioError>,
+{
+    Ok(segments(parameter, tau, param)?.into_iter()
+                                       .flat_map(|(len, value)| std::iter::repeat(value).take(len))
+                                       .collect())
+}
+
+
+// エントロピー源からseed値を1つ生成
+// `deterministic`が`true`の場合はエラー（[`GenOptions::deterministic`]）
+pub(crate) fn entropy_seed(deterministic: bool) -> Result<Seed, ScenarioError> {
+    Ok(Seed(super::entropy_rng(deterministic)?.next_u64()))
+}
+
+
+// エントロピー源から`num`個のseed値を生成し，各seed値で`gen`を並列に呼び出す
+// `deterministic`の扱いは`entropy_seed`と同じ
+pub(crate) fn gen_multiple<T, F>(num: usize, deterministic: bool, gen: F) -> Result<Vec<T>, ScenarioError>
+where
+    T: Send,
+    F: Fn(Seed) -> Result<T, ScenarioError> + Sync + Send,
+{
+    let seeds = super::entropy_seeds(num, deterministic)?;
+    seeds.par_iter()
+         .map(|seed| gen(*seed))
+         .collect()
+}
+
+
+// 各時点の行を1行ずつCSVファイルに出力
+pub(crate) fn write_csv_rows<P, I, R>(path: &P, rows: I) -> Result<(), Box<dyn std::error::Error>>
+where
+    P: AsRef<Path>,
+    I: IntoIterator<Item = R>,
+    R: Serialize,
+{
+    let mut wtr = csv::Writer::from_writer(BufWriter::new(fs::File::create(path)?));
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+
+// 文字列をファイルに出力
+pub(crate) fn write_string<P: AsRef<Path>>(path: &P, s: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut wtr = BufWriter::new(fs::File::create(path)?);
+    write!(wtr, "{}", s)?;
+    wtr.flush()?;
+    Ok(())
+}
+
+
+// TOML形式のRandomScenarioを読み取り・書き込みするための構造体
+#[derive(Debug, Serialize, Deserialize)]
+struct RandomScenarioToml<S, V> {
+    seed: String, // u64からだと整数型に変換できない可能性があるため文字列として記述
+    random_variables: V,
+    scenario: S,
+}
+
+// TOML形式の文字列から(シナリオ, seed値, 乱数列)を読み取り
+// 未知のフィールドやセクションは無視する
+pub(crate) fn parse_random_toml<S, V>(toml_str: &str) -> Result<(S, Seed, V), Box<dyn std::error::Error>>
+where
+    S: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    let file_toml: RandomScenarioToml<S, V> = toml::from_str(toml_str)?;
+    let seed = file_toml.seed.parse::<Seed>()?;
+    Ok((file_toml.scenario, seed, file_toml.random_variables))
+}
+
+// シナリオ，seed値，乱数列をTOML形式の文字列に変換
+pub(crate) fn random_toml_string<S: Serialize, V: Serialize>(scenario: &S, seed: Seed, random_variables: &V) -> String {
+    let rst = RandomScenarioToml { seed: seed.to_string(), random_variables, scenario };
+    toml::to_string(&rst).unwrap()
+}
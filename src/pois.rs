@@ -0,0 +1,388 @@
+//! ポアソン分布に従う乱数生成プログラム
+//!
+//! シナリオとパラメータは`process_param`クレートの`pois`モジュール（[`Scenario`]，[`Parameter`]）を用いる．
+//! シナリオの書式は正規分布の場合と同様であり，区間の終端となる変化点`tau`とその区間での母平均`lambda`を並べる．
+
+use super::{GenOptions, ScenarioError, ScenarioErrorKind};
+
+extern crate serde;
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+extern crate toml;
+
+extern crate process_param;
+use process_param::{Process, ProcessSimulator};
+use process_param::pois::{Scenario, Parameter};
+
+pub use super::norm::Seed;
+
+
+// シナリオの検証用に，TOML形式に変換したシナリオの構成を読み取るための構造体
+#[derive(Debug, Deserialize)]
+struct ScenarioLayout {
+    n: i64,
+    parameter: Vec<ChangePointLayout>,
+}
+
+// シナリオ中の各変化点
+#[derive(Debug, Deserialize)]
+struct ChangePointLayout {
+    tau: i64,
+    lambda: toml::value::Table,
+}
+
+
+/// シナリオを検証
+///
+/// サンプル・サイズ，変化点，母平均について[`norm::validate`](crate::norm::validate)と同様の検証を行う．
+/// 以下の場合はエラーを返す．
+/// * サンプル・サイズ`n`が正でない
+/// * パラメータ区間が無い
+/// * 変化点が正でない，または狭義単調増加でない
+/// * 母平均`lambda`の`level`または`init`が正でない
+/// * 展開したパラメータ系列の長さが最後の区間の終端と一致しない
+///
+/// # 引数
+/// * `scenario` - 検証するシナリオ
+///
+/// # 使用例
+/// ```
+/// extern crate process_param;
+/// use process_param::pois::Scenario;
+/// # use rand_scenario::pois::validate;
+/// let path = std::path::Path::new("test/test_scenario_pois.toml");
+/// let scenario = Scenario::from_toml(&path).unwrap();
+/// assert!(validate(&scenario).is_ok());
+///
+/// // 母平均が負
+/// let negative_lambda = Scenario::parse_toml_str(r#"
+/// n = 5
+/// parameter = [{tau = 10, lambda = {type = "Step", level = -1.0}}]
+/// "#).unwrap();
+/// assert!(validate(&negative_lambda).is_err());
+/// ```
+pub fn validate(scenario: &Scenario) -> Result<(), ScenarioError> {
+    let layout: ScenarioLayout = toml::from_str(&scenario.to_toml_string()).map_err(|e| ScenarioError::new(
+        ScenarioErrorKind::Parse,
+        format!("Scenario layout cannot be read: {e}")
+    ).with_source(e))?;
+    if layout.n <= 0 {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, format!("Sample size n must be positive, but n = {}.", layout.n)));
+    }
+    if layout.parameter.is_empty() {
+        return Err(ScenarioError::new(ScenarioErrorKind::OutOfRange, "Scenario has no parameter."));
+    }
+
+    // 変化点
+    let mut tau_prev = 0;
+    for (i, cp) in layout.parameter.iter().enumerate() {
+        if cp.tau <= tau_prev {
+            return Err(ScenarioError::new(
+                ScenarioErrorKind::OutOfRange,
+                format!("Change points must be positive and strictly increasing, but tau_{} = {} follows {}.", i + 1, cp.tau, tau_prev)
+            ));
+        }
+        tau_prev = cp.tau;
+    }
+
+    // 母平均
+    for (i, cp) in layout.parameter.iter().enumerate() {
+        for key in ["level", "init"] {
+            if let Some(v) = cp.lambda.get(key).and_then(|v| v.as_float()) {
+                if v.is_nan() || v <= 0.0 {
+                    return Err(ScenarioError::new(
+                        ScenarioErrorKind::OutOfRange,
+                        format!("Mean must be positive, but lambda.{key} = {v} in parameter {}.", i + 1)
+                    ));
+                }
+            }
+        }
+    }
+    // 展開したパラメータ系列の長さ（時点数）と最後の区間の終端
+    let horizon = scenario.decomplession()?.len();
+    if i64::try_from(horizon) != Ok(tau_prev) {
+        return Err(ScenarioError::new(
+            ScenarioErrorKind::OutOfRange,
+            format!("Horizon {} doesn't match the end of the last parameter tau = {}.", horizon, tau_prev)
+        ));
+    }
+
+    Ok(())
+}
+
+
+/// シナリオから生成したポアソン乱数を格納
+///
+/// # 引数
+/// * `scenario` - 乱数生成に利用したシナリオ
+/// * `seed` - 乱数生成に利用したシード値
+/// * `random_variables` - 生成された乱数列
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RandomScenario {
+    scenario: Scenario,
+    seed: Seed,
+    random_variables: Vec<Vec<<Parameter as Process>::Observation>>
+}
+
+type RandValToml = Vec<Vec<<Parameter as Process>::Observation>>;
+
+// toml::to_string()用
+#[derive(Serialize, Deserialize)]
+struct StrRandValToml {
+    random_variables: RandValToml
+}
+
+// TOML形式のRandomScenarioを読み取り・書き込みするための構造体
+#[derive(Debug, Serialize, Deserialize)]
+struct RandomScenarioToml {
+    scenario: toml::value::Table,
+    seed: String, // u64からだと整数型に変換できない可能性があるため文字列として記述
+    random_variables: RandValToml,
+}
+
+extern crate rand;
+use rand::RngCore;
+extern crate rand_mt;
+use rand_mt::Mt64;
+use super::par::*;
+impl RandomScenario {
+    /// 乱数列を取得
+    pub fn rand_vars(&self) -> &Vec<Vec<<Parameter as Process>::Observation>> {
+        &self.random_variables
+    }
+
+    /// seedを取得
+    pub fn get_seed(&self) -> Seed {
+        self.seed
+    }
+
+
+    /// Scenarioから乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::pois::Scenario;
+    /// # use rand_scenario::pois::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario_pois.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario);
+    /// println!("{:?}", randoms);
+    /// ```
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, ScenarioError> {
+        Self::from_scenario_with(scenario, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario`]と同様だが，`options.deterministic`が`true`の場合は
+    /// 暗黙のエントロピーでseed値を生成せずにエラーを返す．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::pois::Scenario;
+    /// # use rand_scenario::GenOptions;
+    /// # use rand_scenario::pois::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario_pois.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let options = GenOptions { deterministic: true, ..GenOptions::default() };
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_multiple_with(&scenario, 4, &options).is_err());
+    /// assert!(RandomScenario::from_scenario_with(&scenario, &GenOptions::default()).is_ok());
+    /// ```
+    pub fn from_scenario_with(scenario: &Scenario, options: &GenOptions) -> Result<Self, ScenarioError> {
+        let seed = Seed(super::entropy_rng(options.deterministic)?.next_u64());
+        Self::from_scenario_seed(scenario, seed)
+    }
+
+    /// Seedを指定してScenarioから乱数列を生成
+    ///
+    /// 生成の前に[`validate`]でシナリオを検証する．
+    ///
+    /// # 引数
+    /// * `scenario` - 乱数生成に用いるシナリオ
+    /// * `seed` - 乱数生成に用いるseed値
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::pois::Scenario;
+    /// # use rand_scenario::pois::{RandomScenario, Seed};
+    /// let path = std::path::Path::new("test/test_scenario_pois.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// let randoms_again = RandomScenario::from_scenario_seed(&scenario, Seed(42)).unwrap();
+    /// assert_eq!(randoms, randoms_again);
+    /// ```
+    pub fn from_scenario_seed(scenario: &Scenario, seed: Seed) -> Result<Self, ScenarioError> {
+        validate(scenario)?;
+        let random_variables = Self::gen_random(&scenario, seed)?;
+        Ok(RandomScenario{ scenario: scenario.clone(), seed, random_variables })
+    }
+
+    // 乱数生成コア
+    fn gen_random(scenario: &Scenario, seed: Seed) -> Result<Vec<Vec<<Parameter as Process>::Observation>>, ScenarioError> {
+        let mut rng = Mt64::new(seed.0);
+        let dec_param = scenario.decomplession()?;
+        let n = scenario.n_as_usize()?;
+        Ok(dec_param.iter()
+                    .map(|parameter| Parameter::rand_with_n(parameter, &mut rng, n))
+                    .collect())
+    }
+
+    /// Scenarioから複数の乱数列を生成
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::pois::Scenario;
+    /// # use rand_scenario::pois::RandomScenario;
+    /// let path = std::path::Path::new("test/test_scenario_pois.toml");
+    /// let scenario = Scenario::from_toml(&path).unwrap();
+    /// let randoms = RandomScenario::from_scenario_multiple(&scenario, 4).unwrap();
+    /// assert_eq!(randoms.len(), 4);
+    /// ```
+    pub fn from_scenario_multiple(scenario: &Scenario, num: usize) -> Result<Vec<Self>, ScenarioError> {
+        Self::from_scenario_multiple_with(scenario, num, &GenOptions::default())
+    }
+
+    /// 設定を指定してScenarioから複数の乱数列を生成
+    ///
+    /// [`RandomScenario::from_scenario_multiple`]と同様だが，`options.deterministic`が`true`の場合はエラーを返す．
+    /// 使用例は[`RandomScenario::from_scenario_with`]を参照．
+    ///
+    /// # 引数
+    /// * `scenario`- 乱数生成に用いるシナリオ
+    /// * `num` - 生成する乱数列の個数
+    /// * `options` - 生成の設定（[`GenOptions::deterministic`]のみ参照する）
+    pub fn from_scenario_multiple_with(scenario: &Scenario, num: usize, options: &GenOptions) -> Result<Vec<Self>, ScenarioError> {
+        let seeds = super::entropy_seeds(num, options.deterministic)?;
+        seeds.par_iter()
+             .map(|seed| Self::from_scenario_seed(scenario, *seed))
+             .collect()
+    }
+
+
+    /// TOMLファイルからRandomScenarioを作成
+    ///
+    /// RandomScenario::to_tomlにより生成されたTOMLファイルを読み込む．
+    ///
+    /// # 引数
+    /// * `path` - 読み込むTOMLファイルのパス
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::pois::Scenario;
+    /// # use rand_scenario::pois::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario_pois.toml");
+    /// let path_toml = std::path::Path::new("test/randoms_from_test_scenario_pois.toml");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// // TOMLファイルに保存
+    /// randoms.to_toml(&path_toml).unwrap();
+    /// // TOMLファイルから読み出し
+    /// let rs_read = RandomScenario::from_toml(&path_toml).unwrap();
+    /// assert_eq!(rs_read, randoms);
+    /// ```
+    pub fn from_toml<P: AsRef<Path>>(path: &P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_str = fs::read_to_string(path)?;
+        Self::parse_toml_str(&file_str)
+    }
+
+
+    /// TOML形式の文字列からRandScenario読み取り
+    pub fn parse_toml_str(toml_str: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_toml: RandomScenarioToml = toml::from_str(&toml_str)?;
+        let seed = Seed::from_str(&file_toml.seed)?;
+        let scenario_toml = toml::to_string(&file_toml.scenario)?;
+        let scenario = Scenario::parse_toml_str(&scenario_toml)?;
+
+        Ok(RandomScenario {scenario, seed, random_variables: file_toml.random_variables})
+    }
+
+
+    /// 乱数列をCSVとして出力
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::pois::Scenario;
+    /// # use rand_scenario::pois::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario_pois.toml");
+    /// let path_csv = std::path::Path::new("test/randoms_from_test_scenario_pois.csv");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_csv(&path_csv).unwrap();
+    /// ```
+    ///
+    /// # 注意: 出力されるCSVファイルの見方
+    ///
+    /// 行方向（横）に同一時点でのn個のサンプルが並ぶ．
+    /// 列方向（縦）は，時系列の昇順に並んでいる．
+    pub fn to_csv<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        let mut wtr = csv::Writer::from_writer(BufWriter::new(fs::File::create(path)?));
+        for rnds in self.rand_vars() {
+            wtr.serialize(rnds)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+
+    fn rands_to_toml_string(&self) -> String {
+        let srvt= StrRandValToml{ random_variables: self.rand_vars().clone() };
+        toml::to_string(&srvt).unwrap()
+    }
+
+
+    /// TOML形式の文字列に変換
+    pub fn to_toml_string(&self) -> String {
+        let scenario = self.scenario.to_toml_string();
+        let rands = self.rands_to_toml_string();
+        format!("seed = \"{}\"\n{}\n\n[scenario]\n{}", self.get_seed(), rands, scenario)
+    }
+
+
+    /// 乱数列をtomlとして出力
+    ///
+    /// # 引数
+    /// * `path` - 出力ファイルパス
+    ///
+    /// # 使用例
+    /// ```
+    /// extern crate process_param;
+    /// use process_param::pois::Scenario;
+    /// # use rand_scenario::pois::RandomScenario;
+    /// let path_scenario = std::path::Path::new("test/test_scenario_pois.toml");
+    /// let path_toml = std::path::Path::new("test/randoms_from_test_scenario_pois.toml");
+    /// let scenario = Scenario::from_toml(&path_scenario).unwrap();
+    /// let randoms = RandomScenario::from_scenario(&scenario).unwrap();
+    /// randoms.to_toml(&path_toml).unwrap();
+    /// ```
+    pub fn to_toml<P: AsRef<Path>>(&self, path: &P) -> Result<(), Box<dyn std::error::Error>> {
+        let mut wtr = BufWriter::new(fs::File::create(path)?);
+        let str_self = self.to_toml_string();
+        write!(wtr, "{}", str_self)?;
+        wtr.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+//! Pythonからの乱数生成（PyO3によるバインディング）
+//!
+//! `python` featureを有効にしてビルドすると，Pythonモジュール`rand_scenario`として読み込めます．
+//! 例えば[maturin](https://github.com/PyO3/maturin)を用いて次のようにインストールします．
+//!
+//! > maturin develop --release --features extension-module
+//!
+//! Cargo.tomlでは`crate-type`を指定していないため，共有ライブラリ（cdylib）はmaturinがビルド時に指定して生成します．
+//!
+//! ```python
+//! import rand_scenario
+//! scenario = open("test/test_scenario.toml").read()
+//! randoms = rand_scenario.generate_batch(scenario, 4)
+//! # randoms[i][t][j]はi番目の乱数列の時点tにおけるj番目のサンプル
+//! ```
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+
+extern crate process_param;
+use process_param::norm::Scenario;
+
+
+/// TOML形式のシナリオから複数の乱数列を生成
+///
+/// [`crate::generate_batch`]をPythonから利用するための関数．
+/// 各乱数列は時点ごとのサンプルを並べた2次元のリストとして返す．
+///
+/// # 引数
+/// * `scenario` - 乱数生成のシナリオを記述したTOML形式の文字列
+/// * `num` - 生成する乱数列の個数
+///
+/// # 注意
+/// シナリオが不正な場合はPythonの`ValueError`を送出します．
+///
+/// # 使用例
+/// ```
+/// # #[cfg(feature = "python")] {
+/// # use rand_scenario::python::generate_batch;
+/// let scenario = std::fs::read_to_string("test/test_scenario.toml").unwrap();
+/// let randoms = generate_batch(&scenario, 4).unwrap();
+/// assert_eq!(randoms.len(), 4);
+/// assert_eq!(randoms[0].len(), 40);
+/// assert_eq!(randoms[0][0].len(), 10);
+/// assert!(generate_batch("n = ", 4).is_err());
+/// # }
+/// ```
+#[pyfunction]
+pub fn generate_batch(scenario: &str, num: usize) -> PyResult<Vec<Vec<Vec<f64>>>> {
+    let scenario = Scenario::parse_toml_str(scenario)
+                       .map_err(|e| PyValueError::new_err(format!("Invalid scenario: {e}")))?;
+    let randoms = super::generate_batch(&scenario, num)
+                      .map_err(|e| PyValueError::new_err(e.message))?;
+    Ok(randoms.iter().map(|r| r.rand_vars().clone()).collect())
+}
+
+
+// Pythonモジュールの定義
+#[pymodule]
+fn rand_scenario(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(generate_batch, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,10 @@
+# `mat` featureで出力した.matファイルを，MATLAB以外の独立した実装（scipy）で読み込めるか確認する
+# cargo test --features mat --doc to_mat を実行した後に python test/test_mat_scipy.py で実行する
+import scipy.io
+
+mat = scipy.io.loadmat("test/randoms_from_test_mat_fixture.mat")
+x = mat["X"]
+assert x.shape == (3, 2)
+assert x.tolist() == [[1.0, 2.0], [3.0, 4.0], [5.0, 6.5]]
+
+print("ok")
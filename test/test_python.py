@@ -0,0 +1,20 @@
+# `python` featureのスモークテスト
+# maturin develop --features extension-module を実行した後に python test/test_python.py で実行する
+import rand_scenario
+
+with open("test/test_scenario.toml") as f:
+    scenario = f.read()
+
+randoms = rand_scenario.generate_batch(scenario, 4)
+assert len(randoms) == 4
+assert all(len(r) == 40 for r in randoms)
+assert all(len(row) == 10 for r in randoms for row in r)
+
+try:
+    rand_scenario.generate_batch("n = ", 4)
+except ValueError:
+    pass
+else:
+    raise AssertionError("invalid scenario must raise ValueError")
+
+print("ok")